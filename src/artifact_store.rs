@@ -0,0 +1,255 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result, anyhow, bail};
+use sha2::{Digest, Sha256};
+
+use crate::lockfile::artifact_digest;
+
+/// Overrides the user-level cache location (defaults to `$XDG_CACHE_HOME/greentic/artifacts`).
+pub const CACHE_DIR_ENV: &str = "GREENTIC_DEV_CACHE_DIR";
+
+const SHA256_PREFIX: &str = "sha256:";
+
+/// User-level, content-addressed artifact cache shared by every workspace.
+///
+/// Entries live at `<root>/sha256/<hex>/<file name>`. Writers take an exclusive lock on
+/// `<root>/sha256/<hex>.lock` and publish through an atomic rename, so concurrent `component add`
+/// or `pack init` runs never observe a partially written artifact.
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct StoredArtifact {
+    /// `sha256:<hex>` digest of the stored bytes.
+    pub digest: String,
+    /// Path of the artifact file inside the store.
+    pub path: PathBuf,
+}
+
+impl StoredArtifact {
+    /// Directory holding the artifact (one per digest).
+    pub fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(&self.path)
+    }
+}
+
+impl ArtifactStore {
+    pub fn open() -> Result<Self> {
+        let root = match std::env::var_os(CACHE_DIR_ENV) {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => dirs::cache_dir()
+                .ok_or_else(|| anyhow!("unable to determine user cache directory"))?
+                .join("greentic")
+                .join("artifacts"),
+        };
+        Ok(Self::at(root))
+    }
+
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directory that holds one entry per sha256 digest.
+    pub fn entries_dir(&self) -> PathBuf {
+        self.root.join("sha256")
+    }
+
+    /// Return the cached artifact for `digest` if present and intact.
+    pub fn get(&self, digest: &str, file_name: &str) -> Result<Option<StoredArtifact>> {
+        let digest = digest.to_ascii_lowercase();
+        let Some(hex) = sha256_hex(&digest) else {
+            return Ok(None);
+        };
+        let path = self.entries_dir().join(hex).join(file_name);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let bytes =
+            fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        if artifact_digest(&bytes) != digest {
+            // Corrupted entry; the next insert rewrites it.
+            return Ok(None);
+        }
+        // Refresh the mtime so `cache gc --max-age` treats reused entries as recent.
+        if let Ok(file) = File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Ok(Some(StoredArtifact { digest, path }))
+    }
+
+    /// Verify `bytes` against the distributor-provided digest (when there is one) and store them.
+    pub fn insert(
+        &self,
+        expected_digest: Option<&str>,
+        file_name: &str,
+        bytes: &[u8],
+    ) -> Result<StoredArtifact> {
+        let digest = artifact_digest(bytes);
+        if let Some(expected) = expected_digest {
+            verify_digest(expected, &digest)?;
+        }
+        let hex = &digest[SHA256_PREFIX.len()..];
+        let entries = self.entries_dir();
+        fs::create_dir_all(&entries)
+            .with_context(|| format!("failed to create cache directory {}", entries.display()))?;
+
+        let _lock = self.lock_entry(hex)?;
+        let dir = entries.join(hex);
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let path = dir.join(file_name);
+        let intact = fs::read(&path)
+            .map(|existing| artifact_digest(&existing) == digest)
+            .unwrap_or(false);
+        if !intact {
+            let mut tmp = tempfile::NamedTempFile::new_in(&dir)
+                .with_context(|| format!("failed to create temp file in {}", dir.display()))?;
+            tmp.write_all(bytes)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            tmp.persist(&path)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        Ok(StoredArtifact { digest, path })
+    }
+
+    /// Move a downloaded file into the store after verifying it, like [`ArtifactStore::insert`].
+    /// `src` must live on the same filesystem as the store (e.g. [`ArtifactStore::partial_path`]).
+    pub fn insert_file(
+        &self,
+        expected_digest: Option<&str>,
+        file_name: &str,
+        src: &Path,
+    ) -> Result<StoredArtifact> {
+        let digest = file_digest(src)?;
+        if let Some(expected) = expected_digest
+            && let Err(err) = verify_digest(expected, &digest)
+        {
+            let _ = fs::remove_file(src);
+            return Err(err);
+        }
+        let hex = &digest[SHA256_PREFIX.len()..];
+        let entries = self.entries_dir();
+        fs::create_dir_all(&entries)
+            .with_context(|| format!("failed to create cache directory {}", entries.display()))?;
+
+        let _lock = self.lock_entry(hex)?;
+        let dir = entries.join(hex);
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let path = dir.join(file_name);
+        let intact = path.is_file() && file_digest(&path).is_ok_and(|existing| existing == digest);
+        if intact {
+            let _ = fs::remove_file(src);
+        } else {
+            fs::rename(src, &path)
+                .with_context(|| format!("failed to move download into {}", path.display()))?;
+        }
+        Ok(StoredArtifact { digest, path })
+    }
+
+    /// Directory holding interrupted downloads until they are resumed.
+    pub fn partial_dir(&self) -> PathBuf {
+        self.root.join("partial")
+    }
+
+    /// Where an in-progress download identified by `key` is written.
+    pub fn partial_path(&self, key: &str) -> PathBuf {
+        self.partial_dir().join(format!("{key}.part"))
+    }
+
+    /// Lock the in-progress download identified by `key` so only one process writes its partial
+    /// file and publishes it. The lock file stays behind for the next download of the same key.
+    pub fn lock_partial(&self, key: &str) -> Result<File> {
+        let dir = self.partial_dir();
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        lock_file(&dir.join(format!("{key}.lock")))
+    }
+
+    /// Remove an entry directory while holding its lock.
+    pub fn remove_entry(&self, dir: &Path) -> Result<()> {
+        let hex = dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow!("invalid cache entry {}", dir.display()))?;
+        let lock = self.lock_entry(hex)?;
+        fs::remove_dir_all(dir).with_context(|| format!("failed to remove {}", dir.display()))?;
+        drop(lock);
+        // Best effort: another process may already hold a fresh handle on the lock file.
+        let _ = fs::remove_file(self.lock_path(hex));
+        Ok(())
+    }
+
+    fn lock_path(&self, hex: &str) -> PathBuf {
+        self.entries_dir().join(format!("{hex}.lock"))
+    }
+
+    fn lock_entry(&self, hex: &str) -> Result<File> {
+        lock_file(&self.lock_path(hex))
+    }
+}
+
+/// Open (creating if needed) and exclusively lock `path`, blocking until the lock is free.
+pub(crate) fn lock_file(path: &Path) -> Result<File> {
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    file.lock()
+        .with_context(|| format!("failed to lock {}", path.display()))?;
+    Ok(file)
+}
+
+/// Make `dest` refer to the stored artifact, hardlinking when possible and copying otherwise
+/// (e.g. when the workspace lives on a different filesystem).
+pub fn link_into(artifact: &StoredArtifact, dest: &Path) -> Result<()> {
+    if dest.exists() {
+        fs::remove_file(dest).with_context(|| format!("failed to replace {}", dest.display()))?;
+    }
+    if fs::hard_link(&artifact.path, dest).is_ok() {
+        return Ok(());
+    }
+    fs::copy(&artifact.path, dest)
+        .map(|_| ())
+        .with_context(|| format!("failed to copy artifact to {}", dest.display()))
+}
+
+fn verify_digest(expected: &str, actual: &str) -> Result<()> {
+    let Some(rest) = expected.strip_prefix(SHA256_PREFIX) else {
+        eprintln!("warning: cannot verify artifact digest `{expected}` (only sha256 is supported)");
+        return Ok(());
+    };
+    if sha256_hex(expected).is_none() {
+        eprintln!(
+            "warning: cannot verify artifact digest `{expected}` (`{rest}` is not a sha256 hex digest)"
+        );
+        return Ok(());
+    }
+    if !expected.eq_ignore_ascii_case(actual) {
+        bail!("downloaded artifact digest mismatch: expected {expected}, got {actual}");
+    }
+    Ok(())
+}
+
+fn file_digest(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(format!("{SHA256_PREFIX}{:x}", hasher.finalize()))
+}
+
+fn sha256_hex(digest: &str) -> Option<&str> {
+    digest
+        .strip_prefix(SHA256_PREFIX)
+        .filter(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
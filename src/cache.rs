@@ -0,0 +1,459 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Serialize;
+
+use crate::artifact_store::{ArtifactStore, lock_file};
+use crate::events::{self, Event};
+use crate::lockfile::{LOCKFILE_NAME, WorkspaceLock};
+use crate::pack_init::WorkspaceManifest;
+
+/// Registry of workspace roots, kept in the store root, whose references `cache gc` honours.
+const WORKSPACES_FILE: &str = "workspaces.json";
+/// Held while `workspaces.json` is read and rewritten, so concurrent registrations are not lost.
+const WORKSPACES_LOCK: &str = "workspaces.json.lock";
+
+/// What a cache entry holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheKind {
+    /// Component artifact in the shared artifact store.
+    Component,
+    /// Pack bundle in the shared artifact store.
+    Pack,
+    /// Per-workspace `.greentic/components/` or `~/.greentic/cache/` directory written by older
+    /// releases; only ever pruned.
+    Legacy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntry {
+    pub kind: CacheKind,
+    pub path: PathBuf,
+    pub size: u64,
+    /// Seconds since the entry was last written.
+    pub age_secs: u64,
+    pub referenced: bool,
+}
+
+/// Limits applied by `cache gc`. Referenced entries are never removed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GcBudget {
+    /// Remove unreferenced entries older than this.
+    pub max_age: Option<Duration>,
+    /// Remove the oldest unreferenced entries until the cache fits in this many bytes.
+    pub max_size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcReport {
+    pub removed: Vec<CacheEntry>,
+    pub freed: u64,
+    pub remaining: u64,
+}
+
+pub fn run_ls(emit_json: bool) -> Result<()> {
+    let entries = list_entries()?;
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::to_value(&entries).context("failed to encode cache listing")?,
+        });
+        return Ok(());
+    }
+    if emit_json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).context("failed to encode cache listing")?
+        );
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("Cache is empty.");
+        return Ok(());
+    }
+    for entry in &entries {
+        println!(
+            "{:<10} {:>10} {:>6} {:<12} {}",
+            kind_label(entry.kind),
+            format_size(entry.size),
+            format_age(entry.age_secs),
+            if entry.referenced {
+                "referenced"
+            } else {
+                "unreferenced"
+            },
+            entry.path.display()
+        );
+    }
+    let total: u64 = entries.iter().map(|entry| entry.size).sum();
+    println!("{} entries, {} total", entries.len(), format_size(total));
+    Ok(())
+}
+
+pub fn run_gc(budget: GcBudget, dry_run: bool) -> Result<()> {
+    let report = gc(budget, dry_run)?;
+    if events::json() {
+        let mut data = serde_json::to_value(&report).context("failed to encode gc report")?;
+        data["dry_run"] = dry_run.into();
+        events::emit(Event::Result { data });
+        return Ok(());
+    }
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for entry in &report.removed {
+        println!(
+            "{verb} {} ({})",
+            entry.path.display(),
+            format_size(entry.size)
+        );
+    }
+    println!(
+        "{verb} {} entries, freeing {}; {} remaining",
+        report.removed.len(),
+        format_size(report.freed),
+        format_size(report.remaining)
+    );
+    Ok(())
+}
+
+pub fn run_clear(dry_run: bool) -> Result<()> {
+    let entries = list_entries()?;
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    let mut freed = 0;
+    for entry in &entries {
+        if !dry_run {
+            remove_entry(entry)?;
+        }
+        freed += entry.size;
+    }
+    // Interrupted downloads kept for resuming are not listed as entries but go too.
+    let partial = ArtifactStore::open()?.partial_dir();
+    if !dry_run && partial.is_dir() {
+        fs::remove_dir_all(&partial)
+            .with_context(|| format!("failed to remove {}", partial.display()))?;
+    }
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::json!({
+                "removed": serde_json::to_value(&entries).context("failed to encode cache listing")?,
+                "freed": freed,
+                "dry_run": dry_run,
+            }),
+        });
+        return Ok(());
+    }
+    println!(
+        "{verb} {} entries, freeing {}",
+        entries.len(),
+        format_size(freed)
+    );
+    if !dry_run && entries.iter().any(|entry| entry.referenced) {
+        println!(
+            "Referenced components were removed too; run `greentic-dev component update` to fetch them again."
+        );
+    }
+    Ok(())
+}
+
+/// Prune entries no registered workspace references. Without a budget every unreferenced entry
+/// is removed; with one, only entries past `max_age` and then the oldest entries needed to get
+/// under `max_size`.
+pub fn gc(budget: GcBudget, dry_run: bool) -> Result<GcReport> {
+    let mut entries = list_entries()?;
+    // Oldest first so the size budget evicts least recently written entries.
+    entries.sort_by(|a, b| b.age_secs.cmp(&a.age_secs));
+
+    let unbounded = budget.max_age.is_none() && budget.max_size.is_none();
+    let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+    let mut report = GcReport::default();
+    for entry in entries {
+        if entry.referenced {
+            continue;
+        }
+        let expired = budget
+            .max_age
+            .is_some_and(|max_age| entry.age_secs > max_age.as_secs());
+        let over_size = budget.max_size.is_some_and(|max_size| total > max_size);
+        if !(unbounded || expired || over_size) {
+            continue;
+        }
+        if !dry_run {
+            remove_entry(&entry)?;
+        }
+        total -= entry.size;
+        report.freed += entry.size;
+        report.removed.push(entry);
+    }
+    report.remaining = total;
+    Ok(report)
+}
+
+/// Every cache entry (one directory per cached artifact) in the shared store plus legacy caches.
+pub fn list_entries() -> Result<Vec<CacheEntry>> {
+    let store = ArtifactStore::open()?;
+    let references = References::load(&store)?;
+    let mut entries = Vec::new();
+    let mut roots = vec![store.entries_dir()];
+    roots.extend(legacy_roots()?);
+    for root in roots {
+        if !root.is_dir() {
+            continue;
+        }
+        let mut dirs = fs::read_dir(&root)
+            .with_context(|| format!("failed to read {}", root.display()))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("failed to read {}", root.display()))?;
+        dirs.sort();
+        for path in dirs.into_iter().filter(|path| path.is_dir()) {
+            let kind = if root != store.entries_dir() {
+                CacheKind::Legacy
+            } else if path.join("bundle.gtpack").exists() {
+                CacheKind::Pack
+            } else {
+                CacheKind::Component
+            };
+            let (size, modified) = dir_stats(&path)?;
+            let age_secs = SystemTime::now()
+                .duration_since(modified)
+                .unwrap_or_default()
+                .as_secs();
+            entries.push(CacheEntry {
+                kind,
+                referenced: references.contains(&path),
+                path,
+                size,
+                age_secs,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+fn legacy_roots() -> Result<Vec<PathBuf>> {
+    let workspace = std::env::current_dir().context("unable to determine workspace root")?;
+    let mut roots = vec![workspace.join(".greentic").join("components")];
+    if let Some(home) = dirs::home_dir() {
+        let home_cache = home.join(".greentic").join("cache");
+        roots.push(home_cache.join("components"));
+        roots.push(home_cache.join("packs"));
+    }
+    Ok(roots)
+}
+
+/// Record `workspace` in the store's registry so `cache gc` run from any other workspace keeps
+/// the artifacts it references. Workspaces whose manifest and lockfile are gone are dropped.
+pub fn register_workspace(workspace: &Path) -> Result<()> {
+    let store = ArtifactStore::open()?;
+    let workspace = workspace
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", workspace.display()))?;
+    let root = store.root();
+    fs::create_dir_all(root)
+        .with_context(|| format!("failed to create cache directory {}", root.display()))?;
+    let _lock = lock_file(&root.join(WORKSPACES_LOCK))?;
+    let mut workspaces = registered_workspaces(&store)?;
+    if workspaces.contains(&workspace) {
+        return Ok(());
+    }
+    workspaces.push(workspace);
+    workspaces.sort();
+
+    let path = root.join(WORKSPACES_FILE);
+    let mut tmp = tempfile::NamedTempFile::new_in(root)
+        .with_context(|| format!("failed to create temp file in {}", root.display()))?;
+    serde_json::to_writer_pretty(&mut tmp, &workspaces)
+        .context("failed to encode workspace registry")?;
+    tmp.persist(&path)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// Registered workspaces that still have a manifest or lockfile.
+fn registered_workspaces(store: &ArtifactStore) -> Result<Vec<PathBuf>> {
+    let path = store.root().join(WORKSPACES_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let workspaces: Vec<PathBuf> = serde_json::from_str(&raw)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    Ok(workspaces
+        .into_iter()
+        .filter(|workspace| {
+            workspace.join(".greentic").join("manifest.json").exists()
+                || workspace.join(LOCKFILE_NAME).exists()
+        })
+        .collect())
+}
+
+/// Paths and digests pinned by the manifest and lockfile of every registered workspace and the
+/// current one.
+struct References {
+    wasm_paths: Vec<PathBuf>,
+    /// Hex digests from the lockfiles; store entry directories are named after them.
+    digests: HashSet<String>,
+}
+
+impl References {
+    fn load(store: &ArtifactStore) -> Result<Self> {
+        let mut workspaces = registered_workspaces(store)?;
+        let current = std::env::current_dir().context("unable to determine workspace root")?;
+        if !workspaces
+            .iter()
+            .any(|workspace| same_dir(workspace, &current))
+        {
+            workspaces.push(current);
+        }
+        let mut references = Self {
+            wasm_paths: Vec::new(),
+            digests: HashSet::new(),
+        };
+        for workspace in workspaces {
+            let manifest =
+                WorkspaceManifest::load(&workspace.join(".greentic").join("manifest.json"))?;
+            let lock = WorkspaceLock::load(&workspace.join(LOCKFILE_NAME))?;
+            references.wasm_paths.extend(
+                manifest
+                    .components
+                    .iter()
+                    .map(|component| workspace.join(&component.entry.file_wasm)),
+            );
+            references.digests.extend(
+                lock.components
+                    .iter()
+                    .filter_map(|entry| entry.digest.strip_prefix("sha256:"))
+                    .map(str::to_string),
+            );
+        }
+        Ok(references)
+    }
+
+    fn contains(&self, dir: &Path) -> bool {
+        self.wasm_paths.iter().any(|path| path.starts_with(dir))
+            || dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| self.digests.contains(name))
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn dir_stats(dir: &Path) -> Result<(u64, SystemTime)> {
+    let mut size = 0;
+    let mut modified = fs::metadata(dir)
+        .and_then(|meta| meta.modified())
+        .with_context(|| format!("failed to stat {}", dir.display()))?;
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        for entry in fs::read_dir(&current)
+            .with_context(|| format!("failed to read {}", current.display()))?
+        {
+            let entry = entry.with_context(|| format!("failed to read {}", current.display()))?;
+            let meta = entry
+                .metadata()
+                .with_context(|| format!("failed to stat {}", entry.path().display()))?;
+            if meta.is_dir() {
+                stack.push(entry.path());
+                continue;
+            }
+            size += meta.len();
+            if let Ok(time) = meta.modified() {
+                modified = modified.max(time);
+            }
+        }
+    }
+    Ok((size, modified))
+}
+
+fn remove_entry(entry: &CacheEntry) -> Result<()> {
+    if entry.kind == CacheKind::Legacy {
+        return fs::remove_dir_all(&entry.path)
+            .with_context(|| format!("failed to remove {}", entry.path.display()));
+    }
+    ArtifactStore::open()?.remove_entry(&entry.path)
+}
+
+fn kind_label(kind: CacheKind) -> &'static str {
+    match kind {
+        CacheKind::Component => "component",
+        CacheKind::Pack => "pack",
+        CacheKind::Legacy => "legacy",
+    }
+}
+
+/// Parse a byte size such as `500MB`, `1.5GiB` or `4096`.
+pub fn parse_size(raw: &str) -> Result<u64> {
+    let trimmed = raw.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| anyhow!("invalid size `{raw}` (expected e.g. 500MB or 2GiB)"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "kib" => 1 << 10,
+        "m" | "mb" => 1_000_000,
+        "mib" => 1 << 20,
+        "g" | "gb" => 1_000_000_000,
+        "gib" => 1 << 30,
+        other => bail!("unknown size unit `{other}` in `{raw}`"),
+    };
+    Ok((value * multiplier as f64) as u64)
+}
+
+/// Parse an age such as `30d`, `12h`, `45m` or `90s`.
+pub fn parse_age(raw: &str) -> Result<Duration> {
+    let trimmed = raw.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let value: u64 = number
+        .parse()
+        .map_err(|_| anyhow!("invalid age `{raw}` (expected e.g. 30d or 12h)"))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        other => bail!("unknown age unit `{other}` in `{raw}` (use s, m, h, d or w)"),
+    };
+    Ok(Duration::from_secs(value * seconds))
+}
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+fn format_age(secs: u64) -> String {
+    match secs {
+        s if s < 60 * 60 => format!("{}m", s / 60),
+        s if s < 24 * 60 * 60 => format!("{}h", s / (60 * 60)),
+        s => format!("{}d", s / (24 * 60 * 60)),
+    }
+}
@@ -3,12 +3,19 @@ use std::fs;
 use anyhow::{Context, Result};
 
 use crate::cli::CborArgs;
+use crate::events::{self, Event};
 
 pub fn run(args: CborArgs) -> Result<()> {
     let data = fs::read(&args.path)
         .with_context(|| format!("failed to read CBOR file {}", args.path.display()))?;
     let value: serde_cbor::Value =
         serde_cbor::from_slice(&data).context("failed to decode CBOR payload")?;
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::to_value(&value).context("failed to render CBOR as JSON")?,
+        });
+        return Ok(());
+    }
     let rendered = serde_json::to_string_pretty(&value).context("failed to render CBOR as JSON")?;
     println!("{rendered}");
     Ok(())
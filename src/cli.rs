@@ -1,5 +1,6 @@
-use std::{ffi::OsString, path::PathBuf};
+use std::{ffi::OsString, path::PathBuf, time::Duration};
 
+use crate::events::OutputFormat;
 use crate::secrets_cli::SecretsCommand;
 use clap::{Args, Parser, Subcommand};
 
@@ -8,6 +9,12 @@ use clap::{Args, Parser, Subcommand};
 #[command(version)]
 #[command(about = "Greentic developer tooling CLI")]
 pub struct Cli {
+    /// Report which companion binary each passthrough resolved to, and from where
+    #[arg(long = "verbose")]
+    pub verbose: bool,
+    /// Output format; `json` prints one JSON event per line on stdout
+    #[arg(long = "output", value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
     #[command(subcommand)]
     pub command: Command,
 }
@@ -33,6 +40,23 @@ pub enum Command {
     Secrets(SecretsCommand),
     /// Decode a CBOR file to text
     Cbor(CborArgs),
+    /// Inspect and prune cached component and pack artifacts
+    #[command(subcommand)]
+    Cache(CacheCommand),
+    /// Local distributor tooling
+    #[command(subcommand)]
+    Distributor(DistributorCommand),
+    /// Store a distributor token in the encrypted credentials file
+    Login(LoginArgs),
+    /// Remove a stored distributor token
+    Logout(LogoutArgs),
+    /// Check and pin companion CLI versions
+    #[command(subcommand)]
+    Tools(ToolsCommand),
+    /// Explain an error or warning code (e.g. GD0101), or list all codes
+    Explain(ExplainArgs),
+    /// Check the toolchain, companion CLIs, config, distributor and cache
+    Doctor(DoctorArgs),
 }
 
 #[derive(Args, Debug, Clone)]
@@ -47,6 +71,161 @@ pub struct PassthroughArgs {
     pub args: Vec<OsString>,
 }
 
+/// `component` subcommands implemented natively by greentic-dev instead of greentic-component.
+#[derive(Parser, Debug)]
+#[command(name = "greentic-dev component", no_binary_name = true)]
+pub enum ComponentNativeCommand {
+    /// Resolve a component via the distributor and record it in the workspace
+    Add(ComponentAddArgs),
+    /// Show newer versions available for distributor-sourced workspace components
+    Outdated(ComponentOutdatedArgs),
+    /// Re-resolve workspace components and refresh the cache, manifest, and lockfile
+    Update(ComponentUpdateArgs),
+    /// Remove a component from the workspace manifest and lockfile
+    Remove(ComponentRemoveArgs),
+    /// Publish a built component to an OCI registry
+    Push(ComponentPushArgs),
+}
+
+impl ComponentNativeCommand {
+    pub const SUBCOMMANDS: &'static [&'static str] =
+        &["add", "outdated", "update", "remove", "push"];
+}
+
+#[derive(Args, Debug)]
+pub struct ComponentAddArgs {
+    /// Component coordinate (e.g. component://org/name@^1.2) or local path
+    pub coordinate: String,
+    /// Distributor profile to resolve against
+    #[arg(long = "profile")]
+    pub profile: Option<String>,
+    /// Extra request header for the distributor (repeatable; overrides the profile's headers)
+    #[arg(long = "header", value_name = "NAME: VALUE", value_parser = crate::distributor::parse_header)]
+    pub headers: Vec<(String, String)>,
+    /// Accept the license with this id without prompting (repeatable; for CI)
+    #[arg(long = "accept-license", value_name = "ID")]
+    pub accept_licenses: Vec<String>,
+    /// Fail instead of updating greentic.lock when resolution would change it
+    #[arg(long = "locked")]
+    pub locked: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ComponentOutdatedArgs {
+    /// Distributor profile to resolve against (defaults to the profile recorded in greentic.lock)
+    #[arg(long = "profile")]
+    pub profile: Option<String>,
+    /// Extra request header for the distributor (repeatable; overrides the profile's headers)
+    #[arg(long = "header", value_name = "NAME: VALUE", value_parser = crate::distributor::parse_header)]
+    pub headers: Vec<(String, String)>,
+    /// Emit the report as JSON
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ComponentUpdateArgs {
+    /// Component name to update (defaults to every distributor-sourced component)
+    pub name: Option<String>,
+    /// Distributor profile to resolve against (defaults to the profile recorded in greentic.lock)
+    #[arg(long = "profile")]
+    pub profile: Option<String>,
+    /// Extra request header for the distributor (repeatable; overrides the profile's headers)
+    #[arg(long = "header", value_name = "NAME: VALUE", value_parser = crate::distributor::parse_header)]
+    pub headers: Vec<(String, String)>,
+    /// Accept the license with this id without prompting (repeatable; for CI)
+    #[arg(long = "accept-license", value_name = "ID")]
+    pub accept_licenses: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ComponentRemoveArgs {
+    /// Component name as recorded in the workspace manifest
+    pub name: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ComponentPushArgs {
+    /// Built component (.wasm) to publish
+    #[arg(value_name = "WASM")]
+    pub artifact: PathBuf,
+    /// Target reference (e.g. oci://ghcr.io/acme/echo:1.0.0)
+    pub reference: String,
+}
+
+/// `pack` subcommands implemented natively by greentic-dev instead of greentic-pack.
+#[derive(Parser, Debug)]
+#[command(name = "greentic-dev pack", no_binary_name = true)]
+pub enum PackNativeCommand {
+    /// Run each flow node locally with its manifest limits enforced and report utilisation
+    Profile(PackProfileArgs),
+    /// Publish a built pack to an OCI registry
+    Push(PackPushArgs),
+}
+
+impl PackNativeCommand {
+    pub const SUBCOMMANDS: &'static [&'static str] = &["profile", "push"];
+}
+
+#[derive(Args, Debug)]
+pub struct PackProfileArgs {
+    /// Flow file to profile
+    #[arg(long = "flow", value_name = "PATH")]
+    pub flow: PathBuf,
+    /// Directory containing local component folders
+    #[arg(long = "component-dir", value_name = "DIR")]
+    pub component_dir: Option<PathBuf>,
+    /// Emit the profile report as JSON
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PackPushArgs {
+    /// Built pack (.gtpack) to publish
+    #[arg(value_name = "GTPACK")]
+    pub artifact: PathBuf,
+    /// Target reference (e.g. oci://ghcr.io/acme/support-pack:0.4.0)
+    pub reference: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    /// List cache entries with their size, age, and whether the workspace references them
+    Ls(CacheLsArgs),
+    /// Remove cache entries not referenced by the workspace manifest or lockfile
+    Gc(CacheGcArgs),
+    /// Remove every cache entry
+    Clear(CacheClearArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct CacheLsArgs {
+    /// Emit the listing as JSON
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct CacheGcArgs {
+    /// Only remove unreferenced entries older than this (e.g. 30d, 12h)
+    #[arg(long = "max-age", value_name = "AGE", value_parser = crate::cache::parse_age)]
+    pub max_age: Option<Duration>,
+    /// Remove the oldest unreferenced entries until the cache fits (e.g. 500MB, 2GiB)
+    #[arg(long = "max-size", value_name = "SIZE", value_parser = crate::cache::parse_size)]
+    pub max_size: Option<u64>,
+    /// Report what would be removed without deleting anything
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct CacheClearArgs {
+    /// Report what would be removed without deleting anything
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum McpCommand {
     /// Inspect MCP provider metadata
@@ -66,17 +245,137 @@ pub struct McpDoctorArgs {
 pub enum ConfigCommand {
     /// Set a key in greentic-dev config (e.g. defaults.component.org)
     Set(ConfigSetArgs),
+    /// Print the value of a key in the loaded config
+    Get(ConfigKeyArgs),
+    /// Remove a key from the loaded config
+    Unset(ConfigUnsetArgs),
+    /// List every key set in the loaded config, secrets redacted
+    List(ConfigListArgs),
+    /// Print the loaded config with secrets redacted
+    Show(ConfigShowArgs),
+    /// Check config files for unknown keys, deprecated aliases and unresolvable tokens
+    Validate(ConfigValidateArgs),
+    /// Rewrite legacy profile layouts into [distributor.profiles.*], keeping comments
+    Migrate(ConfigMigrateArgs),
+    /// Print the JSON schema of the config file
+    Schema,
 }
 
 #[derive(Args, Debug)]
 pub struct ConfigSetArgs {
     /// Config key path (e.g. defaults.component.org)
     pub key: String,
-    /// Value to assign to the key (stored as a string)
+    /// Value to assign: a string, or a TOML bool, number, array or inline table where the key
+    /// expects one
     pub value: String,
     /// Override config file path (default: $XDG_CONFIG_HOME/greentic-dev/config.toml)
     #[arg(long = "file")]
     pub file: Option<PathBuf>,
+    /// Write to the project `.greentic/config.toml` (nearest one, or in the current directory)
+    #[arg(long = "project", conflicts_with = "file")]
+    pub project: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigKeyArgs {
+    /// Config key path (e.g. defaults.component.org)
+    pub key: String,
+    /// Override config file path (default: discovered like every other command)
+    #[arg(long = "file")]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigUnsetArgs {
+    /// Config key path (e.g. defaults.component.org)
+    pub key: String,
+    /// Override config file path (default: discovered like every other command)
+    #[arg(long = "file")]
+    pub file: Option<PathBuf>,
+    /// Remove the key from the project `.greentic/config.toml` instead of the user config
+    #[arg(long = "project", conflicts_with = "file")]
+    pub project: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigListArgs {
+    /// Override config file path (default: discovered like every other command)
+    #[arg(long = "file")]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigShowArgs {
+    /// Override config file path (default: discovered like every other command)
+    #[arg(long = "file")]
+    pub file: Option<PathBuf>,
+    /// Print the effective settings, defaults included, with the source of each value
+    #[arg(long = "resolved")]
+    pub resolved: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigValidateArgs {
+    /// Validate only this file (default: the user config and the project config)
+    #[arg(long = "file")]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigMigrateArgs {
+    /// Override config file path (default: $XDG_CONFIG_HOME/greentic-dev/config.toml)
+    #[arg(long = "file")]
+    pub file: Option<PathBuf>,
+    /// Migrate the project `.greentic/config.toml` instead of the user config
+    #[arg(long = "project", conflicts_with = "file")]
+    pub project: bool,
+    /// Print the migrated config instead of writing it
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ToolsCommand {
+    /// Run `--version` on each companion CLI and compare it with [tools.*].version
+    Doctor(ToolsDoctorArgs),
+    /// Record the installed companion CLI versions in the project config
+    Pin(ToolsPinArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ToolsDoctorArgs {
+    /// Emit the report as JSON
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ToolsPinArgs {
+    /// Tools to pin (defaults to every installed companion CLI)
+    #[arg(value_name = "TOOL", value_parser = clap::builder::PossibleValuesParser::new(crate::config::COMPANION_TOOLS))]
+    pub tools: Vec<String>,
+    /// Write to this config file instead of the project `.greentic/config.toml`
+    #[arg(long = "file")]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    /// Apply safe fixes (install missing rustup targets and cargo-component, create the cache)
+    #[arg(long = "fix")]
+    pub fix: bool,
+    /// Emit the report as JSON
+    #[arg(long = "json")]
+    pub json: bool,
+    /// Distributor profile to check (defaults to the selected profile)
+    #[arg(long = "profile")]
+    pub profile: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ExplainArgs {
+    /// Code to explain; lists every code when omitted
+    pub code: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -85,3 +384,36 @@ pub struct CborArgs {
     #[arg(value_name = "PATH")]
     pub path: PathBuf,
 }
+
+#[derive(Subcommand, Debug)]
+pub enum DistributorCommand {
+    /// Serve a directory of components and packs as a local distributor
+    Serve(DistributorServeArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct DistributorServeArgs {
+    /// Registry directory holding `components/<org>/<name>/<version>/` and `packs/...`
+    #[arg(long = "root", value_name = "DIR")]
+    pub root: PathBuf,
+    /// Address to listen on
+    #[arg(long = "listen", value_name = "ADDR", default_value = "127.0.0.1:7070")]
+    pub listen: String,
+    /// Require this bearer token on every request
+    #[arg(long = "token")]
+    pub token: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct LoginArgs {
+    /// Distributor profile to store the token for (defaults to the selected profile)
+    #[arg(long = "profile")]
+    pub profile: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct LogoutArgs {
+    /// Distributor profile to remove the token for (defaults to the selected profile)
+    #[arg(long = "profile")]
+    pub profile: Option<String>,
+}
@@ -1,21 +1,413 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::cli::{ConfigCommand, ConfigSetArgs};
+use crate::cli::{
+    ConfigCommand, ConfigKeyArgs, ConfigListArgs, ConfigMigrateArgs, ConfigSetArgs, ConfigShowArgs,
+    ConfigUnsetArgs, ConfigValidateArgs,
+};
 use anyhow::{Context, Result, anyhow, bail};
-use toml_edit::{DocumentMut, Item, Table, value};
+use toml_edit::{Array, DocumentMut, Item, Key, Table, Value};
 
-use crate::config;
+use crate::config::{self, ConfigValueKind, GreenticConfig, LoadedGreenticConfig};
+use crate::config_check::{self, Severity};
+use crate::credentials;
+use crate::diagnostics;
+use crate::distributor::{self, DEFAULT_PROFILE, PROFILE_ENV};
+use crate::events::{self, Event};
 
 pub fn run(command: ConfigCommand) -> Result<()> {
     match command {
         ConfigCommand::Set(args) => set_value(&args),
+        ConfigCommand::Get(args) => get_value(&args),
+        ConfigCommand::Unset(args) => unset_value(&args),
+        ConfigCommand::List(args) => list_values(&args),
+        ConfigCommand::Show(args) => show(&args),
+        ConfigCommand::Validate(args) => validate(&args),
+        ConfigCommand::Migrate(args) => migrate(&args),
+        ConfigCommand::Schema => {
+            if events::json() {
+                events::emit(Event::Result {
+                    data: config::schema(),
+                });
+                return Ok(());
+            }
+            let schema = serde_json::to_string_pretty(&config::schema())
+                .context("failed to render config schema")?;
+            println!("{schema}");
+            Ok(())
+        }
+    }
+}
+
+fn show(args: &ConfigShowArgs) -> Result<()> {
+    if events::json() {
+        let data = if args.resolved {
+            let settings = resolved_settings(&load(args.file.as_deref())?)?;
+            serde_json::json!({
+                "settings": settings
+                    .iter()
+                    .map(|setting| serde_json::json!({
+                        "key": setting.key,
+                        "value": setting.value,
+                        "source": setting.source,
+                    }))
+                    .collect::<Vec<_>>(),
+            })
+        } else {
+            serde_json::json!({ "config": render_show(args.file.as_deref())? })
+        };
+        events::emit(Event::Result { data });
+        return Ok(());
+    }
+    if args.resolved {
+        print!("{}", render_resolved(args.file.as_deref())?);
+    } else {
+        print!("{}", render_show(args.file.as_deref())?);
+    }
+    Ok(())
+}
+
+/// Render each loaded config layer with literal tokens, passwords and authorization headers
+/// replaced by `<redacted>`, followed by the profiles that have a stored token.
+fn render_show(file: Option<&Path>) -> Result<String> {
+    let loaded = load(file)?;
+    let mut out = String::new();
+    match &loaded.loaded_from {
+        Some(path) => out.push_str(&format!(
+            "# user config: {}\n{}",
+            path.display(),
+            redacted_document(path)?
+        )),
+        None => out.push_str("# user config: none found\n"),
+    }
+    if let Some(path) = &loaded.project_path {
+        out.push_str(&format!(
+            "\n# project config: {}\n# merged over the user config: tables merge key by key, other values replace\n{}",
+            path.display(),
+            redacted_document(path)?
+        ));
+    }
+
+    let stored = credentials::stored_profiles()?;
+    if !stored.is_empty() {
+        out.push_str(&format!(
+            "\n# stored tokens ({})\n",
+            credentials::credentials_path()?.display()
+        ));
+        for (profile, base_url) in stored {
+            let base_url = base_url
+                .as_deref()
+                .unwrap_or("no URL recorded; run `login` again");
+            out.push_str(&format!("# {profile} = <redacted> ({base_url})\n"));
+        }
+    }
+    Ok(out)
+}
+
+fn redacted_document(path: &Path) -> Result<DocumentMut> {
+    let mut doc = read_document(path)?;
+    for (key, item) in doc.as_table_mut().iter_mut() {
+        redact_item(key.get(), item);
+    }
+    Ok(doc)
+}
+
+/// One effective setting for `config show --resolved`.
+struct Setting {
+    key: String,
+    value: String,
+    source: String,
+}
+
+impl Setting {
+    fn new(key: impl Into<String>, value: impl Into<Value>, source: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into().to_string(),
+            source: source.into(),
+        }
+    }
+}
+
+/// Render the effective settings, each followed by where it came from: the user or project config
+/// file, an env var, the credentials file or a built-in default.
+fn render_resolved(file: Option<&Path>) -> Result<String> {
+    let loaded = load(file)?;
+    let mut out = match &loaded.loaded_from {
+        Some(path) => format!("# user config: {}\n", path.display()),
+        None => "# user config: none found\n".to_string(),
+    };
+    if let Some(path) = &loaded.project_path {
+        out.push_str(&format!(
+            "# project config: {} (merged over the user config)\n",
+            path.display()
+        ));
+    }
+    out.push_str(&format!(
+        "# searched: {}\n",
+        loaded
+            .attempted_paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+
+    let settings = resolved_settings(&loaded)?;
+    let width = settings
+        .iter()
+        .map(|setting| setting.key.len() + setting.value.len() + 3)
+        .max()
+        .unwrap_or(0);
+    for setting in settings {
+        let line = format!("{} = {}", setting.key, setting.value);
+        out.push_str(&format!("{line:<width$}  # {}\n", setting.source));
+    }
+    Ok(out)
+}
+
+fn resolved_settings(loaded: &LoadedGreenticConfig) -> Result<Vec<Setting>> {
+    let cfg: &GreenticConfig = &loaded.config;
+    let mut settings = Vec::new();
+
+    for name in config::COMPANION_TOOLS {
+        let Some(entry) = cfg.tools.entry(name) else {
+            continue;
+        };
+        if let Some(path) = &entry.path {
+            settings.push(Setting::new(
+                format!("tools.{name}.path"),
+                path.display().to_string(),
+                file_source(loaded, &[&["tools", name, "path"]]),
+            ));
+        }
+        if let Some(version) = &entry.version {
+            settings.push(Setting::new(
+                format!("tools.{name}.version"),
+                version.to_string(),
+                file_source(loaded, &[&["tools", name, "version"]]),
+            ));
+        }
+    }
+    let defaults = &cfg.defaults.component;
+    for (field, value) in [("org", &defaults.org), ("template", &defaults.template)] {
+        if let Some(value) = value {
+            settings.push(Setting::new(
+                format!("defaults.component.{field}"),
+                value.as_str(),
+                file_source(loaded, &[&["defaults", "component", field]]),
+            ));
+        }
+    }
+    let default_profile_source = file_source(loaded, &[&["distributor", "default_profile"]]);
+
+    let configured = cfg.distributor_profiles();
+    let mut profiles = configured.clone().into_iter().collect::<Vec<_>>();
+    let default_profile = match (std::env::var(PROFILE_ENV), &cfg.distributor.default_profile) {
+        (Ok(name), _) if !name.is_empty() => Setting::new(
+            "distributor.default_profile",
+            name,
+            format!("env {PROFILE_ENV}"),
+        ),
+        (_, Some(config::DefaultProfileSelection::Name(name))) => Setting::new(
+            "distributor.default_profile",
+            name.as_str(),
+            &default_profile_source,
+        ),
+        (_, Some(config::DefaultProfileSelection::Inline(inline))) => {
+            let name = inline
+                .name
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+            profiles.push((name.clone(), inline.clone()));
+            Setting::new("distributor.default_profile", name, &default_profile_source)
+        }
+        (_, None) => Setting::new("distributor.default_profile", DEFAULT_PROFILE, "default"),
+    };
+    settings.push(default_profile);
+
+    let stored = credentials::stored_profiles()?;
+    let credentials_source = if stored.is_empty() {
+        String::new()
+    } else {
+        credentials::credentials_path()?.display().to_string()
+    };
+    profiles.sort_by(|a, b| a.0.cmp(&b.0));
+    for (name, profile) in profiles {
+        let prefix = format!(
+            "distributor.profiles.{}",
+            Key::new(name.as_str()).display_repr()
+        );
+        let inline = !configured.contains_key(&name);
+        let source = |field: &[&str]| {
+            if inline {
+                return default_profile_source.clone();
+            }
+            let paths = [
+                [&["distributor", "profiles", name.as_str()][..], field].concat(),
+                [&["distributor", name.as_str()][..], field].concat(),
+                [&["profiles", name.as_str()][..], field].concat(),
+            ];
+            file_source(loaded, &paths.iter().map(Vec::as_slice).collect::<Vec<_>>())
+        };
+        let mut field = |field: &str, value: &Option<String>, default: &str| {
+            let (value, source) = match value {
+                Some(value) => (value.as_str(), source(&[field])),
+                None => (default, "default".to_string()),
+            };
+            settings.push(Setting::new(format!("{prefix}.{field}"), value, source));
+        };
+        field(
+            "base_url",
+            &profile.base_url.clone().or(profile.url.clone()),
+            distributor::DEFAULT_BASE_URL,
+        );
+        field(
+            "tenant_id",
+            &profile.tenant_id,
+            distributor::DEFAULT_TENANT_ID,
+        );
+        field(
+            "environment_id",
+            &profile.environment_id,
+            distributor::DEFAULT_ENVIRONMENT_ID,
+        );
+        if let Some(token) = &profile.token {
+            settings.push(Setting::new(
+                format!("{prefix}.token"),
+                credentials::redact_token(token),
+                source(&["token"]),
+            ));
+        } else if stored.get(&name).is_some_and(|base_url| {
+            base_url.as_deref() == Some(distributor::profile_base_url(&profile).as_str())
+        }) {
+            settings.push(Setting::new(
+                format!("{prefix}.token"),
+                "<redacted>",
+                &credentials_source,
+            ));
+        }
+        let mut headers = profile.headers.iter().flatten().collect::<Vec<_>>();
+        headers.sort();
+        for (header, value) in headers {
+            settings.push(Setting::new(
+                format!(
+                    "{prefix}.headers.{}",
+                    Key::new(header.as_str()).display_repr()
+                ),
+                redact_if_secret(header, value),
+                source(&["headers", header.as_str()]),
+            ));
+        }
+    }
+
+    let mut hooks = cfg.hooks.iter().collect::<Vec<_>>();
+    hooks.sort_by(|a, b| a.0.cmp(b.0));
+    for (command, entry) in hooks {
+        let prefix = format!("hooks.{}", Key::new(command.as_str()).display_repr());
+        for (phase, commands) in [("pre", &entry.pre), ("post", &entry.post)] {
+            if !commands.is_empty() {
+                settings.push(Setting::new(
+                    format!("{prefix}.{phase}"),
+                    commands.iter().collect::<Array>(),
+                    file_source(loaded, &[&["hooks", command, phase]]),
+                ));
+            }
+        }
+    }
+
+    let mut registries = cfg.oci.registries.iter().collect::<Vec<_>>();
+    registries.sort_by(|a, b| a.0.cmp(b.0));
+    for (host, registry) in registries {
+        let prefix = format!("oci.registries.{}", Key::new(host.as_str()).display_repr());
+        let source = |field: &str| file_source(loaded, &[&["oci", "registries", host, field]]);
+        if let Some(username) = &registry.username {
+            settings.push(Setting::new(
+                format!("{prefix}.username"),
+                username.as_str(),
+                source("username"),
+            ));
+        }
+        if let Some(password) = &registry.password {
+            settings.push(Setting::new(
+                format!("{prefix}.password"),
+                credentials::redact_token(password),
+                source("password"),
+            ));
+        }
+        settings.push(Setting::new(
+            format!("{prefix}.insecure"),
+            registry.insecure,
+            source("insecure"),
+        ));
+    }
+    Ok(settings)
+}
+
+/// The project config when it sets any of `paths`, otherwise the user config.
+fn file_source(loaded: &LoadedGreenticConfig, paths: &[&[&str]]) -> String {
+    let file = if paths.iter().any(|path| loaded.project_sets(path)) {
+        &loaded.project_path
+    } else {
+        &loaded.loaded_from
+    };
+    file.as_ref()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default()
+}
+
+fn is_secret_key(key: &str) -> bool {
+    ["token", "password", "authorization"]
+        .iter()
+        .any(|secret| key.eq_ignore_ascii_case(secret))
+}
+
+fn redact_if_secret(key: &str, value: &str) -> String {
+    if is_secret_key(key) {
+        credentials::redact_token(value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn redact_item(key: &str, item: &mut Item) {
+    match item {
+        Item::Table(table) => {
+            for (key, item) in table.iter_mut() {
+                redact_item(key.get(), item);
+            }
+        }
+        Item::ArrayOfTables(tables) => {
+            for table in tables.iter_mut() {
+                for (key, item) in table.iter_mut() {
+                    redact_item(key.get(), item);
+                }
+            }
+        }
+        Item::Value(value) => redact_value(key, value),
+        Item::None => {}
+    }
+}
+
+fn redact_value(key: &str, value: &mut Value) {
+    if let Value::InlineTable(table) = value {
+        for (key, value) in table.iter_mut() {
+            redact_value(key.get(), value);
+        }
+    } else if let Value::String(raw) = value
+        && is_secret_key(key)
+    {
+        let redacted = credentials::redact_token(raw.value());
+        let decor = raw.decor().clone();
+        *value = Value::from(redacted);
+        *value.decor_mut() = decor;
     }
 }
 
 fn set_value(args: &ConfigSetArgs) -> Result<()> {
     let path = match &args.file {
         Some(path) => path.clone(),
+        None if args.project => project_config_target()?,
         None => config::config_path().ok_or_else(|| {
             anyhow!("failed to resolve default config path (no home directory found)")
         })?,
@@ -24,27 +416,318 @@ fn set_value(args: &ConfigSetArgs) -> Result<()> {
     ensure_parent(&path)?;
 
     let mut doc = if path.exists() {
-        let raw = fs::read_to_string(&path)
-            .with_context(|| format!("failed to read {}", path.display()))?;
-        if raw.trim().is_empty() {
-            DocumentMut::new()
-        } else {
-            raw.parse::<DocumentMut>()
-                .with_context(|| format!("failed to parse {}", path.display()))?
-        }
+        read_document(&path)?
     } else {
         DocumentMut::new()
     };
 
     apply_key(&mut doc, &args.key, &args.value)?;
+    toml::from_str::<GreenticConfig>(&doc.to_string())
+        .with_context(|| format!("`{}` would make the config invalid", args.key))?;
+
+    fs::write(&path, doc.to_string())
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    events::artifact("config", &path, None);
+    if !events::json() {
+        println!("Updated {}", path.display());
+    }
+    Ok(())
+}
+
+/// Validate the user and project config files without loading them, so a config that other
+/// commands reject can still be diagnosed.
+fn validate(args: &ConfigValidateArgs) -> Result<()> {
+    let layers = match &args.file {
+        Some(path) => vec![path.clone()],
+        None => {
+            let user = config::resolve_config_path(None).selected;
+            let project = config::find_project_config(user.as_deref());
+            user.into_iter().chain(project).collect()
+        }
+    };
+    if layers.is_empty() {
+        bail!("no config file found; create one with `greentic-dev config set`");
+    }
+    let mut errors = 0;
+    for path in layers {
+        let findings = config_check::validate_file(&path)?;
+        if findings.is_empty() && !events::json() {
+            println!("{}: ok", path.display());
+        }
+        for finding in &findings {
+            if !events::json() {
+                println!("{}: {finding}", path.display());
+                continue;
+            }
+            let message = format!("{}: {}: {}", path.display(), finding.key, finding.message);
+            match finding.severity {
+                Severity::Error => events::emit(Event::Error {
+                    code: diagnostics::CONFIG_INVALID,
+                    message: &message,
+                    help: Some("run `greentic-dev explain GD0501`"),
+                }),
+                Severity::Warning => events::warning(diagnostics::CONFIG_DEPRECATED, &message),
+            }
+        }
+        errors += findings
+            .iter()
+            .filter(|finding| finding.severity == Severity::Error)
+            .count();
+    }
+    if errors > 0 {
+        bail!("config validation found {errors} error(s)");
+    }
+    Ok(())
+}
+
+fn migrate(args: &ConfigMigrateArgs) -> Result<()> {
+    let path = match &args.file {
+        Some(path) => path.clone(),
+        None if args.project => {
+            let user = config::resolve_config_path(None).selected;
+            config::find_project_config(user.as_deref()).ok_or_else(|| {
+                anyhow!(
+                    "no project config ({}) found in the current directory or its parents",
+                    config::PROJECT_CONFIG
+                )
+            })?
+        }
+        None => config::resolve_config_path(None)
+            .selected
+            .ok_or_else(|| anyhow!("no config file found; nothing to migrate"))?,
+    };
+    let mut doc = read_document(&path)?;
+    let changes = config_check::migrate_document(&mut doc)?;
+    if events::json() {
+        if !args.dry_run && !changes.is_empty() {
+            fs::write(&path, doc.to_string())
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            events::artifact("config", &path, None);
+        }
+        let mut data = serde_json::json!({ "changes": changes });
+        if args.dry_run {
+            data["document"] = doc.to_string().into();
+        }
+        events::emit(Event::Result { data });
+        return Ok(());
+    }
+    if args.dry_run {
+        for change in &changes {
+            eprintln!("{change}");
+        }
+        print!("{doc}");
+        return Ok(());
+    }
+    if changes.is_empty() {
+        println!("{} is already up to date", path.display());
+        return Ok(());
+    }
+    fs::write(&path, doc.to_string())
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    for change in &changes {
+        println!("{change}");
+    }
+    println!("Migrated {}", path.display());
+    Ok(())
+}
+
+fn get_value(args: &ConfigKeyArgs) -> Result<()> {
+    let (sources, doc) = effective_document(args.file.as_deref())?;
+    let keys = parse_key(&args.key)?;
+    let item = keys
+        .iter()
+        .try_fold(doc.as_item(), |item, key| item.get(key.get()))
+        .filter(|item| !item.is_none())
+        .ok_or_else(|| anyhow!("`{}` is not set in {sources}", args.key))?;
+    if events::json() {
+        let value = match item {
+            Item::Value(Value::String(value)) => value.value().to_string(),
+            Item::Value(value) => value.to_string().trim().to_string(),
+            other => other.to_string(),
+        };
+        events::emit(Event::Result {
+            data: serde_json::json!({ "key": args.key, "value": value }),
+        });
+        return Ok(());
+    }
+    match item {
+        Item::Value(Value::String(value)) => println!("{}", value.value()),
+        Item::Value(value) => println!("{}", value.to_string().trim()),
+        other => print!("{other}"),
+    }
+    Ok(())
+}
 
+fn unset_value(args: &ConfigUnsetArgs) -> Result<()> {
+    let loaded = load(args.file.as_deref())?;
+    let path = if args.project {
+        loaded.project_path.clone().ok_or_else(|| {
+            anyhow!(
+                "no project config ({}) found in the current directory or its parents",
+                config::PROJECT_CONFIG
+            )
+        })?
+    } else {
+        loaded.loaded_from.clone().ok_or_else(|| {
+            anyhow!("no config file found; create one with `greentic-dev config set`")
+        })?
+    };
+    let mut doc = read_document(&path)?;
+    let keys = parse_key(&args.key)?;
+    let (last, parents) = keys
+        .split_last()
+        .expect("parse_key returns at least one key");
+    let removed = parents
+        .iter()
+        .try_fold(doc.as_item_mut(), |item, key| item.get_mut(key.get()))
+        .and_then(|item| item.as_table_like_mut())
+        .and_then(|table| table.remove(last.get()));
+    if removed.is_none() {
+        let segments = keys.iter().map(Key::get).collect::<Vec<_>>();
+        if !args.project && loaded.project_sets(&segments) {
+            bail!(
+                "`{}` is not set in {} but in the project config; rerun with --project",
+                args.key,
+                path.display()
+            );
+        }
+        bail!("`{}` is not set in {}", args.key, path.display());
+    }
     fs::write(&path, doc.to_string())
         .with_context(|| format!("failed to write {}", path.display()))?;
-    println!("Updated {}", path.display());
+    events::artifact("config", &path, None);
+    if !events::json() {
+        println!("Removed {} from {}", args.key, path.display());
+    }
     Ok(())
 }
 
-fn ensure_parent(path: &Path) -> Result<()> {
+fn list_values(args: &ConfigListArgs) -> Result<()> {
+    let (_, doc) = effective_document(args.file.as_deref())?;
+    let mut entries = Vec::new();
+    let mut prefix = Vec::new();
+    for (key, item) in doc.as_table().iter() {
+        collect_item(&mut prefix, key, item, &mut entries);
+    }
+    if events::json() {
+        let values: serde_json::Map<String, serde_json::Value> = entries
+            .into_iter()
+            .map(|(key, value)| (key, value.into()))
+            .collect();
+        events::emit(Event::Result {
+            data: values.into(),
+        });
+        return Ok(());
+    }
+    for (key, value) in entries {
+        println!("{key} = {value}");
+    }
+    Ok(())
+}
+
+/// Flatten `item` into `dotted.key = value` pairs, redacting literal secrets.
+fn collect_item(prefix: &mut Vec<String>, key: &str, item: &Item, out: &mut Vec<(String, String)>) {
+    prefix.push(Key::new(key).display_repr().into_owned());
+    match item {
+        Item::Table(table) => {
+            for (key, item) in table.iter() {
+                collect_item(prefix, key, item, out);
+            }
+        }
+        Item::Value(value) => collect_value(prefix, key, value, out),
+        Item::ArrayOfTables(tables) => out.push((
+            prefix.join("."),
+            Value::Array(tables.clone().into_array()).to_string(),
+        )),
+        Item::None => {}
+    }
+    prefix.pop();
+}
+
+fn collect_value(
+    prefix: &mut Vec<String>,
+    key: &str,
+    value: &Value,
+    out: &mut Vec<(String, String)>,
+) {
+    match value {
+        Value::InlineTable(table) => {
+            for (key, value) in table.iter() {
+                prefix.push(Key::new(key).display_repr().into_owned());
+                collect_value(prefix, key, value, out);
+                prefix.pop();
+            }
+        }
+        Value::String(raw) if is_secret_key(key) => out.push((
+            prefix.join("."),
+            Value::from(credentials::redact_token(raw.value())).to_string(),
+        )),
+        other => out.push((prefix.join("."), other.to_string().trim().to_string())),
+    }
+}
+
+fn load(file: Option<&Path>) -> Result<LoadedGreenticConfig> {
+    let file = file.map(|path| path.to_string_lossy().into_owned());
+    config::load_with_meta(file.as_deref())
+}
+
+/// The config other commands see: the user config with the project config merged over it.
+/// Returns a description of the files it came from alongside the document.
+fn effective_document(file: Option<&Path>) -> Result<(String, DocumentMut)> {
+    let loaded = load(file)?;
+    let layers = [&loaded.loaded_from, &loaded.project_path]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    let sources = layers
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" + ");
+    match layers.as_slice() {
+        [] => bail!("no config file found; create one with `greentic-dev config set`"),
+        [path] => Ok((sources, read_document(path)?)),
+        _ => {
+            let mut merged = toml::Table::new();
+            for path in layers {
+                let raw = fs::read_to_string(path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                let table = toml::from_str(&raw)
+                    .with_context(|| format!("failed to parse {}", path.display()))?;
+                config::merge_tables(&mut merged, table);
+            }
+            let rendered = toml::to_string(&merged).context("failed to render merged config")?;
+            let doc = rendered
+                .parse::<DocumentMut>()
+                .context("failed to parse merged config")?;
+            Ok((sources, doc))
+        }
+    }
+}
+
+/// Project config `config set --project` writes: the nearest existing one, or
+/// `.greentic/config.toml` in the current directory.
+pub(crate) fn project_config_target() -> Result<PathBuf> {
+    let loaded = load(None)?;
+    if let Some(path) = loaded.project_path {
+        return Ok(path);
+    }
+    Ok(std::env::current_dir()
+        .context("unable to determine current directory")?
+        .join(config::PROJECT_CONFIG))
+}
+
+pub(crate) fn read_document(path: &Path) -> Result<DocumentMut> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if raw.trim().is_empty() {
+        return Ok(DocumentMut::new());
+    }
+    raw.parse::<DocumentMut>()
+        .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+pub(crate) fn ensure_parent(path: &Path) -> Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("failed to create {}", parent.display()))?;
@@ -52,28 +735,74 @@ fn ensure_parent(path: &Path) -> Result<()> {
     Ok(())
 }
 
-fn apply_key(doc: &mut DocumentMut, key: &str, value_str: &str) -> Result<()> {
-    let segments = key
-        .split('.')
-        .filter(|segment| !segment.is_empty())
-        .collect::<Vec<_>>();
-    if segments.is_empty() {
+/// Split a dotted key; segments may be quoted (`oci.registries."ghcr.io".insecure`).
+fn parse_key(key: &str) -> Result<Vec<Key>> {
+    let keys = Key::parse(key).map_err(|err| anyhow!("invalid config key `{key}`: {err}"))?;
+    if keys.is_empty() {
         bail!("config key cannot be empty");
     }
+    Ok(keys)
+}
+
+pub(crate) fn apply_key(doc: &mut DocumentMut, key: &str, value_str: &str) -> Result<()> {
+    let keys = parse_key(key)?;
+    let segments = keys.iter().map(Key::get).collect::<Vec<_>>();
+    let kind = config::key_kind(&segments).ok_or_else(|| anyhow!("unknown config key `{key}`"))?;
+    let value = typed_value(key, kind, value_str)?;
 
     let mut current = doc.as_table_mut();
     for segment in &segments[..segments.len() - 1] {
         current = current
             .entry(segment)
-            .or_insert(Item::Table(Table::new()))
+            .or_insert_with(|| {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            })
             .as_table_mut()
             .ok_or_else(|| anyhow!("path `{segment}` is not a table in the config"))?;
     }
 
-    current.insert(segments.last().unwrap(), value(value_str));
+    current.insert(segments.last().unwrap(), Item::Value(value));
     Ok(())
 }
 
+/// Interpret `raw` as a TOML value of the type `key` expects. Strings may be given bare.
+fn typed_value(key: &str, kind: ConfigValueKind, raw: &str) -> Result<Value> {
+    let parsed = raw.trim().parse::<Value>().ok();
+    let value = match (kind, parsed) {
+        (ConfigValueKind::String, Some(value @ Value::String(_))) => value,
+        (ConfigValueKind::String, _) => Value::from(raw),
+        (ConfigValueKind::Bool, Some(value @ Value::Boolean(_))) => value,
+        (ConfigValueKind::Bool, _) => bail!("`{key}` expects true or false, got `{raw}`"),
+        (ConfigValueKind::Array, Some(Value::Array(array)))
+            if array.iter().all(|value| value.is_str()) =>
+        {
+            Value::Array(array)
+        }
+        (ConfigValueKind::Array, Some(Value::Array(_))) => {
+            bail!("`{key}` expects an array of strings, got `{raw}`")
+        }
+        // A single command may be given bare: `cargo fmt --check` becomes `["cargo fmt --check"]`.
+        (ConfigValueKind::Array, Some(Value::String(value))) => {
+            Value::Array(Array::from_iter([value.value().clone()]))
+        }
+        (ConfigValueKind::Array, _) => Value::Array(Array::from_iter([raw])),
+        (ConfigValueKind::StringTable, Some(Value::InlineTable(table)))
+            if table.iter().all(|(_, value)| value.is_str()) =>
+        {
+            Value::InlineTable(table)
+        }
+        (ConfigValueKind::StringTable, _) => bail!(
+            "`{key}` expects an inline table of strings, e.g. {{ \"x-region\" = \"eu\" }}, got `{raw}`"
+        ),
+        (ConfigValueKind::ProfileSelection, Some(value @ Value::InlineTable(_))) => value,
+        (ConfigValueKind::ProfileSelection, Some(value @ Value::String(_))) => value,
+        (ConfigValueKind::ProfileSelection, _) => Value::from(raw),
+    };
+    Ok(value.decorated(" ", ""))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,6 +816,7 @@ mod tests {
             key: "defaults.component.org".into(),
             value: "ai.greentic".into(),
             file: Some(path.clone()),
+            project: false,
         };
         set_value(&args).unwrap();
         let written = fs::read_to_string(path).unwrap();
@@ -94,6 +824,157 @@ mod tests {
         assert!(written.contains("ai.greentic"));
     }
 
+    #[test]
+    fn show_redacts_literal_secrets() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        fs::write(
+            &path,
+            r#"
+[distributor.profiles.staging]
+url = "https://distributor.example.com"
+token = "s3cret"
+headers = { Authorization = "Bearer abc", x-region = "eu" }
+
+[distributor.profiles.prod]
+token = "env:PROD_TOKEN"
+
+[oci.registries."ghcr.io"]
+username = "me"
+password = "hunter2"
+"#,
+        )
+        .unwrap();
+
+        let shown = render_show(Some(&path)).unwrap();
+        assert!(!shown.contains("s3cret"));
+        assert!(!shown.contains("Bearer abc"));
+        assert!(!shown.contains("hunter2"));
+        assert!(shown.contains("token = \"env:PROD_TOKEN\""));
+        assert!(shown.contains("x-region = \"eu\""));
+        assert!(shown.contains("https://distributor.example.com"));
+    }
+
+    #[test]
+    fn set_parses_typed_values_against_the_schema() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        let set = |key: &str, value: &str| {
+            set_value(&ConfigSetArgs {
+                key: key.into(),
+                value: value.into(),
+                file: Some(path.clone()),
+                project: false,
+            })
+        };
+        set("oci.registries.\"localhost:5000\".insecure", "true").unwrap();
+        set(
+            "distributor.profiles.staging.headers",
+            "{ x-region = \"eu\" }",
+        )
+        .unwrap();
+        set("distributor.profiles.staging.tenant_id", "42").unwrap();
+        assert!(set("oci.registries.ghcr.insecure", "yes").is_err());
+        assert!(set("distributor.profiles.staging.headers", "{ x = 1 }").is_err());
+        assert!(set("defaults.component.orgg", "typo").is_err());
+
+        let written = fs::read_to_string(&path).unwrap();
+        let config: GreenticConfig = toml::from_str(&written).unwrap();
+        assert!(config.oci.registries["localhost:5000"].insecure);
+        let staging = &config.distributor.profiles["staging"];
+        assert_eq!(staging.tenant_id.as_deref(), Some("42"));
+        assert_eq!(staging.headers.as_ref().unwrap()["x-region"], "eu");
+    }
+
+    #[test]
+    fn set_parses_hook_arrays() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        let set = |key: &str, value: &str| {
+            set_value(&ConfigSetArgs {
+                key: key.into(),
+                value: value.into(),
+                file: Some(path.clone()),
+                project: false,
+            })
+        };
+        set(
+            "hooks.\"pack build\".pre",
+            "[\"cargo fmt --check\", \"cargo test\"]",
+        )
+        .unwrap();
+        set("hooks.flow.post", "echo done").unwrap();
+        assert!(set("hooks.flow.pre", "[1, 2]").is_err());
+        assert!(set("hooks.flow.around", "[]").is_err());
+
+        let written = fs::read_to_string(&path).unwrap();
+        let config: GreenticConfig = toml::from_str(&written).unwrap();
+        assert_eq!(
+            config.hooks["pack build"].pre,
+            ["cargo fmt --check", "cargo test"]
+        );
+        assert_eq!(config.hooks["flow"].post, ["echo done"]);
+    }
+
+    #[test]
+    fn unset_removes_keys_from_tables_and_inline_tables() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        fs::write(
+            &path,
+            r#"
+[distributor.profiles.staging]
+tenant_id = "acme"
+headers = { x-region = "eu", x-zone = "a" }
+"#,
+        )
+        .unwrap();
+        let unset = |key: &str| {
+            unset_value(&ConfigUnsetArgs {
+                key: key.into(),
+                file: Some(path.clone()),
+                project: false,
+            })
+        };
+        unset("distributor.profiles.staging.tenant_id").unwrap();
+        unset("distributor.profiles.staging.headers.x-zone").unwrap();
+        assert!(unset("distributor.profiles.staging.tenant_id").is_err());
+
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(!written.contains("tenant_id"));
+        assert!(!written.contains("x-zone"));
+        assert!(written.contains("x-region"));
+    }
+
+    #[test]
+    fn resolved_reports_value_sources() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        fs::write(
+            &path,
+            r#"
+[distributor.profiles.staging]
+base_url = "https://staging.example"
+token = "s3cret"
+"#,
+        )
+        .unwrap();
+        let resolved = render_resolved(Some(&path)).unwrap();
+        let line = |key: &str| {
+            resolved
+                .lines()
+                .find(|line| line.starts_with(key))
+                .unwrap_or_else(|| panic!("missing {key} in {resolved}"))
+                .to_string()
+        };
+        let file = path.display().to_string();
+        assert!(line("distributor.profiles.staging.base_url").ends_with(&format!("# {file}")));
+        assert!(line("distributor.profiles.staging.tenant_id").contains("\"local\""));
+        assert!(line("distributor.profiles.staging.tenant_id").ends_with("# default"));
+        assert!(line("distributor.profiles.staging.token").contains("<redacted>"));
+        assert!(!resolved.contains("s3cret"));
+    }
+
     #[test]
     fn updates_nested_tables() {
         let temp = TempDir::new().unwrap();
@@ -112,6 +993,7 @@ org = "ai.greentic"
             key: "defaults.component.template".into(),
             value: "rust-wasi".into(),
             file: Some(path.clone()),
+            project: false,
         };
         set_value(&args).unwrap();
         let written = fs::read_to_string(path).unwrap();
@@ -0,0 +1,489 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::artifact_store::ArtifactStore;
+use crate::cli::DoctorArgs;
+use crate::cmd::tools::{self, ToolStatus};
+use crate::component_cli::ensure_cargo_component_installed;
+use crate::config::{self, GreenticConfig, LoadedGreenticConfig};
+use crate::distributor::{self, OFFLINE_ENV, RESOLVE_STUB_ENV, StubDistributor};
+use crate::events::{self, Event};
+
+const WASM_TARGET: &str = "wasm32-wasip2";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    fn label(self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "pass",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "fail",
+        }
+    }
+}
+
+/// Safe remedy `--fix` may apply for a failing or warning check.
+#[derive(Debug, Clone)]
+enum Remedy {
+    Run(Vec<String>),
+    CreateDir(PathBuf),
+}
+
+impl Remedy {
+    fn run(args: &[&str]) -> Self {
+        Remedy::Run(args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Remedy::Run(args) => args.join(" "),
+            Remedy::CreateDir(path) => format!("mkdir -p {}", path.display()),
+        }
+    }
+
+    fn apply(&self) -> Result<()> {
+        match self {
+            Remedy::Run(args) => {
+                // Keep stdout free for the report; installers write progress to stderr.
+                let status = Command::new(&args[0])
+                    .args(&args[1..])
+                    .stdout(Stdio::from(std::io::stderr()))
+                    .status()
+                    .with_context(|| format!("failed to run `{}`", self.describe()))?;
+                if !status.success() {
+                    bail!("`{}` exited with {status}", self.describe());
+                }
+                Ok(())
+            }
+            Remedy::CreateDir(path) => fs::create_dir_all(path)
+                .with_context(|| format!("failed to create {}", path.display())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckReport {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// Command that fixes the check; `--fix` runs it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+    /// Set when `--fix` ran the remedy; `status` is the result of re-running the check.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub fixed: bool,
+    #[serde(skip)]
+    remedy: Option<Remedy>,
+}
+
+impl CheckReport {
+    fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+            fix: None,
+            fixed: false,
+            remedy: None,
+        }
+    }
+
+    fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Pass, detail)
+    }
+
+    fn warn(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Warn, detail)
+    }
+
+    fn fail(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Fail, detail)
+    }
+
+    fn with_remedy(mut self, remedy: Remedy) -> Self {
+        self.fix = Some(remedy.describe());
+        self.remedy = Some(remedy);
+        self
+    }
+}
+
+pub fn run(args: &DoctorArgs) -> Result<()> {
+    let reports = check_all(args);
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::to_value(&reports).context("failed to encode doctor report")?,
+        });
+    } else if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&reports).context("failed to encode doctor report")?
+        );
+    } else {
+        print_reports(&reports, args.fix);
+    }
+    let failures = reports
+        .iter()
+        .filter(|report| report.status == CheckStatus::Fail)
+        .count();
+    if failures > 0 {
+        bail!("{failures} doctor check(s) failed");
+    }
+    Ok(())
+}
+
+/// Run every setup check, applying safe remedies first when `--fix` is set.
+pub fn check_all(args: &DoctorArgs) -> Vec<CheckReport> {
+    let fix = args.fix;
+    let mut reports = vec![
+        fixing(fix, check_rust_toolchain),
+        fixing(fix, check_wasm_target),
+        fixing(fix, check_cargo_component),
+    ];
+
+    let loaded = config::load_with_meta(None);
+    let tools_config = match &loaded {
+        Ok(loaded) => loaded.config.clone(),
+        Err(_) => GreenticConfig::default(),
+    };
+    reports.extend(tools::check_tools(&tools_config).into_iter().map(|tool| {
+        let detail = match (&tool.path, &tool.version) {
+            (Some(path), Some(version)) => format!("{version} at {}", path.display()),
+            (Some(path), None) => format!("found at {}", path.display()),
+            (None, _) => "not found".to_string(),
+        };
+        let detail = match &tool.requirement {
+            Some(req) => format!("{detail} (requires {req})"),
+            None => detail,
+        };
+        let status = match tool.status {
+            ToolStatus::Ok => CheckStatus::Pass,
+            ToolStatus::NotInstalled => CheckStatus::Warn,
+            _ => CheckStatus::Fail,
+        };
+        CheckReport::new(&tool.name, status, detail)
+    }));
+
+    match &loaded {
+        Ok(loaded) => {
+            reports.push(config_report(loaded));
+            reports.push(check_profile(loaded, args.profile.as_deref()));
+            reports.push(check_distributor(loaded, args.profile.as_deref()));
+        }
+        Err(err) => {
+            reports.push(CheckReport::fail("config", format!("{err:#}")));
+            reports.push(CheckReport::warn(
+                "distributor profile",
+                "skipped: config did not load",
+            ));
+            reports.push(CheckReport::warn(
+                "distributor",
+                "skipped: config did not load",
+            ));
+        }
+    }
+
+    reports.push(fixing(fix, check_cache_dir));
+    reports
+}
+
+fn fixing(fix: bool, check: impl Fn() -> CheckReport) -> CheckReport {
+    let report = check();
+    if !fix || report.status == CheckStatus::Pass {
+        return report;
+    }
+    let Some(remedy) = report.remedy.clone() else {
+        return report;
+    };
+    if !events::json() {
+        eprintln!("fixing {}: {}", report.name, remedy.describe());
+    }
+    match remedy.apply() {
+        Ok(()) => CheckReport {
+            fixed: true,
+            ..check()
+        },
+        Err(err) => CheckReport {
+            detail: format!("{}; fix failed: {err:#}", report.detail),
+            ..report
+        },
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolchainFile {
+    toolchain: ToolchainSpec,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolchainSpec {
+    channel: Option<String>,
+}
+
+/// Nearest `rust-toolchain.toml` at or above the current directory, with its channel.
+fn pinned_channel() -> Option<(PathBuf, Option<String>)> {
+    let cwd = std::env::current_dir().ok()?;
+    cwd.ancestors().find_map(|dir| {
+        let path = dir.join("rust-toolchain.toml");
+        let contents = fs::read_to_string(&path).ok()?;
+        let channel = toml::from_str::<ToolchainFile>(&contents)
+            .ok()
+            .and_then(|file| file.toolchain.channel);
+        Some((path, channel))
+    })
+}
+
+fn rustc_version() -> Result<String> {
+    let output = Command::new("rustc")
+        .arg("--version")
+        .output()
+        .context("failed to run `rustc --version`")?;
+    if !output.status.success() {
+        bail!(
+            "`rustc --version` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn check_rust_toolchain() -> CheckReport {
+    const NAME: &str = "rust toolchain";
+    let pinned = pinned_channel();
+    let channel = pinned.as_ref().and_then(|(_, channel)| channel.clone());
+    let version = match rustc_version() {
+        Ok(version) => version,
+        Err(err) => {
+            let report = CheckReport::fail(NAME, format!("{err:#}"));
+            return match channel {
+                Some(channel) => {
+                    report.with_remedy(Remedy::run(&["rustup", "toolchain", "install", &channel]))
+                }
+                None => report,
+            };
+        }
+    };
+    let (Some((path, _)), Some(channel)) = (&pinned, channel) else {
+        return CheckReport::pass(NAME, format!("{version} (no rust-toolchain.toml channel)"));
+    };
+    if channel_matches(&channel, &version) {
+        CheckReport::pass(
+            NAME,
+            format!("{version} matches {} ({channel})", path.display()),
+        )
+    } else {
+        CheckReport::warn(
+            NAME,
+            format!(
+                "{version} does not match {} ({channel}); is RUSTUP_TOOLCHAIN or an override set?",
+                path.display()
+            ),
+        )
+    }
+}
+
+/// Whether `rustc --version` output (e.g. `rustc 1.90.0 (1159e78c4 2025-09-14)`) satisfies a
+/// toolchain channel such as `1.90.0`, `1.90`, `stable` or `nightly-2025-01-01`.
+fn channel_matches(channel: &str, rustc_version: &str) -> bool {
+    let Some(version) = rustc_version.split_whitespace().nth(1) else {
+        return false;
+    };
+    let channel = channel.split('-').next().unwrap_or(channel);
+    match channel {
+        "stable" => !version.contains('-'),
+        "beta" | "nightly" => version.contains(channel),
+        pinned => version == pinned || version.starts_with(&format!("{pinned}.")),
+    }
+}
+
+fn check_wasm_target() -> CheckReport {
+    let remedy = Remedy::run(&["rustup", "target", "add", WASM_TARGET]);
+    let output = match Command::new("rustc").args(["--print", "sysroot"]).output() {
+        Ok(output) if output.status.success() => output,
+        _ => {
+            return CheckReport::fail(WASM_TARGET, "cannot locate the rustc sysroot")
+                .with_remedy(remedy);
+        }
+    };
+    let sysroot = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    let target_dir = sysroot.join("lib").join("rustlib").join(WASM_TARGET);
+    if target_dir.is_dir() {
+        CheckReport::pass(WASM_TARGET, format!("installed in {}", sysroot.display()))
+    } else {
+        CheckReport::fail(WASM_TARGET, "target not installed").with_remedy(remedy)
+    }
+}
+
+fn check_cargo_component() -> CheckReport {
+    const NAME: &str = "cargo-component";
+    match ensure_cargo_component_installed() {
+        Ok(()) => CheckReport::pass(NAME, "`cargo component` is available"),
+        Err(err) => CheckReport::fail(NAME, format!("{err:#}")).with_remedy(Remedy::run(&[
+            "cargo",
+            "install",
+            "cargo-component",
+            "--locked",
+        ])),
+    }
+}
+
+fn config_report(loaded: &LoadedGreenticConfig) -> CheckReport {
+    let mut sources: Vec<String> = loaded
+        .loaded_from
+        .iter()
+        .chain(&loaded.project_path)
+        .map(|path| path.display().to_string())
+        .collect();
+    if sources.is_empty() {
+        sources.push("no config file; using defaults".to_string());
+    }
+    CheckReport::pass("config", sources.join(", "))
+}
+
+/// A missing profile only fails when a config file exists; on a fresh machine it is a hint.
+fn check_profile(loaded: &LoadedGreenticConfig, profile: Option<&str>) -> CheckReport {
+    const NAME: &str = "distributor profile";
+    match distributor::resolve_profile(loaded, profile) {
+        Ok(profile) => CheckReport::pass(NAME, format!("`{}` -> {}", profile.name, profile.url)),
+        Err(_) if loaded.loaded_from.is_none() && loaded.project_path.is_none() => {
+            let name = profile
+                .map(str::to_string)
+                .or_else(|| std::env::var(distributor::PROFILE_ENV).ok())
+                .unwrap_or_else(|| distributor::DEFAULT_PROFILE.to_string());
+            CheckReport::warn(
+                NAME,
+                format!(
+                    "no config file defines profile `{name}`; add one with `greentic-dev config set distributor.profiles.{name}.base_url <url>`"
+                ),
+            )
+        }
+        Err(err) => CheckReport::fail(NAME, format!("{err:#}")),
+    }
+}
+
+/// The configured distributor answers over HTTP (any status counts), or a local resolve stub
+/// stands in for it.
+fn check_distributor(loaded: &LoadedGreenticConfig, profile: Option<&str>) -> CheckReport {
+    const NAME: &str = "distributor";
+    if let Ok(stub) = std::env::var(RESOLVE_STUB_ENV) {
+        return match StubDistributor::load(PathBuf::from(&stub)) {
+            Ok(_) => CheckReport::pass(NAME, format!("using resolve stub {stub}")),
+            Err(err) => CheckReport::fail(NAME, format!("{err:#}")),
+        };
+    }
+    if distributor::offline() {
+        return CheckReport::warn(NAME, format!("skipped: offline mode ({OFFLINE_ENV}=1)"));
+    }
+    let Ok(profile) = distributor::resolve_profile(loaded, profile) else {
+        return CheckReport::warn(NAME, "skipped: no distributor profile");
+    };
+    let response = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .context("failed to build HTTP client")
+        .and_then(|client| Ok(client.get(&profile.url).send()?));
+    match response {
+        Ok(response) => CheckReport::pass(
+            NAME,
+            format!("{} reachable (HTTP {})", profile.url, response.status()),
+        ),
+        Err(err) => CheckReport::fail(
+            NAME,
+            format!(
+                "{} unreachable: {err:#}; start a local one with `greentic-dev distributor serve`",
+                profile.url
+            ),
+        ),
+    }
+}
+
+fn check_cache_dir() -> CheckReport {
+    const NAME: &str = "cache";
+    let store = match ArtifactStore::open() {
+        Ok(store) => store,
+        Err(err) => return CheckReport::fail(NAME, format!("{err:#}")),
+    };
+    let root = store.root();
+    if !root.exists() {
+        return CheckReport::warn(NAME, format!("{} does not exist yet", root.display()))
+            .with_remedy(Remedy::CreateDir(root.to_path_buf()));
+    }
+    match probe_writable(root) {
+        Ok(()) => CheckReport::pass(NAME, format!("{} is writable", root.display())),
+        Err(err) => CheckReport::fail(NAME, format!("{err:#}")),
+    }
+}
+
+fn probe_writable(dir: &Path) -> Result<()> {
+    let probe = dir.join(format!(".doctor-probe-{}", std::process::id()));
+    fs::write(&probe, b"ok").with_context(|| format!("{} is not writable", dir.display()))?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+fn print_reports(reports: &[CheckReport], fixing: bool) {
+    let width = reports
+        .iter()
+        .map(|report| report.name.len())
+        .max()
+        .unwrap_or(0);
+    for report in reports {
+        let fixed = if report.fixed { " (fixed)" } else { "" };
+        println!(
+            "{}  {:<width$}  {}{fixed}",
+            report.status.label(),
+            report.name,
+            report.detail
+        );
+        if let Some(fix) = &report.fix
+            && report.status != CheckStatus::Pass
+            && !fixing
+        {
+            println!("      {:<width$}  fix: {fix} (or run with --fix)", "");
+        }
+    }
+    let count = |status| {
+        reports
+            .iter()
+            .filter(|report| report.status == status)
+            .count()
+    };
+    println!(
+        "\n{} passed, {} warning(s), {} failed",
+        count(CheckStatus::Pass),
+        count(CheckStatus::Warn),
+        count(CheckStatus::Fail)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_pinned_and_named_channels() {
+        let rustc = "rustc 1.90.0 (1159e78c4 2025-09-14)";
+        assert!(channel_matches("1.90.0", rustc));
+        assert!(channel_matches("1.90", rustc));
+        assert!(channel_matches("stable", rustc));
+        assert!(!channel_matches("1.89.0", rustc));
+        assert!(!channel_matches("1.9", rustc));
+        assert!(!channel_matches("nightly", rustc));
+        assert!(channel_matches(
+            "nightly-2025-01-01",
+            "rustc 1.86.0-nightly (abcdef 2025-01-01)"
+        ));
+    }
+}
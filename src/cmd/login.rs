@@ -0,0 +1,87 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+use crate::config;
+use crate::credentials;
+use crate::distributor;
+use crate::events::{self, Event};
+
+/// `login [--profile]`: read a token from stdin (prompting without echo on a terminal) and store
+/// it encrypted for the selected distributor profile and its current base URL.
+pub fn run_login(profile: Option<&str>) -> Result<()> {
+    let loaded = config::load_with_meta(None)?;
+    let (name, cfg) = distributor::select_profile_config(&loaded, profile)?;
+    let base_url = distributor::profile_base_url(&cfg);
+    let token = read_token(&name)?;
+    let path = credentials::store_token(&name, &base_url, &token)?;
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::json!({ "profile": name, "base_url": base_url, "stored_in": path }),
+        });
+    } else {
+        println!(
+            "Stored token for profile `{name}` ({base_url}) in {}",
+            path.display()
+        );
+    }
+    if let Some(configured) = cfg.token {
+        eprintln!(
+            "warning: profile `{name}` sets `token = \"{}\"` in its config, which takes precedence over the stored token",
+            credentials::redact_token(&configured)
+        );
+    }
+    Ok(())
+}
+
+/// `logout [--profile]`: forget the stored token for the selected distributor profile.
+pub fn run_logout(profile: Option<&str>) -> Result<()> {
+    let loaded = config::load_with_meta(None)?;
+    let (name, _) = distributor::select_profile_config(&loaded, profile)?;
+    let removed = credentials::remove_token(&name)?;
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::json!({ "profile": name, "removed": removed }),
+        });
+    } else if removed {
+        println!("Removed stored token for profile `{name}`");
+    } else {
+        println!("No stored token for profile `{name}`");
+    }
+    Ok(())
+}
+
+fn read_token(profile: &str) -> Result<String> {
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    if interactive {
+        eprint!("Token for distributor profile `{profile}`: ");
+        io::stderr().flush()?;
+    }
+    let echo_off = interactive && set_echo(false);
+    let mut line = String::new();
+    let read = stdin.lock().read_line(&mut line);
+    if echo_off {
+        set_echo(true);
+        eprintln!();
+    }
+    read.context("failed to read token from stdin")?;
+    let token = line.trim();
+    if token.is_empty() {
+        bail!("no token given; pipe it on stdin or type it at the prompt");
+    }
+    Ok(token.to_string())
+}
+
+/// Toggle terminal echo with `stty`; returns whether it succeeded.
+fn set_echo(on: bool) -> bool {
+    if !cfg!(unix) {
+        return false;
+    }
+    Command::new("stty")
+        .arg(if on { "echo" } else { "-echo" })
+        .stdin(Stdio::inherit())
+        .status()
+        .is_ok_and(|status| status.success())
+}
@@ -1,2 +1,5 @@
 pub mod component;
 pub mod config;
+pub mod doctor;
+pub mod login;
+pub mod tools;
@@ -0,0 +1,271 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use toml_edit::DocumentMut;
+
+use crate::cli::{ToolsCommand, ToolsDoctorArgs, ToolsPinArgs};
+use crate::cmd::config::{apply_key, ensure_parent, project_config_target, read_document};
+use crate::config::{self, COMPANION_TOOLS, GreenticConfig};
+use crate::events::{self, Event};
+use crate::passthrough;
+
+pub fn run(command: ToolsCommand) -> Result<()> {
+    match command {
+        ToolsCommand::Doctor(args) => doctor(&args),
+        ToolsCommand::Pin(args) => pin(&args),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolStatus {
+    Ok,
+    /// Not installed and not required.
+    NotInstalled,
+    /// Required by [tools.<name>].version but not found.
+    Missing,
+    /// `--version` output did not contain a semver version.
+    UnknownVersion,
+    Mismatch,
+}
+
+impl ToolStatus {
+    fn is_failure(self) -> bool {
+        matches!(
+            self,
+            ToolStatus::Missing | ToolStatus::UnknownVersion | ToolStatus::Mismatch
+        )
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ToolStatus::Ok => "ok",
+            ToolStatus::NotInstalled => "not installed",
+            ToolStatus::Missing => "missing",
+            ToolStatus::UnknownVersion => "unknown version",
+            ToolStatus::Mismatch => "mismatch",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolReport {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub version: Option<String>,
+    pub requirement: Option<String>,
+    pub status: ToolStatus,
+}
+
+/// Locate and version-check every companion tool against its [tools.<name>].version.
+pub fn check_tools(config: &GreenticConfig) -> Vec<ToolReport> {
+    COMPANION_TOOLS
+        .iter()
+        .map(|name| check_tool(name, config))
+        .collect()
+}
+
+fn check_tool(name: &str, config: &GreenticConfig) -> ToolReport {
+    let requirement = config
+        .tools
+        .entry(name)
+        .and_then(|entry| entry.version.clone());
+    let path = locate(name, config).ok();
+    let version = path
+        .as_deref()
+        .and_then(|path| installed_version(path).ok());
+    let status = match (&path, &version, &requirement) {
+        (None, _, Some(_)) => ToolStatus::Missing,
+        (None, _, None) => ToolStatus::NotInstalled,
+        (Some(_), None, Some(_)) => ToolStatus::UnknownVersion,
+        (Some(_), Some(version), Some(req)) if !req.matches(version) => ToolStatus::Mismatch,
+        (Some(_), _, _) => ToolStatus::Ok,
+    };
+    ToolReport {
+        name: name.to_string(),
+        path,
+        version: version.map(|version| version.to_string()),
+        requirement: requirement.map(|req| req.to_string()),
+        status,
+    }
+}
+
+/// Binary for a companion tool, resolved the same way passthrough commands resolve it.
+pub fn locate(name: &str, config: &GreenticConfig) -> Result<PathBuf> {
+    passthrough::resolve_tool(name, &config.tools).map(|resolved| resolved.path)
+}
+
+/// Run `<bin> --version` and take the first token that parses as semver (a leading `v` is
+/// allowed), e.g. `greentic-flow 0.4.12`.
+pub fn installed_version(bin: &Path) -> Result<Version> {
+    let output = Command::new(bin)
+        .arg("--version")
+        .output()
+        .with_context(|| format!("failed to run `{} --version`", bin.display()))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_version_output(&stdout).with_context(|| {
+        format!(
+            "`{} --version` printed no version: {}",
+            bin.display(),
+            stdout.trim()
+        )
+    })
+}
+
+fn parse_version_output(output: &str) -> Option<Version> {
+    output
+        .split_whitespace()
+        .find_map(|token| Version::parse(token.trim_start_matches('v')).ok())
+}
+
+fn doctor(args: &ToolsDoctorArgs) -> Result<()> {
+    let config = config::load()?;
+    let reports = check_tools(&config);
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::to_value(&reports).context("failed to encode tools report")?,
+        });
+    } else if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&reports).context("failed to encode tools report")?
+        );
+    } else {
+        print_table(&reports);
+    }
+    let failures = reports
+        .iter()
+        .filter(|report| report.status.is_failure())
+        .count();
+    if failures > 0 {
+        bail!("{failures} tool(s) do not satisfy their [tools.*] version requirement");
+    }
+    Ok(())
+}
+
+fn print_table(reports: &[ToolReport]) {
+    let headers = ["TOOL", "VERSION", "REQUIRED", "STATUS", "PATH"];
+    let rows: Vec<[String; 5]> = reports
+        .iter()
+        .map(|report| {
+            [
+                report.name.clone(),
+                report.version.clone().unwrap_or_else(|| "-".into()),
+                report.requirement.clone().unwrap_or_else(|| "-".into()),
+                report.status.label().to_string(),
+                report
+                    .path
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_else(|| "-".into()),
+            ]
+        })
+        .collect();
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let render = |cells: [&str; 5]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", render(headers));
+    for row in &rows {
+        println!("{}", render(row.each_ref().map(String::as_str)));
+    }
+}
+
+/// Record the installed version of each tool as an exact requirement in the project config, so
+/// `tools doctor` flags teammates running a different toolchain.
+fn pin(args: &ToolsPinArgs) -> Result<()> {
+    let path = match &args.file {
+        Some(path) => path.clone(),
+        None => project_config_target()?,
+    };
+    let config = config::load()?;
+    let names: Vec<&str> = if args.tools.is_empty() {
+        COMPANION_TOOLS.to_vec()
+    } else {
+        args.tools.iter().map(String::as_str).collect()
+    };
+
+    let mut pins = Vec::new();
+    for name in names {
+        let version = match locate(name, &config).and_then(|bin| installed_version(&bin)) {
+            Ok(version) => version,
+            Err(err) if args.tools.is_empty() => {
+                eprintln!("skipping {name}: {err:#}");
+                continue;
+            }
+            Err(err) => return Err(err.context(format!("cannot pin {name}"))),
+        };
+        pins.push((name, VersionReq::parse(&format!("={version}"))?));
+    }
+    if pins.is_empty() {
+        bail!("no companion tools found to pin");
+    }
+
+    ensure_parent(&path)?;
+    let mut doc = if path.exists() {
+        read_document(&path)?
+    } else {
+        DocumentMut::new()
+    };
+    for (name, requirement) in &pins {
+        apply_key(
+            &mut doc,
+            &format!("tools.{name}.version"),
+            &requirement.to_string(),
+        )?;
+    }
+    fs::write(&path, doc.to_string())
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    events::artifact("config", &path, None);
+    if events::json() {
+        let pinned: serde_json::Map<String, serde_json::Value> = pins
+            .iter()
+            .map(|(name, requirement)| (name.to_string(), requirement.to_string().into()))
+            .collect();
+        events::emit(Event::Result {
+            data: serde_json::json!({ "pinned": pinned }),
+        });
+        return Ok(());
+    }
+    for (name, requirement) in &pins {
+        println!("Pinned {name} {requirement}");
+    }
+    println!("Updated {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_version_from_common_outputs() {
+        for (output, expected) in [
+            ("greentic-flow 0.4.12\n", "0.4.12"),
+            ("greentic-pack v1.2.3-rc.1 (abc123)", "1.2.3-rc.1"),
+            ("0.9.0", "0.9.0"),
+        ] {
+            assert_eq!(
+                parse_version_output(output),
+                Some(Version::parse(expected).unwrap())
+            );
+        }
+        assert_eq!(parse_version_output("greentic-gui dev build"), None);
+    }
+}
@@ -1,223 +1,294 @@
-use std::convert::TryFrom;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
 
-use anyhow::{Context, Result, anyhow, bail};
-use bytes::Bytes;
-use greentic_distributor_client::{
-    DistributorClient, DistributorClientConfig, DistributorEnvironmentId, HttpDistributorClient,
-    ResolveComponentRequest,
-};
+use anyhow::{Context, Result, bail};
+use greentic_flow::flow_bundle::blake3_hex;
 use greentic_pack::builder::ComponentEntry;
-use greentic_types::{EnvId, TenantCtx, TenantId};
-use reqwest::blocking::Client;
-use semver::Version;
-use serde_json::json;
-use tokio::runtime::Runtime;
+use semver::{Version, VersionReq};
 
-use crate::config;
-use crate::distributor;
+use crate::artifact_store::{ArtifactStore, link_into};
+use crate::cache;
+use crate::distributor::{self, DevArtifactKind, DevIntent, DevResolveRequest};
+use crate::events;
+use crate::license::{self, ComponentLicense};
+use crate::lockfile::{self, LockedComponent, WorkspaceLock};
+use crate::oci::{self, OciArtifactKind, OciClient};
 use crate::pack_init::{
-    PackInitIntent, WorkspaceComponent, WorkspaceManifest, manifest_path, slugify,
+    PackInitIntent, WorkspaceComponent, WorkspaceManifest, default_platform, handle_resolve_result,
+    manifest_path, slugify,
 };
 
-#[derive(Debug, Clone)]
-struct SimpleStub {
-    artifact_path: PathBuf,
-    digest: String,
-    signature: greentic_types::distributor::SignatureSummary,
-    cache: greentic_types::distributor::CacheInfo,
-}
+const COMPONENT_FILE_NAME: &str = "artifact.wasm";
+/// Workspace directory that holds links to the components recorded in the manifest.
+const WORKSPACE_ARTIFACTS_DIR: &str = ".greentic/artifacts";
 
 pub fn run_component_add(
     coordinate: &str,
     profile: Option<&str>,
+    header_overrides: &[(String, String)],
+    accepted_licenses: &[String],
     intent: PackInitIntent,
+    locked: bool,
 ) -> Result<PathBuf> {
     let coordinate_path = PathBuf::from(coordinate);
     if coordinate_path.exists() {
         return Ok(coordinate_path);
     }
 
-    let offline = std::env::var("GREENTIC_DEV_OFFLINE")
-        .ok()
-        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
-        .unwrap_or(false);
-    let stubbed_response = load_stubbed_response();
-    if offline && stubbed_response.is_none() {
+    let is_oci = coordinate.starts_with(oci::OCI_SCHEME);
+    if is_oci && distributor::offline() {
         bail!(
-            "offline mode enabled (GREENTIC_DEV_OFFLINE=1) and coordinate `{coordinate}` is not a local path; provide a stub via GREENTIC_DEV_RESOLVE_STUB or use a local component path"
+            "offline mode enabled (GREENTIC_DEV_OFFLINE=1); cannot pull `{coordinate}` from an OCI registry"
         );
     }
 
-    let (component_id, version_req) = parse_coordinate(coordinate)?;
-
-    let response = if let Some(resp) = stubbed_response {
-        resp?
+    events::stage_started("resolve");
+    let store = ArtifactStore::open()?;
+    let (component_id, version_req, stored, reported_version, source_profile, license) = if is_oci {
+        let (name, tag) = oci::name_and_tag(coordinate)?;
+        // Tags such as `latest` (also the default for untagged references) carry no version.
+        let version_req = tag
+            .map(|tag| tag.trim_start_matches('v').to_string())
+            .filter(|tag| VersionReq::parse(tag).is_ok())
+            .unwrap_or_else(|| "*".to_string());
+        let pulled = OciClient::load()?.pull(coordinate, OciArtifactKind::Component)?;
+        let stored = store.insert(Some(&pulled.digest), COMPONENT_FILE_NAME, &pulled.bytes)?;
+        let reported = pulled
+            .version
+            .map(|version| version.trim_start_matches('v').to_string())
+            .or_else(|| (version_req == "*").then(|| digest_version(&stored.digest)));
+        (name, version_req, stored, reported, None, None)
     } else {
-        let cfg = config::load_with_meta(None)?;
-        let profile = distributor::resolve_profile(&cfg, profile)?;
-        let tenant_ctx = build_tenant_ctx(&profile)?;
-        let environment_id = DistributorEnvironmentId::from(profile.environment_id.as_str());
-        let pack_id = detect_pack_id().unwrap_or_else(|| "greentic-dev-local".to_string());
+        let version_req = parse_version_req(coordinate);
+        let distributor = distributor::open(profile, header_overrides)
+            .with_context(|| format!("cannot resolve `{coordinate}`"))?;
+        let resolved = handle_resolve_result(distributor.resolve(&DevResolveRequest {
+            coordinate: coordinate.to_string(),
+            intent: match intent {
+                PackInitIntent::Dev => DevIntent::Dev,
+                PackInitIntent::Runtime => DevIntent::Runtime,
+            },
+            platform: Some(default_platform()),
+            features: Vec::new(),
+        }))?;
+        if resolved.kind != DevArtifactKind::Component {
+            bail!(
+                "coordinate `{}` resolved to {:?}, expected component",
+                resolved.coordinate,
+                resolved.kind
+            );
+        }
+        let license = license::ensure_accepted(
+            &resolved,
+            accepted_licenses,
+            &WorkspaceManifest::load(&manifest_path()?)?,
+        )?;
+        let stored = distributor.fetch(&resolved, &store, COMPONENT_FILE_NAME)?;
+        if let Some(license) = &license {
+            license::record_in_store(&stored, license)?;
+        }
+        (
+            resolved.name,
+            version_req,
+            stored,
+            resolved.version,
+            distributor.profile_name().map(str::to_string),
+            license,
+        )
+    };
+    events::stage_finished("resolve");
+    events::artifact("component", &stored.path, Some(&stored.digest));
 
-        let req = ResolveComponentRequest {
-            tenant: tenant_ctx.clone(),
-            environment_id,
-            pack_id,
-            component_id: component_id.clone(),
-            version: version_req.to_string(),
-            extra: json!({ "intent": format!("{:?}", intent) }),
-        };
+    events::stage_started("lock");
+    let artifact_bytes = fs::read(&stored.path)
+        .with_context(|| format!("failed to read {}", stored.path.display()))?;
+    let digest = stored.digest.clone();
 
-        let client = http_client(&profile)?;
-        let rt = Runtime::new().context("failed to start tokio runtime for distributor client")?;
-        rt.block_on(client.resolve_component(req))?
+    let lock_path = lockfile::lockfile_path()?;
+    let mut lock = WorkspaceLock::load(&lock_path)?;
+    let version = resolve_exact_version(
+        coordinate,
+        &component_id,
+        &version_req,
+        reported_version.as_deref(),
+        lock.component(&component_id)
+            .filter(|entry| entry.coordinate == coordinate && entry.digest == digest),
+    )?;
+    let locked_entry = LockedComponent {
+        name: component_id.clone(),
+        coordinate: coordinate.to_string(),
+        version: version.to_string(),
+        digest,
+        profile: source_profile,
+        size: artifact_bytes.len() as u64,
     };
+    lockfile::verify(&lock, &locked_entry, locked)?;
 
-    let artifact_bytes = fetch_artifact(&response.artifact)?;
-    let (cache_dir, cache_path) =
-        write_component_to_cache(&component_id, &version_req, &artifact_bytes)?;
-    update_manifest(coordinate, &component_id, &version_req, &cache_path)?;
+    // The manifest points at a workspace-local link so pruning the shared store never breaks it.
+    let workspace_wasm = workspace_artifact_path(&component_id);
+    let artifacts_dir = Path::new(WORKSPACE_ARTIFACTS_DIR);
+    fs::create_dir_all(artifacts_dir)
+        .with_context(|| format!("failed to create {}", artifacts_dir.display()))?;
+    link_into(&stored, &workspace_wasm)?;
 
-    println!(
-        "Resolved {} -> {}@{}",
-        coordinate, component_id, version_req
-    );
-    println!("Cached component at {}", cache_path.display());
-    println!(
-        "Updated workspace manifest at {}",
-        manifest_path()?.display()
-    );
+    update_manifest(
+        coordinate,
+        &component_id,
+        &version,
+        &blake3_hex(&artifact_bytes),
+        &workspace_wasm,
+        license,
+    )?;
+    if !locked {
+        lock.upsert(locked_entry);
+        lock.save(&lock_path)?;
+    }
+    cache::register_workspace(
+        &std::env::current_dir().context("unable to determine workspace root")?,
+    )?;
 
-    Ok(cache_dir)
-}
+    events::stage_finished("lock");
+    events::artifact("manifest", &manifest_path()?, None);
+    if !locked {
+        events::artifact("lockfile", &lock_path, None);
+    }
 
-fn parse_coordinate(input: &str) -> Result<(String, String)> {
-    if let Some((id, ver)) = input.rsplit_once('@') {
-        Ok((id.to_string(), ver.to_string()))
-    } else {
-        Ok((input.to_string(), "*".to_string()))
+    if !events::json() {
+        println!("Resolved {} -> {}@{}", coordinate, component_id, version);
+        println!("Cached component at {}", stored.path.display());
+        println!("Linked component into {}", workspace_wasm.display());
+        println!(
+            "Updated workspace manifest at {}",
+            manifest_path()?.display()
+        );
+        if locked {
+            println!("Verified against {}", lock_path.display());
+        } else {
+            println!("Updated lockfile at {}", lock_path.display());
+        }
     }
-}
 
-fn build_tenant_ctx(profile: &distributor::DistributorProfile) -> Result<TenantCtx> {
-    let env = EnvId::from_str(&profile.environment_id)
-        .or_else(|_| EnvId::try_from(profile.environment_id.as_str()))
-        .map_err(|err| anyhow!("invalid environment id `{}`: {err}", profile.environment_id))?;
-    let tenant = TenantId::from_str(&profile.tenant_id)
-        .or_else(|_| TenantId::try_from(profile.tenant_id.as_str()))
-        .map_err(|err| anyhow!("invalid tenant id `{}`: {err}", profile.tenant_id))?;
-    Ok(TenantCtx::new(env, tenant))
+    Ok(stored.dir().to_path_buf())
 }
 
-fn http_client(profile: &distributor::DistributorProfile) -> Result<HttpDistributorClient> {
-    let env_id = EnvId::from_str(&profile.environment_id)
-        .or_else(|_| EnvId::try_from(profile.environment_id.as_str()))
-        .map_err(|err| anyhow!("invalid environment id `{}`: {err}", profile.environment_id))?;
-    let tenant_id = TenantId::from_str(&profile.tenant_id)
-        .or_else(|_| TenantId::try_from(profile.tenant_id.as_str()))
-        .map_err(|err| anyhow!("invalid tenant id `{}`: {err}", profile.tenant_id))?;
-    let cfg = DistributorClientConfig {
-        base_url: Some(profile.url.clone()),
-        environment_id: DistributorEnvironmentId::from(profile.environment_id.as_str()),
-        tenant: TenantCtx::new(env_id, tenant_id),
-        auth_token: profile.token.clone(),
-        extra_headers: profile.headers.clone(),
-        request_timeout: None,
-    };
-    HttpDistributorClient::new(cfg).map_err(Into::into)
+/// Drop a component from the workspace manifest and lockfile, along with its workspace link.
+/// Cached artifacts are left for `cache gc` to prune.
+pub fn run_component_remove(name: &str) -> Result<()> {
+    let manifest_path = manifest_path()?;
+    let mut manifest = WorkspaceManifest::load(&manifest_path)?;
+    let before = manifest.components.len();
+    manifest
+        .components
+        .retain(|component| component.entry.name != name);
+    if manifest.components.len() == before {
+        bail!("component `{name}` is not recorded in the workspace manifest");
+    }
+    manifest.save(&manifest_path)?;
+    let workspace_wasm = workspace_artifact_path(name);
+    if workspace_wasm.exists() {
+        fs::remove_file(&workspace_wasm)
+            .with_context(|| format!("failed to remove {}", workspace_wasm.display()))?;
+    }
+
+    let lock_path = lockfile::lockfile_path()?;
+    let mut lock = WorkspaceLock::load(&lock_path)?;
+    events::artifact("manifest", &manifest_path, None);
+    if lock.remove(name) {
+        lock.save(&lock_path)?;
+        events::artifact("lockfile", &lock_path, None);
+    }
+
+    if !events::json() {
+        println!("Removed {name} from {}", manifest_path.display());
+        println!("Run `greentic-dev cache gc` to prune its cached artifact.");
+    }
+    Ok(())
 }
 
-fn fetch_artifact(location: &greentic_distributor_client::ArtifactLocation) -> Result<Bytes> {
-    match location {
-        greentic_distributor_client::ArtifactLocation::FilePath { path } => {
-            if path.starts_with("http://") || path.starts_with("https://") {
-                let client = Client::new();
-                let resp = client
-                    .get(path)
-                    .send()
-                    .context("failed to download artifact")?;
-                if !resp.status().is_success() {
-                    bail!("artifact download failed with status {}", resp.status());
-                }
-                resp.bytes().map_err(Into::into)
-            } else if let Some(rest) = path.strip_prefix("file://") {
-                fs::read(rest)
-                    .map(Bytes::from)
-                    .with_context(|| format!("failed to read component at {}", rest))
-            } else {
-                fs::read(path)
-                    .map(Bytes::from)
-                    .with_context(|| format!("failed to read component at {}", path))
-            }
-        }
-        greentic_distributor_client::ArtifactLocation::OciReference { reference } => {
-            bail!("OCI component artifacts are not supported yet ({reference})")
-        }
-        greentic_distributor_client::ArtifactLocation::DistributorInternal { handle } => {
-            bail!("Distributor internal artifacts are not supported yet ({handle})")
+/// Determine the exact version a coordinate resolved to: an exact pin in the coordinate wins, then a
+/// version reported by the resolver, then the lockfile entry for the same artifact.
+fn resolve_exact_version(
+    coordinate: &str,
+    component_id: &str,
+    version_req: &str,
+    reported: Option<&str>,
+    locked: Option<&LockedComponent>,
+) -> Result<Version> {
+    let pinned = version_req.trim().trim_start_matches('=');
+    if let Ok(version) = Version::parse(pinned) {
+        return Ok(version);
+    }
+    if let Some(raw) = reported {
+        let version = Version::parse(raw)
+            .with_context(|| format!("resolver reported invalid version `{raw}`"))?;
+        let req = VersionReq::parse(version_req)
+            .with_context(|| format!("invalid version requirement `{version_req}`"))?;
+        if !req.matches(&version) {
+            bail!(
+                "resolver returned {component_id}@{version}, which does not satisfy `{version_req}`"
+            );
         }
+        return Ok(version);
     }
+    if let Some(entry) = locked {
+        return Version::parse(&entry.version).with_context(|| {
+            format!(
+                "{} records invalid version `{}` for {component_id}",
+                lockfile::LOCKFILE_NAME,
+                entry.version
+            )
+        });
+    }
+    bail!(
+        "unable to determine the exact version `{coordinate}` resolved to; pin one (e.g. `{component_id}@1.2.3`) or include `version` in the resolve stub"
+    )
 }
 
-fn write_component_to_cache(
-    component_id: &str,
-    version: &str,
-    bytes: &Bytes,
-) -> Result<(PathBuf, PathBuf)> {
-    let mut path = cache_base_dir()?;
-    let slug = cache_slug_parts(component_id, version);
-    path.push(slug);
-    fs::create_dir_all(&path).with_context(|| format!("failed to create {}", path.display()))?;
-    let file_path = path.join("artifact.wasm");
-    fs::write(&file_path, bytes)
-        .with_context(|| format!("failed to write {}", file_path.display()))?;
-    Ok((path, file_path))
+/// Version recorded for an OCI artifact whose tag and manifest name no version: `0.0.0` with the
+/// short layer digest as build metadata, so the same bytes always lock to the same version.
+fn digest_version(digest: &str) -> String {
+    let hex = digest.trim_start_matches("sha256:");
+    format!("0.0.0+sha256.{}", &hex[..hex.len().min(12)])
 }
 
-fn cache_base_dir() -> Result<PathBuf> {
-    let mut base = std::env::current_dir().context("unable to determine workspace root")?;
-    base.push(".greentic");
-    base.push("components");
-    fs::create_dir_all(&base)
-        .with_context(|| format!("failed to create cache directory {}", base.display()))?;
-    Ok(base)
+/// Workspace-relative path of the link to `component_id`'s artifact.
+fn workspace_artifact_path(component_id: &str) -> PathBuf {
+    Path::new(WORKSPACE_ARTIFACTS_DIR).join(format!("{}.wasm", slugify(component_id)))
 }
 
-fn cache_slug_parts(component_id: &str, version: &str) -> String {
-    slugify(&format!("{}-{}", component_id.replace('/', "-"), version))
+/// The `@<requirement>` suffix of a distributor coordinate, `*` when there is none.
+fn parse_version_req(input: &str) -> String {
+    let scheme_end = input.find("://").map(|idx| idx + 3).unwrap_or(0);
+    match input[scheme_end..].rsplit_once('@') {
+        Some((_, req)) => req.to_string(),
+        None => "*".to_string(),
+    }
 }
 
 fn update_manifest(
     coordinate: &str,
     component_id: &str,
-    version: &str,
+    version: &Version,
+    hash_blake3: &str,
     wasm_path: &Path,
+    license: Option<ComponentLicense>,
 ) -> Result<()> {
     let manifest_path = manifest_path()?;
-    let mut manifest: WorkspaceManifest = if manifest_path.exists() {
-        let data = fs::read_to_string(&manifest_path)
-            .with_context(|| format!("failed to read {}", manifest_path.display()))?;
-        serde_json::from_str(&data)
-            .with_context(|| format!("failed to parse {}", manifest_path.display()))?
-    } else {
-        WorkspaceManifest::default()
-    };
+    let mut manifest = WorkspaceManifest::load(&manifest_path)?;
 
     let entry = WorkspaceComponent {
         coordinate: coordinate.to_string(),
         entry: ComponentEntry {
             name: component_id.to_string(),
-            version: Version::parse(version).unwrap_or_else(|_| Version::new(0, 0, 0)),
+            version: version.clone(),
             file_wasm: wasm_path.display().to_string(),
-            hash_blake3: String::new(),
+            hash_blake3: hash_blake3.to_string(),
             schema_file: None,
             manifest_file: None,
             world: None,
             capabilities: None,
         },
+        license,
     };
 
     let mut replaced = false;
@@ -232,88 +303,5 @@ fn update_manifest(
         manifest.components.push(entry);
     }
 
-    let rendered =
-        serde_json::to_string_pretty(&manifest).context("failed to render workspace manifest")?;
-    fs::write(&manifest_path, rendered)
-        .with_context(|| format!("failed to write {}", manifest_path.display()))?;
-    Ok(())
-}
-
-fn detect_pack_id() -> Option<String> {
-    let candidates = ["pack.toml", "Pack.toml"];
-    for candidate in candidates {
-        let path = Path::new(candidate);
-        if path.exists() {
-            let data = fs::read_to_string(path).ok()?;
-            if let Ok(value) = data.parse::<toml::Value>()
-                && let Some(id) = value.get("pack_id").and_then(|v| v.as_str())
-            {
-                return Some(id.to_string());
-            }
-        }
-    }
-    None
-}
-
-fn load_stubbed_response() -> Option<Result<greentic_distributor_client::ResolveComponentResponse>>
-{
-    let path = std::env::var("GREENTIC_DEV_RESOLVE_STUB").ok()?;
-    let data = fs::read_to_string(&path)
-        .map_err(|err| anyhow!("failed to read stub response {}: {err}", path))
-        .ok()?;
-
-    // First try to parse the real response shape.
-    if let Ok(resp) =
-        serde_json::from_str::<greentic_distributor_client::ResolveComponentResponse>(&data)
-    {
-        return Some(Ok(resp));
-    }
-
-    // Fallback: accept a minimal stub JSON.
-    let parsed = serde_json::from_str::<serde_json::Value>(&data).ok()?;
-    let Some(artifact_path) = parsed
-        .get("artifact_path")
-        .and_then(|v| v.as_str())
-        .map(PathBuf::from)
-    else {
-        return Some(Err(anyhow!(
-            "stub missing `artifact_path` (expected JSON with artifact_path pointing to the component wasm)"
-        )));
-    };
-    let digest = parsed
-        .get("digest")
-        .and_then(|v| v.as_str())
-        .unwrap_or("sha256:stub")
-        .to_string();
-    let signature = greentic_types::distributor::SignatureSummary {
-        verified: false,
-        signer: "stub".to_string(),
-        extra: serde_json::Value::Object(serde_json::Map::new()),
-    };
-    let cache = greentic_types::distributor::CacheInfo {
-        size_bytes: 0,
-        last_used_utc: "stub".to_string(),
-        last_refreshed_utc: "stub".to_string(),
-    };
-    let stub = SimpleStub {
-        artifact_path,
-        digest,
-        signature,
-        cache,
-    };
-
-    Some(Ok(to_resolve_response(stub)))
-}
-
-fn to_resolve_response(stub: SimpleStub) -> greentic_distributor_client::ResolveComponentResponse {
-    greentic_distributor_client::ResolveComponentResponse {
-        status: greentic_types::distributor::ComponentStatus::Ready,
-        digest: greentic_types::distributor::ComponentDigest(stub.digest),
-        artifact: greentic_distributor_client::ArtifactLocation::FilePath {
-            path: stub.artifact_path.display().to_string(),
-        },
-        signature: stub.signature,
-        cache: stub.cache,
-        secret_requirements: None,
-    }
+    manifest.save(&manifest_path)
 }
@@ -1,8 +1,7 @@
 use anyhow::{Context, Result, anyhow, bail};
 use clap::{Args, Subcommand};
 use convert_case::{Case, Casing};
-use once_cell::sync::Lazy;
-use semver::Version;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::{BTreeSet, HashMap};
@@ -16,31 +15,12 @@ use time::format_description::well_known::Rfc3339;
 use wit_component::{DecodedWasm, decode as decode_component};
 use wit_parser::{Resolve, WorldId, WorldItem};
 
+use crate::component_templates::{self, ComponentTemplate, DEFAULT_ORG, DEFAULT_TEMPLATE};
+use crate::config;
+use crate::diagnostics::{self, Diagnostic};
+use crate::events;
 use crate::path_safety::normalize_under_root;
 
-static WORKSPACE_ROOT: Lazy<PathBuf> = Lazy::new(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")));
-
-const TEMPLATE_COMPONENT_CARGO: &str = include_str!(concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/templates/component/Cargo.toml.in"
-));
-const TEMPLATE_SRC_LIB: &str = include_str!(concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/templates/component/src/lib.rs"
-));
-const TEMPLATE_PROVIDER: &str = include_str!(concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/templates/component/provider.toml"
-));
-const TEMPLATE_SCHEMA_CONFIG: &str = include_str!(concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/templates/component/schemas/v1/config.schema.json"
-));
-const TEMPLATE_README: &str = include_str!(concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/templates/component/README.md"
-));
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct ProviderMetadata {
     name: String,
@@ -83,6 +63,50 @@ struct CapabilitiesSection {
     filesystem: bool,
 }
 
+impl CapabilitiesSection {
+    fn declared(&self, capability: &str) -> bool {
+        match capability {
+            "secrets" => self.secrets,
+            "telemetry" => self.telemetry,
+            "network" => self.network,
+            "filesystem" => self.filesystem,
+            _ => false,
+        }
+    }
+}
+
+/// Which imported WIT packages exercise each capability. Undeclared imports of a `required`
+/// capability fail validation; `filesystem` only warns because the Rust standard library links
+/// `wasi:filesystem` on wasm32-wasip2 even when the component never touches a file.
+struct CapabilityRule {
+    capability: &'static str,
+    packages: &'static [&'static str],
+    required: bool,
+}
+
+const CAPABILITY_RULES: &[CapabilityRule] = &[
+    CapabilityRule {
+        capability: "network",
+        packages: &["wasi:http", "wasi:sockets", "greentic:http"],
+        required: true,
+    },
+    CapabilityRule {
+        capability: "secrets",
+        packages: &["greentic:secrets", "greentic:secrets-store"],
+        required: true,
+    },
+    CapabilityRule {
+        capability: "telemetry",
+        packages: &["greentic:telemetry"],
+        required: true,
+    },
+    CapabilityRule {
+        capability: "filesystem",
+        packages: &["wasi:filesystem"],
+        required: false,
+    },
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct ExportsSection {
     #[serde(default)]
@@ -121,6 +145,7 @@ struct ValidationReport {
     sha256: String,
     world: String,
     packages: Vec<String>,
+    warnings: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -135,40 +160,54 @@ struct Versions {
     telemetry_wit_version: String,
 }
 
+/// Crate and WIT package versions pinned into scaffolds, from `templates/component/versions.toml`.
+const VERSIONS_TOML: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/templates/component/versions.toml"
+));
+
+#[derive(Debug, Deserialize)]
+struct VersionsFile {
+    crates: HashMap<String, String>,
+    wit: HashMap<String, String>,
+}
+
 impl Versions {
     fn load() -> Result<Self> {
-        let interfaces_version = resolved_version("greentic-interfaces")?;
-        let types_version = resolved_version("greentic-types")?;
-        let component_runtime_version = resolved_version("greentic-component")?;
-
-        let interfaces_root = find_crate_source("greentic-interfaces", &interfaces_version)?;
-        let component_wit_version = detect_component_node_world_version(&interfaces_root)?;
-        let secrets_wit_version = detect_wit_package_version(&interfaces_root, "secrets")?;
-        let state_wit_version = detect_wit_package_version(&interfaces_root, "state")?;
-        let http_wit_version = detect_wit_package_version(&interfaces_root, "http")?;
-        let telemetry_wit_version = detect_wit_package_version(&interfaces_root, "telemetry")?;
+        let file: VersionsFile =
+            toml::from_str(VERSIONS_TOML).context("embedded versions.toml is invalid")?;
+        let pick = |table: &HashMap<String, String>, key: &str| {
+            table
+                .get(key)
+                .cloned()
+                .ok_or_else(|| anyhow!("embedded versions.toml has no version for `{key}`"))
+        };
 
         Ok(Self {
-            interfaces: interfaces_version,
-            types: types_version,
-            component_runtime: component_runtime_version,
-            component_wit_version,
-            secrets_wit_version,
-            state_wit_version,
-            http_wit_version,
-            telemetry_wit_version,
+            interfaces: pick(&file.crates, "greentic-interfaces")?,
+            types: pick(&file.crates, "greentic-types")?,
+            component_runtime: pick(&file.crates, "greentic-component")?,
+            component_wit_version: pick(&file.wit, "greentic:component")?,
+            secrets_wit_version: pick(&file.wit, "greentic:secrets-store")?,
+            state_wit_version: pick(&file.wit, "greentic:state")?,
+            http_wit_version: pick(&file.wit, "greentic:http")?,
+            telemetry_wit_version: pick(&file.wit, "greentic:telemetry")?,
         })
     }
 }
 
-static VERSIONS: Lazy<Versions> =
-    Lazy::new(|| Versions::load().expect("load greentic crate versions"));
+static VERSIONS: OnceCell<Versions> = OnceCell::new();
+
+fn versions() -> Result<&'static Versions> {
+    VERSIONS.get_or_try_init(Versions::load)
+}
 
 pub fn run_component_command(command: ComponentCommands) -> Result<()> {
     match command {
         ComponentCommands::New(args) => new_component(args),
         ComponentCommands::Validate(args) => validate_command(args),
         ComponentCommands::Pack(args) => pack_command(args),
+        ComponentCommands::Templates(TemplatesCommand::List(args)) => list_templates(args),
     }
 }
 
@@ -180,6 +219,9 @@ pub enum ComponentCommands {
     Validate(ValidateArgs),
     /// Package a component into `packs/<name>/<version>`
     Pack(PackArgs),
+    /// Inspect the templates `new` can scaffold from
+    #[command(subcommand)]
+    Templates(TemplatesCommand),
 }
 
 #[derive(Args, Debug, Clone)]
@@ -189,6 +231,38 @@ pub struct NewComponentArgs {
     /// Optional directory where the component should be created
     #[arg(long, value_name = "DIR")]
     dir: Option<PathBuf>,
+    /// Template to scaffold from: a built-in kind, a name from the templates directory, or a
+    /// template directory / .tar.gz path (defaults to `defaults.component.template`, then `plain`)
+    #[arg(long, value_name = "TEMPLATE")]
+    template: Option<String>,
+    /// Set a template variable (repeatable)
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_var)]
+    vars: Vec<(String, String)>,
+    /// Use defaults for variables not set with --var instead of prompting
+    #[arg(long)]
+    no_prompt: bool,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum TemplatesCommand {
+    /// List built-in and local component templates
+    List(TemplatesListArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct TemplatesListArgs {
+    /// Emit the list as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+fn parse_var(raw: &str) -> Result<(String, String), String> {
+    match raw.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected NAME=VALUE, got `{raw}`")),
+    }
 }
 
 #[derive(Args, Debug, Clone)]
@@ -215,7 +289,18 @@ pub struct PackArgs {
 }
 
 pub fn new_component(args: NewComponentArgs) -> Result<()> {
-    let context = TemplateContext::new(&args.name)?;
+    let defaults = config::load()?.defaults.component;
+    let template_spec = args
+        .template
+        .or(defaults.template)
+        .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string());
+    let template = component_templates::load(&template_spec)?;
+    let org = defaults.org.unwrap_or_else(|| DEFAULT_ORG.to_string());
+    let mut context = TemplateContext::new(&args.name, &org, &template)?;
+    let interactive = !args.no_prompt && component_templates::interactive();
+    let variables = template.resolve_variables(&context.placeholders, &args.vars, interactive)?;
+    context.placeholders.extend(variables);
+
     let workspace_root = workspace_root()?;
     let base_dir = match args.dir {
         Some(dir) => normalize_under_root(&workspace_root, &dir)?,
@@ -233,34 +318,19 @@ pub fn new_component(args: NewComponentArgs) -> Result<()> {
     }
 
     println!(
-        "Creating new component scaffold at `{}`",
-        component_dir.display()
+        "Creating new component scaffold at `{}` from template `{}` ({})",
+        component_dir.display(),
+        template.name,
+        template.source
     );
 
-    create_dir(component_dir.join("src"))?;
-    create_dir(component_dir.join("schemas/v1"))?;
-
-    write_template(
-        &component_dir.join("Cargo.toml"),
-        TEMPLATE_COMPONENT_CARGO,
-        &context,
-    )?;
-    write_template(&component_dir.join("README.md"), TEMPLATE_README, &context)?;
-    write_template(
-        &component_dir.join("provider.toml"),
-        TEMPLATE_PROVIDER,
-        &context,
-    )?;
-    write_template(
-        &component_dir.join("src/lib.rs"),
-        TEMPLATE_SRC_LIB,
-        &context,
-    )?;
-    write_template(
-        &component_dir.join("schemas/v1/config.schema.json"),
-        TEMPLATE_SCHEMA_CONFIG,
-        &context,
-    )?;
+    for file in &template.files {
+        let path = component_dir.join(&file.path);
+        if let Some(parent) = path.parent() {
+            create_dir(parent.to_path_buf())?;
+        }
+        write_template(&path, &file.contents, &context)?;
+    }
 
     println!(
         "Component `{}` scaffolded successfully.",
@@ -270,6 +340,32 @@ pub fn new_component(args: NewComponentArgs) -> Result<()> {
     Ok(())
 }
 
+fn list_templates(args: TemplatesListArgs) -> Result<()> {
+    let templates = component_templates::list()?;
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&templates).context("failed to encode template list")?
+        );
+        return Ok(());
+    }
+    let width = templates
+        .iter()
+        .map(|template| template.name.len())
+        .max()
+        .unwrap_or(0);
+    for template in &templates {
+        println!(
+            "{:<width$}  {}  ({})",
+            template.name, template.description, template.source
+        );
+    }
+    if let Some(dir) = component_templates::templates_dir() {
+        println!("\nLocal templates: {}", dir.display());
+    }
+    Ok(())
+}
+
 pub fn validate_command(args: ValidateArgs) -> Result<()> {
     let workspace_root = workspace_root()?;
     let report = validate_component(&workspace_root, &args.path, !args.skip_build)?;
@@ -280,6 +376,7 @@ pub fn validate_command(args: ValidateArgs) -> Result<()> {
 pub fn pack_command(args: PackArgs) -> Result<()> {
     let workspace_root = workspace_root()?;
     let report = validate_component(&workspace_root, &args.path, !args.skip_build)?;
+    emit_warnings(&report);
     let base_out = match args.out_dir {
         Some(ref dir) if dir.is_absolute() => {
             bail!("--out-dir must be relative to the component directory")
@@ -319,10 +416,6 @@ pub fn pack_command(args: PackArgs) -> Result<()> {
 
     let mut meta = report.provider.clone();
     meta.artifact.path = artifact_file.clone();
-    meta.artifact.sha256 = report.sha256.clone();
-    meta.artifact.created = OffsetDateTime::now_utc()
-        .format(&Rfc3339)
-        .context("unable to format timestamp")?;
     meta.abi.wit_packages = report.packages.clone();
 
     let meta_path = dest_dir.join("meta.json");
@@ -344,190 +437,19 @@ fn create_dir(path: PathBuf) -> Result<()> {
         .with_context(|| format!("failed to create directory `{}`", path.display()))
 }
 
-fn write_template(path: &Path, template: &str, context: &TemplateContext) -> Result<()> {
+/// Render a template file; files that are not UTF-8 are copied as-is.
+fn write_template(path: &Path, contents: &[u8], context: &TemplateContext) -> Result<()> {
     if path.exists() {
         bail!("file `{}` already exists", path.display());
     }
 
-    let rendered = render_template(template, context);
+    let rendered = match std::str::from_utf8(contents) {
+        Ok(text) => component_templates::render(text, &context.placeholders).into_bytes(),
+        Err(_) => contents.to_vec(),
+    };
     fs::write(path, rendered).with_context(|| format!("failed to write `{}`", path.display()))
 }
 
-fn render_template(template: &str, context: &TemplateContext) -> String {
-    let mut output = template.to_owned();
-    for (key, value) in &context.placeholders {
-        let token = format!("{{{{{key}}}}}");
-        output = output.replace(&token, value);
-    }
-    output
-}
-
-fn detect_component_node_world_version(crate_root: &Path) -> Result<String> {
-    let wit_dir = crate_root.join("wit");
-    let namespace_dir = wit_dir.join("greentic");
-    let prefix = "component@";
-    let mut best: Option<(Version, PathBuf)> = None;
-
-    for entry in fs::read_dir(&namespace_dir).with_context(|| {
-        format!(
-            "failed to read namespace directory {}",
-            namespace_dir.display()
-        )
-    })? {
-        let entry = entry?;
-        let path = entry.path();
-        if !path.is_dir() {
-            continue;
-        }
-        let name = entry
-            .file_name()
-            .into_string()
-            .map_err(|_| anyhow!("non-unicode filename under {}", namespace_dir.display()))?;
-        if let Some(rest) = name.strip_prefix(prefix) {
-            let version = Version::parse(rest)
-                .with_context(|| format!("invalid semver `{rest}` for {prefix}"))?;
-            let package_path = path.join("package.wit");
-            let contents = fs::read_to_string(&package_path).with_context(|| {
-                format!("failed to read package file {}", package_path.display())
-            })?;
-            if contents.contains("export node") {
-                match &best {
-                    Some((best_ver, _)) if version <= *best_ver => {}
-                    _ => best = Some((version, path)),
-                }
-            }
-        }
-    }
-
-    if let Some((version, _)) = best {
-        return Ok(version.to_string());
-    }
-
-    detect_wit_package_version(crate_root, "component")
-}
-
-fn detect_wit_package_version(crate_root: &Path, prefix: &str) -> Result<String> {
-    let wit_dir = crate_root.join("wit");
-    let namespace_dir = wit_dir.join("greentic");
-    let prefix = format!("{prefix}@");
-
-    let mut best: Option<(Version, PathBuf)> = None;
-    for entry in fs::read_dir(&namespace_dir).with_context(|| {
-        format!(
-            "failed to read namespace directory {}",
-            namespace_dir.display()
-        )
-    })? {
-        let entry = entry?;
-        let path = entry.path();
-        if !path.is_dir() {
-            continue;
-        }
-        let name = entry
-            .file_name()
-            .into_string()
-            .map_err(|_| anyhow!("non-unicode filename under {}", namespace_dir.display()))?;
-        if let Some(rest) = name.strip_prefix(&prefix) {
-            let version = Version::parse(rest)
-                .with_context(|| format!("invalid semver `{rest}` for {prefix}"))?;
-            if best.as_ref().is_none_or(|(current, _)| &version > current) {
-                best = Some((version, path));
-            }
-        }
-    }
-
-    match best {
-        Some((version, _)) => Ok(version.to_string()),
-        None => Err(anyhow!(
-            "unable to locate WIT package `{}` under {}",
-            prefix,
-            namespace_dir.display()
-        )),
-    }
-}
-
-#[derive(Deserialize)]
-struct LockPackage {
-    name: String,
-    version: String,
-}
-
-#[derive(Deserialize)]
-struct LockFile {
-    package: Vec<LockPackage>,
-}
-
-fn resolved_version(crate_name: &str) -> Result<String> {
-    let lock_path = WORKSPACE_ROOT.join("Cargo.lock");
-    let contents = fs::read_to_string(&lock_path)
-        .with_context(|| format!("failed to read {}", lock_path.display()))?;
-    let lock: LockFile =
-        toml::from_str(&contents).with_context(|| format!("invalid {}", lock_path.display()))?;
-
-    let mut best: Option<(Version, String)> = None;
-    for pkg in lock
-        .package
-        .into_iter()
-        .filter(|pkg| pkg.name == crate_name)
-    {
-        let version = Version::parse(&pkg.version)
-            .with_context(|| format!("invalid semver `{}` for {}", pkg.version, crate_name))?;
-        if best.as_ref().is_none_or(|(current, _)| &version > current) {
-            best = Some((version, pkg.version));
-        }
-    }
-
-    match best {
-        Some((_, version)) => Ok(version),
-        None => Err(anyhow!(
-            "crate `{}` not found in {}",
-            crate_name,
-            lock_path.display()
-        )),
-    }
-}
-
-fn cargo_home() -> Result<PathBuf> {
-    if let Ok(path) = env::var("CARGO_HOME") {
-        return Ok(PathBuf::from(path));
-    }
-    if let Ok(home) = env::var("HOME") {
-        return Ok(PathBuf::from(home).join(".cargo"));
-    }
-    Err(anyhow!(
-        "unable to determine CARGO_HOME; set the environment variable explicitly"
-    ))
-}
-
-fn find_crate_source(crate_name: &str, version: &str) -> Result<PathBuf> {
-    let home = cargo_home()?;
-    let registry_src = home.join("registry/src");
-    if !registry_src.exists() {
-        return Err(anyhow!(
-            "cargo registry src directory not found at {}",
-            registry_src.display()
-        ));
-    }
-
-    for index in fs::read_dir(&registry_src)? {
-        let index_path = index?.path();
-        if !index_path.is_dir() {
-            continue;
-        }
-        let candidate = index_path.join(format!("{crate_name}-{version}"));
-        if candidate.exists() {
-            return Ok(candidate);
-        }
-    }
-
-    Err(anyhow!(
-        "crate `{}` version `{}` not found under {}",
-        crate_name,
-        version,
-        registry_src.display()
-    ))
-}
-
 struct TemplateContext {
     component_name: String,
     component_kebab: String,
@@ -535,7 +457,7 @@ struct TemplateContext {
 }
 
 impl TemplateContext {
-    fn new(raw: &str) -> Result<Self> {
+    fn new(raw: &str, org: &str, template: &ComponentTemplate) -> Result<Self> {
         let trimmed = raw.trim();
         if trimmed.is_empty() {
             bail!("component name cannot be empty");
@@ -545,10 +467,16 @@ impl TemplateContext {
         let component_snake = trimmed.to_case(Case::Snake);
         let component_pascal = trimmed.to_case(Case::Pascal);
         let component_name = component_kebab.clone();
-        let versions = VERSIONS.clone();
+        let versions = versions()?;
 
         let mut placeholders = HashMap::new();
         placeholders.insert("component_name".into(), component_name.clone());
+        placeholders.insert("org".into(), org.to_string());
+        placeholders.insert("template".into(), template.name.clone());
+        placeholders.insert(
+            "template_description".into(),
+            template.manifest.description.clone(),
+        );
         placeholders.insert("component_kebab".into(), component_kebab.clone());
         placeholders.insert("component_snake".into(), component_snake.clone());
         placeholders.insert("component_pascal".into(), component_pascal.clone());
@@ -609,6 +537,14 @@ fn print_validation_summary(report: &ValidationReport) {
     for pkg in &report.packages {
         println!("    - {pkg}");
     }
+    emit_warnings(report);
+}
+
+/// Report the capability declarations that did not match the component's imports.
+fn emit_warnings(report: &ValidationReport) {
+    for warning in &report.warnings {
+        events::warning(diagnostics::COMPONENT_CAPABILITY_MISMATCH, warning);
+    }
 }
 
 fn validate_component(workspace_root: &Path, path: &Path, build: bool) -> Result<ValidationReport> {
@@ -620,10 +556,9 @@ fn validate_component(workspace_root: &Path, path: &Path, build: bool) -> Result
     }
 
     let provider_path = normalize_under_root(&component_dir, Path::new("provider.toml"))?;
-    let provider = load_provider(&provider_path)?;
+    let mut provider = load_provider(&provider_path)?;
 
-    let versions = Versions::load()?;
-    ensure_version_alignment(&provider, &versions)?;
+    ensure_version_alignment(&provider, versions()?)?;
 
     let mut attempted = Vec::new();
     let mut artifact_path: Option<PathBuf> = None;
@@ -711,6 +646,9 @@ fn validate_component(workspace_root: &Path, path: &Path, build: bool) -> Result
         }
     }
 
+    let warnings = check_capabilities(&provider.capabilities, &world_imports(&resolve, world_id))?;
+    record_artifact(&provider_path, &mut provider, &sha256)?;
+
     Ok(ValidationReport {
         provider,
         component_dir,
@@ -718,9 +656,92 @@ fn validate_component(workspace_root: &Path, path: &Path, build: bool) -> Result
         sha256,
         world,
         packages,
+        warnings,
     })
 }
 
+/// Compare declared capabilities with the packages the component imports. Undeclared
+/// capabilities are errors (warnings for `filesystem`); declared but unused ones are returned
+/// as warnings.
+fn check_capabilities(
+    capabilities: &CapabilitiesSection,
+    imports: &BTreeSet<String>,
+) -> Result<Vec<String>> {
+    let mut missing = Vec::new();
+    let mut warnings = Vec::new();
+    for rule in CAPABILITY_RULES {
+        let used: Vec<&str> = rule
+            .packages
+            .iter()
+            .copied()
+            .filter(|pkg| imports.contains(*pkg))
+            .collect();
+        let declared = capabilities.declared(rule.capability);
+        if !used.is_empty() && !declared {
+            let message = format!(
+                "component imports {} but provider.toml does not declare the `{}` capability",
+                used.join(", "),
+                rule.capability
+            );
+            if rule.required {
+                missing.push((rule.capability, message));
+            } else {
+                warnings.push(message);
+            }
+        } else if used.is_empty() && declared {
+            warnings.push(format!(
+                "provider.toml declares the `{}` capability but the component imports none of {}",
+                rule.capability,
+                rule.packages.join(", ")
+            ));
+        }
+    }
+    if !missing.is_empty() {
+        let help = missing
+            .iter()
+            .map(|(capability, _)| format!("`{capability} = true`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = missing
+            .into_iter()
+            .map(|(_, message)| message)
+            .collect::<Vec<_>>()
+            .join("; ");
+        bail!(
+            Diagnostic::new(diagnostics::COMPONENT_CAPABILITY_MISMATCH, message).with_help(
+                format!("declare {help} under [capabilities] in provider.toml")
+            )
+        );
+    }
+    Ok(warnings)
+}
+
+/// Write the validated artifact hash into `[artifact]` of provider.toml, keeping comments and
+/// layout. `created` only moves when the hash changes, so revalidating is a no-op.
+fn record_artifact(path: &Path, provider: &mut ProviderMetadata, sha256: &str) -> Result<()> {
+    if provider.artifact.sha256 == sha256 && !provider.artifact.created.is_empty() {
+        return Ok(());
+    }
+    let created = OffsetDateTime::now_utc()
+        .format(&Rfc3339)
+        .context("unable to format timestamp")?;
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("failed to read provider metadata {}", path.display()))?;
+    let mut doc: toml_edit::DocumentMut = contents
+        .parse()
+        .context("provider.toml is not valid TOML")?;
+    let artifact = doc["artifact"]
+        .as_table_mut()
+        .context("provider.toml [artifact] must be a table")?;
+    artifact["sha256"] = toml_edit::value(sha256);
+    artifact["created"] = toml_edit::value(created.as_str());
+    fs::write(path, doc.to_string())
+        .with_context(|| format!("failed to update {}", path.display()))?;
+    provider.artifact.sha256 = sha256.to_string();
+    provider.artifact.created = created;
+    Ok(())
+}
+
 fn resolve_path(base: &Path, raw: impl AsRef<Path>) -> PathBuf {
     let raw_path = raw.as_ref();
     if raw_path.is_absolute() {
@@ -753,13 +774,14 @@ fn candidate_artifact_paths(original: &str) -> Vec<String> {
     paths
 }
 
-fn ensure_cargo_component_installed() -> Result<()> {
-    let status = Command::new("cargo")
+/// Fail unless `cargo component --version` runs; also used by `greentic-dev doctor`.
+pub(crate) fn ensure_cargo_component_installed() -> Result<()> {
+    let output = Command::new("cargo")
         .arg("component")
         .arg("--version")
-        .status();
-    match status {
-        Ok(status) if status.success() => Ok(()),
+        .output();
+    match output {
+        Ok(output) if output.status.success() => Ok(()),
         Ok(_) => bail!(
             "cargo-component is required. Install with `cargo install cargo-component --locked`."
         ),
@@ -881,6 +903,21 @@ fn extract_wit_metadata(
     Ok((packages, world_string, export_package))
 }
 
+/// Unversioned `namespace:name` of every package the world imports an interface from.
+fn world_imports(resolve: &Resolve, world_id: WorldId) -> BTreeSet<String> {
+    resolve.worlds[world_id]
+        .imports
+        .values()
+        .filter_map(|item| match item {
+            WorldItem::Interface { id, .. } => resolve.interfaces[*id].package,
+            _ => None,
+        })
+        .map(|pkg_id| &resolve.packages[pkg_id].name)
+        .filter(|name| name.namespace != "root")
+        .map(|name| format!("{}:{}", name.namespace, name.name))
+        .collect()
+}
+
 fn world_to_package_id(world: &str) -> Option<String> {
     let (pkg_part, rest) = world.split_once('/')?;
     let (_, version) = rest.rsplit_once('@')?;
@@ -893,3 +930,213 @@ fn workspace_root() -> Result<PathBuf> {
         .canonicalize()
         .context("failed to canonicalize workspace root")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semver::Version;
+
+    #[derive(Deserialize)]
+    struct LockPackage {
+        name: String,
+        version: String,
+    }
+
+    #[derive(Deserialize)]
+    struct LockFile {
+        package: Vec<LockPackage>,
+    }
+
+    fn locked_version(lock: &LockFile, crate_name: &str) -> Option<Version> {
+        lock.package
+            .iter()
+            .filter(|pkg| pkg.name == crate_name)
+            .filter_map(|pkg| Version::parse(&pkg.version).ok())
+            .max()
+    }
+
+    /// `wit/greentic` of the locked greentic-interfaces sources, when the registry has them.
+    fn interfaces_wit_dir(version: &str) -> Option<PathBuf> {
+        let cargo_home = env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))?;
+        fs::read_dir(cargo_home.join("registry/src"))
+            .ok()?
+            .filter_map(|index| index.ok())
+            .map(|index| {
+                index
+                    .path()
+                    .join(format!("greentic-interfaces-{version}/wit/greentic"))
+            })
+            .find(|dir| dir.is_dir())
+    }
+
+    /// Newest version of each `greentic:*` package declared under `wit_dir`, plus the newest
+    /// `greentic:component` whose world exports `node`.
+    fn declared_packages(wit_dir: &Path) -> (HashMap<String, Version>, Option<Version>) {
+        let mut newest: HashMap<String, Version> = HashMap::new();
+        let mut node_component = None;
+        for entry in fs::read_dir(wit_dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+        {
+            let Ok(contents) = fs::read_to_string(entry.path().join("package.wit")) else {
+                continue;
+            };
+            let Some((name, version)) = contents.lines().find_map(|line| {
+                let decl = line.trim().strip_prefix("package ")?.trim_end_matches(';');
+                let (name, version) = decl.split_once('@')?;
+                Some((name.to_string(), Version::parse(version).ok()?))
+            }) else {
+                continue;
+            };
+            if name == "greentic:component" && contents.contains("export node") {
+                node_component = node_component.max(Some(version.clone()));
+            }
+            let current = newest.entry(name).or_insert_with(|| version.clone());
+            if version > *current {
+                *current = version;
+            }
+        }
+        (newest, node_component)
+    }
+
+    #[test]
+    fn embedded_versions_match_cargo_lock() {
+        let versions = versions().expect("embedded versions load");
+        let lock_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.lock");
+        let lock: LockFile = toml::from_str(&fs::read_to_string(lock_path).unwrap()).unwrap();
+        for (crate_name, embedded) in [
+            ("greentic-interfaces", &versions.interfaces),
+            ("greentic-types", &versions.types),
+            ("greentic-component", &versions.component_runtime),
+        ] {
+            let locked = locked_version(&lock, crate_name).unwrap().to_string();
+            assert_eq!(
+                &locked, embedded,
+                "update `{crate_name}` in templates/component/versions.toml to {locked}"
+            );
+        }
+    }
+
+    #[test]
+    fn embedded_versions_match_wit_sources() {
+        let versions = versions().expect("embedded versions load");
+        let Some(wit_dir) = interfaces_wit_dir(&versions.interfaces) else {
+            eprintln!("Skipping test; greentic-interfaces sources are not in the cargo registry");
+            return;
+        };
+        let (declared, node_component) = declared_packages(&wit_dir);
+        assert_eq!(
+            node_component.map(|version| version.to_string()).as_ref(),
+            Some(&versions.component_wit_version),
+            "update `greentic:component` in templates/component/versions.toml"
+        );
+        for (package, embedded) in [
+            ("greentic:secrets-store", &versions.secrets_wit_version),
+            ("greentic:state", &versions.state_wit_version),
+            ("greentic:http", &versions.http_wit_version),
+            ("greentic:telemetry", &versions.telemetry_wit_version),
+        ] {
+            let found = declared
+                .get(package)
+                .unwrap_or_else(|| panic!("{package} not declared under {}", wit_dir.display()))
+                .to_string();
+            assert_eq!(
+                &found, embedded,
+                "update `{package}` in templates/component/versions.toml to {found}"
+            );
+        }
+    }
+
+    fn capabilities(secrets: bool, network: bool, filesystem: bool) -> CapabilitiesSection {
+        CapabilitiesSection {
+            secrets,
+            telemetry: false,
+            network,
+            filesystem,
+        }
+    }
+
+    fn imports(packages: &[&str]) -> BTreeSet<String> {
+        packages.iter().map(|pkg| pkg.to_string()).collect()
+    }
+
+    #[test]
+    fn undeclared_capability_imports_are_errors() {
+        let err = check_capabilities(
+            &capabilities(false, false, false),
+            &imports(&["wasi:http", "greentic:secrets-store", "wasi:io"]),
+        )
+        .unwrap_err();
+        let diagnostic = err.downcast_ref::<Diagnostic>().unwrap();
+        assert_eq!(diagnostic.code, diagnostics::COMPONENT_CAPABILITY_MISMATCH);
+        assert!(diagnostic.message.contains("wasi:http"));
+        assert!(diagnostic.message.contains("greentic:secrets-store"));
+        assert_eq!(
+            diagnostic.help.as_deref(),
+            Some(
+                "declare `network = true`, `secrets = true` under [capabilities] in provider.toml"
+            )
+        );
+    }
+
+    #[test]
+    fn unused_capabilities_and_filesystem_imports_are_warnings() {
+        let warnings = check_capabilities(
+            &capabilities(true, true, false),
+            &imports(&["wasi:http", "wasi:filesystem"]),
+        )
+        .unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("`secrets` capability"));
+        assert!(warnings[1].contains("wasi:filesystem"));
+
+        let warnings = check_capabilities(
+            &capabilities(false, true, true),
+            &imports(&["wasi:http", "wasi:filesystem"]),
+        )
+        .unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn record_artifact_keeps_comments_and_created_for_unchanged_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("provider.toml");
+        fs::write(
+            &path,
+            "# --- pack/build ---\n[artifact]\nformat  = \"wasm-component\"\nsha256  = \"\"\n",
+        )
+        .unwrap();
+        let mut provider: ProviderMetadata = toml::from_str(
+            r#"
+            name = "demo"
+            version = "0.1.0"
+            [abi]
+            interfaces_version = "0"
+            types_version = "0"
+            component_runtime = "0"
+            world = "greentic:component/component@0.6.0"
+            [capabilities]
+            [exports]
+            [artifact]
+            format = "wasm-component"
+            path = "demo.wasm"
+            "#,
+        )
+        .unwrap();
+
+        record_artifact(&path, &mut provider, "abc").unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("# --- pack/build ---\n"));
+        assert!(written.contains("sha256  = \"abc\""));
+        let created = provider.artifact.created.clone();
+        assert!(written.contains(&format!("created = \"{created}\"")));
+
+        fs::write(&path, "unchanged").unwrap();
+        record_artifact(&path, &mut provider, "abc").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "unchanged");
+        assert_eq!(provider.artifact.created, created);
+    }
+}
@@ -6,6 +6,7 @@ use std::sync::Arc;
 use anyhow::{Context, Result, anyhow, bail};
 use greentic_component::describe::{DescribePayload, DescribeVersion};
 use greentic_component::lifecycle::Lifecycle;
+use greentic_component::limits::Limits;
 use greentic_component::manifest::ComponentManifest;
 use greentic_component::prepare::PreparedComponent;
 use greentic_component::prepare_component;
@@ -15,6 +16,8 @@ use semver::{Version, VersionReq};
 use serde::Serialize;
 use serde_json::Value as JsonValue;
 
+use crate::diagnostics::{self, Diagnostic};
+
 #[derive(Debug, Clone)]
 pub struct ResolvedComponent {
     pub name: String,
@@ -25,7 +28,6 @@ pub struct ResolvedComponent {
     pub schema_json: Option<String>,
     pub manifest_json: Option<String>,
     pub capabilities_json: Option<JsonValue>,
-    #[allow(dead_code)]
     pub limits_json: Option<JsonValue>,
     pub world: String,
     pub wasm_hash: String,
@@ -33,6 +35,19 @@ pub struct ResolvedComponent {
     describe: DescribePayload,
 }
 
+impl ResolvedComponent {
+    /// Resource limits declared in the component manifest, if any.
+    pub fn limits(&self) -> Result<Option<Limits>> {
+        self.limits_json
+            .as_ref()
+            .map(|value| {
+                serde_json::from_value(value.clone())
+                    .with_context(|| format!("component `{}` declares invalid limits", self.name))
+            })
+            .transpose()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ResolvedNode {
     pub node_id: String,
@@ -178,6 +193,19 @@ impl ComponentResolver {
             ComponentTarget::Path(path) => path.display().to_string(),
         };
 
+        if let ComponentTarget::Path(path) = &target
+            && !path.exists()
+        {
+            bail!(
+                Diagnostic::new(
+                    diagnostics::UNKNOWN_COMPONENT,
+                    format!("unknown component `{name}`: {target_display} does not exist"),
+                )
+                .with_help(
+                    "check the component name, pass --component-dir, or run `greentic-dev component add`"
+                )
+            );
+        }
         let prepared = prepare_component(target.as_ref()).with_context(|| {
             format!(
                 "resolver looked for `{name}` via `{target_display}` but prepare_component failed"
@@ -185,10 +213,13 @@ impl ComponentResolver {
         })?;
 
         if !version_req.matches(&prepared.manifest.version) {
-            bail!(
-                "component `{name}` version `{}` does not satisfy requirement `{version_req}`",
-                prepared.manifest.version
-            );
+            bail!(Diagnostic::new(
+                diagnostics::COMPONENT_VERSION_MISMATCH,
+                format!(
+                    "component `{name}` version `{}` does not satisfy requirement `{version_req}`",
+                    prepared.manifest.version
+                ),
+            ));
         }
 
         let key = ComponentCacheKey::new(name, &prepared.manifest.version);
@@ -0,0 +1,592 @@
+//! Component scaffold templates: the built-in kinds plus local template directories and tarballs.
+//!
+//! A template is a directory with a `template.toml` manifest next to the files it renders. Files
+//! ending in `.in` lose the suffix when written (so a template can ship `Cargo.toml.in` without
+//! becoming a cargo package itself). `{{name}}` placeholders are replaced by the built-in values
+//! (`component_name`, `org`, `interfaces_version`, ...) and by the variables the manifest declares.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+
+/// Overrides the directory holding named local templates
+/// (defaults to `$XDG_CONFIG_HOME/greentic-dev/templates`).
+pub const TEMPLATES_DIR_ENV: &str = "GREENTIC_DEV_TEMPLATES_DIR";
+
+/// Template used when neither `--template` nor `defaults.component.template` is set.
+pub const DEFAULT_TEMPLATE: &str = "plain";
+
+/// Organisation used when `defaults.component.org` is not set.
+pub const DEFAULT_ORG: &str = "greentic-ai";
+
+const MANIFEST_FILE: &str = "template.toml";
+
+macro_rules! template_file {
+    ($dir:literal, $path:literal) => {
+        (
+            $path,
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/templates/component/",
+                $dir,
+                "/",
+                $path
+            )),
+        )
+    };
+}
+
+macro_rules! builtin_kind {
+    ($name:literal) => {
+        BuiltinTemplate {
+            name: $name,
+            files: &[
+                template_file!($name, "template.toml"),
+                template_file!($name, "provider.toml"),
+                template_file!($name, "src/lib.rs"),
+                template_file!($name, "schemas/v1/config.schema.json"),
+            ],
+        }
+    };
+}
+
+struct BuiltinTemplate {
+    name: &'static str,
+    files: &'static [(&'static str, &'static str)],
+}
+
+/// Files every built-in kind shares.
+const SHARED_FILES: &[(&str, &str)] = &[
+    template_file!("shared", "Cargo.toml.in"),
+    template_file!("shared", "README.md"),
+];
+
+const BUILTIN_TEMPLATES: &[BuiltinTemplate] = &[
+    builtin_kind!("plain"),
+    builtin_kind!("provider"),
+    builtin_kind!("messaging-adapter"),
+    builtin_kind!("http"),
+    builtin_kind!("stateful"),
+];
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateManifest {
+    /// One-line summary shown by `component templates list`.
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+}
+
+/// A value the template needs from its user, prompted for when `component new` runs in a
+/// terminal.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateVariable {
+    pub name: String,
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// May reference built-in placeholders, e.g. `{{component_name}}/api-key`.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "path", rename_all = "snake_case")]
+pub enum TemplateSource {
+    Builtin,
+    Directory(PathBuf),
+    Tarball(PathBuf),
+}
+
+impl std::fmt::Display for TemplateSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TemplateSource::Builtin => write!(f, "built-in"),
+            TemplateSource::Directory(path) | TemplateSource::Tarball(path) => {
+                write!(f, "{}", path.display())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateFile {
+    /// Output path relative to the component directory, `.in` suffix already stripped.
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ComponentTemplate {
+    pub name: String,
+    pub source: TemplateSource,
+    pub manifest: TemplateManifest,
+    pub files: Vec<TemplateFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateSummary {
+    pub name: String,
+    pub source: TemplateSource,
+    pub description: String,
+}
+
+/// Directory searched for named local templates.
+pub fn templates_dir() -> Option<PathBuf> {
+    match std::env::var_os(TEMPLATES_DIR_ENV) {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(dirs::config_dir)
+            .map(|dir| dir.join("greentic-dev").join("templates")),
+    }
+}
+
+/// Load a template by built-in name, by name under [`templates_dir`], or from a directory or
+/// tarball path.
+pub fn load(spec: &str) -> Result<ComponentTemplate> {
+    if let Some(builtin) = BUILTIN_TEMPLATES.iter().find(|t| t.name == spec) {
+        return load_builtin(builtin);
+    }
+    let path = Path::new(spec);
+    if path.exists() {
+        return load_path(path);
+    }
+    if let Some(dir) = templates_dir()
+        && let Some(found) = named_in(&dir, spec)
+    {
+        return load_path(&found);
+    }
+    let available = list()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|summary| summary.name)
+        .collect::<Vec<_>>()
+        .join(", ");
+    bail!(
+        "unknown component template `{spec}` (available: {available}); pass a template name, a directory or a .tar.gz path"
+    )
+}
+
+/// Built-in templates followed by those under [`templates_dir`].
+pub fn list() -> Result<Vec<TemplateSummary>> {
+    let mut summaries = Vec::new();
+    for builtin in BUILTIN_TEMPLATES {
+        let template = load_builtin(builtin)?;
+        summaries.push(template.summary());
+    }
+    let Some(dir) = templates_dir().filter(|dir| dir.is_dir()) else {
+        return Ok(summaries);
+    };
+    let mut entries = fs::read_dir(&dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir() || archive_stem(path).is_some())
+        .collect::<Vec<_>>();
+    entries.sort();
+    for path in entries {
+        match load_path(&path) {
+            Ok(template) => summaries.push(template.summary()),
+            Err(err) => summaries.push(TemplateSummary {
+                name: template_name(&path),
+                source: source_for(&path),
+                description: format!("invalid template: {err:#}"),
+            }),
+        }
+    }
+    Ok(summaries)
+}
+
+impl ComponentTemplate {
+    fn summary(&self) -> TemplateSummary {
+        TemplateSummary {
+            name: self.name.clone(),
+            source: self.source.clone(),
+            description: self.manifest.description.clone(),
+        }
+    }
+
+    /// Values for the manifest's variables: `provided` first, then an interactive answer when
+    /// `interactive`, then the declared default. Defaults may use the `builtins` placeholders.
+    pub fn resolve_variables(
+        &self,
+        builtins: &HashMap<String, String>,
+        provided: &[(String, String)],
+        interactive: bool,
+    ) -> Result<HashMap<String, String>> {
+        for (name, _) in provided {
+            if !self.manifest.variables.iter().any(|var| &var.name == name) {
+                let declared = self
+                    .manifest
+                    .variables
+                    .iter()
+                    .map(|var| var.name.as_str())
+                    .collect::<Vec<_>>();
+                bail!(
+                    "template `{}` has no variable `{name}` (declared: {})",
+                    self.name,
+                    if declared.is_empty() {
+                        "none".to_string()
+                    } else {
+                        declared.join(", ")
+                    }
+                );
+            }
+        }
+
+        let mut values = HashMap::new();
+        for var in &self.manifest.variables {
+            if builtins.contains_key(&var.name) {
+                bail!(
+                    "template `{}` declares variable `{}`, which is a reserved placeholder",
+                    self.name,
+                    var.name
+                );
+            }
+            let default = var
+                .default
+                .as_deref()
+                .map(|default| render(default, builtins));
+            let value = match provided.iter().rev().find(|(name, _)| name == &var.name) {
+                Some((_, value)) => value.clone(),
+                None if interactive => prompt(var, default.as_deref())?,
+                None => default.ok_or_else(|| {
+                    anyhow!(
+                        "template variable `{}` has no default; pass --var {}=<value>",
+                        var.name,
+                        var.name
+                    )
+                })?,
+            };
+            values.insert(var.name.clone(), value);
+        }
+        Ok(values)
+    }
+}
+
+/// Replace every `{{key}}` in `template` with its value.
+pub fn render(template: &str, placeholders: &HashMap<String, String>) -> String {
+    let mut output = template.to_owned();
+    for (key, value) in placeholders {
+        let token = format!("{{{{{key}}}}}");
+        output = output.replace(&token, value);
+    }
+    output
+}
+
+/// Whether variables should be prompted for: stdin is a terminal and `CI` is unset.
+pub fn interactive() -> bool {
+    io::stdin().is_terminal() && std::env::var_os("CI").is_none()
+}
+
+fn prompt(var: &TemplateVariable, default: Option<&str>) -> Result<String> {
+    let label = var.prompt.as_deref().unwrap_or(&var.name);
+    let mut stderr = io::stderr();
+    loop {
+        match default {
+            Some(default) => write!(stderr, "{label} [{default}]: ")?,
+            None => write!(stderr, "{label}: ")?,
+        }
+        stderr.flush()?;
+        let mut answer = String::new();
+        let read = io::stdin()
+            .lock()
+            .read_line(&mut answer)
+            .with_context(|| format!("failed to read a value for `{}`", var.name))?;
+        let answer = answer.trim();
+        match (answer.is_empty(), default) {
+            (false, _) => return Ok(answer.to_string()),
+            (true, Some(default)) => return Ok(default.to_string()),
+            (true, None) if read == 0 => bail!("no value given for `{}`", var.name),
+            (true, None) => writeln!(stderr, "`{}` is required", var.name)?,
+        }
+    }
+}
+
+fn load_builtin(builtin: &BuiltinTemplate) -> Result<ComponentTemplate> {
+    let files = SHARED_FILES
+        .iter()
+        .chain(builtin.files)
+        .map(|(path, contents)| (PathBuf::from(path), contents.as_bytes().to_vec()))
+        .collect();
+    from_files(builtin.name, TemplateSource::Builtin, files)
+        .with_context(|| format!("built-in template `{}` is invalid", builtin.name))
+}
+
+fn load_path(path: &Path) -> Result<ComponentTemplate> {
+    let name = template_name(path);
+    let source = source_for(path);
+    let files = if path.is_dir() {
+        read_dir_files(path)?
+    } else {
+        let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        strip_root_dir(
+            read_tar(&bytes)
+                .with_context(|| format!("{} is not a tar or tar.gz archive", path.display()))?,
+        )
+    };
+    from_files(&name, source, files)
+        .with_context(|| format!("failed to load template from {}", path.display()))
+}
+
+fn from_files(
+    name: &str,
+    source: TemplateSource,
+    files: Vec<(PathBuf, Vec<u8>)>,
+) -> Result<ComponentTemplate> {
+    let mut manifest = None;
+    let mut rendered = Vec::new();
+    for (path, contents) in files {
+        if path == Path::new(MANIFEST_FILE) {
+            let text = String::from_utf8(contents).context("template.toml is not UTF-8")?;
+            manifest =
+                Some(toml::from_str::<TemplateManifest>(&text).context("invalid template.toml")?);
+            continue;
+        }
+        let path = match path.extension() {
+            Some(ext) if ext == "in" => path.with_extension(""),
+            _ => path,
+        };
+        rendered.push(TemplateFile { path, contents });
+    }
+    let manifest = manifest.ok_or_else(|| anyhow!("missing {MANIFEST_FILE}"))?;
+    rendered.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(ComponentTemplate {
+        name: name.to_string(),
+        source,
+        manifest,
+        files: rendered,
+    })
+}
+
+fn named_in(dir: &Path, name: &str) -> Option<PathBuf> {
+    let candidate = dir.join(name);
+    if candidate.is_dir() {
+        return Some(candidate);
+    }
+    ["tar.gz", "tgz", "tar"]
+        .iter()
+        .map(|ext| dir.join(format!("{name}.{ext}")))
+        .find(|path| path.is_file())
+}
+
+fn archive_stem(path: &Path) -> Option<&str> {
+    let file_name = path.file_name()?.to_str()?;
+    [".tar.gz", ".tgz", ".tar"]
+        .iter()
+        .find_map(|ext| file_name.strip_suffix(ext))
+}
+
+fn template_name(path: &Path) -> String {
+    archive_stem(path)
+        .map(str::to_string)
+        .or_else(|| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+fn source_for(path: &Path) -> TemplateSource {
+    if path.is_dir() {
+        TemplateSource::Directory(path.to_path_buf())
+    } else {
+        TemplateSource::Tarball(path.to_path_buf())
+    }
+}
+
+fn read_dir_files(root: &Path) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("failed to read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                if path.file_name().is_some_and(|name| name == ".git") {
+                    continue;
+                }
+                pending.push(path);
+            } else {
+                let contents = fs::read(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                let relative = path
+                    .strip_prefix(root)
+                    .expect("walked paths live under the root")
+                    .to_path_buf();
+                files.push((relative, contents));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Regular files of a (optionally gzip-compressed) tar archive. Links and other special entries
+/// are skipped; absolute paths and `..` segments are rejected.
+fn read_tar(bytes: &[u8]) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+    let reader: Box<dyn Read + '_> = if bytes.starts_with(&[0x1f, 0x8b]) {
+        Box::new(GzDecoder::new(bytes))
+    } else {
+        Box::new(bytes)
+    };
+
+    let mut archive = tar::Archive::new(reader);
+    let mut files = Vec::new();
+    for entry in archive.entries().context("failed to read archive")? {
+        let mut entry = entry.context("failed to read archive entry")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry
+            .path()
+            .context("invalid archive entry path")?
+            .to_string_lossy()
+            .into_owned();
+        let path = safe_relative(&name)?;
+        let mut contents = Vec::new();
+        entry
+            .read_to_end(&mut contents)
+            .with_context(|| format!("failed to read archive entry `{name}`"))?;
+        files.push((path, contents));
+    }
+    Ok(files)
+}
+
+fn safe_relative(name: &str) -> Result<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => bail!("archive entry `{name}` escapes the template root"),
+        }
+    }
+    Ok(path)
+}
+
+/// Drop a single top-level directory (`my-template-1.0/...`) when the manifest lives inside it.
+fn strip_root_dir(files: Vec<(PathBuf, Vec<u8>)>) -> Vec<(PathBuf, Vec<u8>)> {
+    if files
+        .iter()
+        .any(|(path, _)| path == Path::new(MANIFEST_FILE))
+    {
+        return files;
+    }
+    let Some(root) = files
+        .iter()
+        .find(|(path, _)| path.ends_with(MANIFEST_FILE) && path.components().count() == 2)
+        .and_then(|(path, _)| path.parent().map(Path::to_path_buf))
+    else {
+        return files;
+    };
+    if !files.iter().all(|(path, _)| path.starts_with(&root)) {
+        return files;
+    }
+    files
+        .into_iter()
+        .map(|(path, contents)| {
+            let relative = path
+                .strip_prefix(&root)
+                .expect("checked above")
+                .to_path_buf();
+            (relative, contents)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tar_archive(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, contents) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *contents).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn builtin_kinds_load_with_manifests() {
+        for name in ["plain", "provider", "messaging-adapter", "http", "stateful"] {
+            let template = load(name).unwrap();
+            assert_eq!(template.source, TemplateSource::Builtin);
+            assert!(!template.manifest.description.is_empty());
+            let paths: Vec<_> = template.files.iter().map(|file| &file.path).collect();
+            assert!(
+                paths.contains(&&PathBuf::from("Cargo.toml")),
+                "{name}: {paths:?}"
+            );
+            assert!(paths.contains(&&PathBuf::from("src/lib.rs")));
+        }
+    }
+
+    #[test]
+    fn tarballs_load_with_their_root_directory_stripped() {
+        let archive = tar_archive(&[
+            (
+                "my-template-1.0/template.toml",
+                b"description = \"custom\"\n[[variables]]\nname = \"greeting\"\ndefault = \"hi {{component_name}}\"\n",
+            ),
+            ("my-template-1.0/src/lib.rs.in", b"// {{greeting}}\n"),
+        ]);
+
+        let files = strip_root_dir(read_tar(&archive).unwrap());
+        let template = from_files(
+            "my-template",
+            TemplateSource::Tarball("my-template.tar".into()),
+            files,
+        )
+        .unwrap();
+        assert_eq!(template.manifest.description, "custom");
+        assert_eq!(template.files[0].path, PathBuf::from("src/lib.rs"));
+
+        let builtins = HashMap::from([("component_name".to_string(), "demo".to_string())]);
+        let values = template.resolve_variables(&builtins, &[], false).unwrap();
+        assert_eq!(values["greeting"], "hi demo");
+        let values = template
+            .resolve_variables(&builtins, &[("greeting".into(), "hello".into())], false)
+            .unwrap();
+        assert_eq!(values["greeting"], "hello");
+        assert!(
+            template
+                .resolve_variables(&builtins, &[("nope".into(), "x".into())], false)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_entries_outside_the_template() {
+        // `Builder::append_data` refuses such paths, so write the raw name into the header.
+        let mut header = tar::Header::new_gnu();
+        header.as_gnu_mut().unwrap().name[..10].copy_from_slice(b"../evil.rs");
+        header.set_size(0);
+        header.set_cksum();
+        let mut builder = tar::Builder::new(Vec::new());
+        builder.append(&header, io::empty()).unwrap();
+        assert!(read_tar(&builder.into_inner().unwrap()).is_err());
+    }
+
+    #[test]
+    fn gzipped_archives_keep_long_entry_names() {
+        let long = format!("{}/lib.rs.in", "nested".repeat(20));
+        let archive = tar_archive(&[(long.as_str(), b"// long\n")]);
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(&archive).unwrap();
+
+        let files = read_tar(&encoder.finish().unwrap()).unwrap();
+        assert_eq!(files, vec![(PathBuf::from(long), b"// long\n".to_vec())]);
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use semver::Version;
+use serde::Serialize;
+
+use crate::component_add::run_component_add;
+use crate::distributor::{self, DevArtifactKind, DevIntent, DevResolveRequest, Distributor};
+use crate::events::{self, Event};
+use crate::lockfile::{self, WorkspaceLock};
+use crate::oci::OCI_SCHEME;
+use crate::pack_init::{
+    PackInitIntent, WorkspaceComponent, WorkspaceManifest, default_platform, handle_resolve_result,
+    manifest_path,
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutdatedEntry {
+    pub name: String,
+    pub coordinate: String,
+    pub profile: String,
+    pub current: String,
+    /// Newest version satisfying the coordinate's requirement.
+    pub compatible: String,
+    /// Newest version ignoring the requirement.
+    pub latest: String,
+}
+
+impl OutdatedEntry {
+    pub fn is_outdated(&self) -> bool {
+        self.current != self.compatible || self.current != self.latest
+    }
+}
+
+/// `component outdated`: re-resolve every workspace component and report newer versions.
+pub fn run_outdated(
+    profile: Option<&str>,
+    header_overrides: &[(String, String)],
+    emit_json: bool,
+) -> Result<()> {
+    let entries = collect_outdated(profile, header_overrides)?;
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::to_value(&entries).context("failed to encode outdated report")?,
+        });
+        return Ok(());
+    }
+    if emit_json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).context("failed to encode outdated report")?
+        );
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No distributor components recorded in the workspace manifest.");
+        return Ok(());
+    }
+    if !entries.iter().any(OutdatedEntry::is_outdated) {
+        println!("All components are up to date.");
+    }
+    print_table(&entries);
+    Ok(())
+}
+
+pub fn collect_outdated(
+    profile_override: Option<&str>,
+    header_overrides: &[(String, String)],
+) -> Result<Vec<OutdatedEntry>> {
+    let manifest = WorkspaceManifest::load(&manifest_path()?)?;
+    let lock = WorkspaceLock::load(&lockfile::lockfile_path()?)?;
+    let mut distributors: HashMap<Option<String>, Box<dyn Distributor>> = HashMap::new();
+
+    let mut entries = Vec::new();
+    for component in &manifest.components {
+        if !is_distributor_coordinate(&component.coordinate) {
+            continue;
+        }
+        let requested_profile = profile_override
+            .or_else(|| {
+                lock.component(&component.entry.name)
+                    .and_then(|entry| entry.profile.as_deref())
+            })
+            .map(str::to_string);
+        if !distributors.contains_key(&requested_profile) {
+            let distributor = distributor::open(requested_profile.as_deref(), header_overrides)
+                .context("`component outdated` needs the distributor")?;
+            distributors.insert(requested_profile.clone(), distributor);
+        }
+        let distributor = distributors[&requested_profile].as_ref();
+
+        let compatible = resolve_version(distributor, &component.coordinate)?;
+        let latest = resolve_version(distributor, &unversioned(&component.coordinate))?;
+        entries.push(OutdatedEntry {
+            name: component.entry.name.clone(),
+            coordinate: component.coordinate.clone(),
+            profile: distributor.profile_name().unwrap_or("stub").to_string(),
+            current: component.entry.version.to_string(),
+            compatible: compatible.to_string(),
+            latest: latest.to_string(),
+        });
+    }
+    Ok(entries)
+}
+
+/// `component update [name]`: re-add components so cache, manifest and lockfile pick up the newest
+/// compatible version.
+pub fn run_update(
+    name: Option<&str>,
+    profile: Option<&str>,
+    header_overrides: &[(String, String)],
+    accepted_licenses: &[String],
+) -> Result<()> {
+    let manifest = WorkspaceManifest::load(&manifest_path()?)?;
+    let targets: Vec<&WorkspaceComponent> = match name {
+        Some(name) => {
+            let Some(component) = manifest.component(name) else {
+                bail!("component `{name}` is not recorded in the workspace manifest");
+            };
+            vec![component]
+        }
+        None => manifest
+            .components
+            .iter()
+            .filter(|component| is_distributor_coordinate(&component.coordinate))
+            .collect(),
+    };
+
+    if targets.is_empty() {
+        if events::json() {
+            events::emit(Event::Result {
+                data: serde_json::json!({ "updated": [] }),
+            });
+        } else {
+            println!("No distributor components to update.");
+        }
+        return Ok(());
+    }
+
+    let lock = WorkspaceLock::load(&lockfile::lockfile_path()?)?;
+    let mut updated = Vec::new();
+    for component in targets {
+        let previous = component.entry.version.clone();
+        let requested_profile = profile.or_else(|| {
+            lock.component(&component.entry.name)
+                .and_then(|entry| entry.profile.as_deref())
+        });
+        run_component_add(
+            &component.coordinate,
+            requested_profile,
+            header_overrides,
+            accepted_licenses,
+            PackInitIntent::Dev,
+            false,
+        )
+        .with_context(|| format!("failed to update `{}`", component.entry.name))?;
+
+        let refreshed = WorkspaceManifest::load(&manifest_path()?)?;
+        if let Some(current) = refreshed.component(&component.entry.name) {
+            report_update(&component.entry.name, &previous, &current.entry.version);
+            updated.push(serde_json::json!({
+                "name": component.entry.name,
+                "previous": previous.to_string(),
+                "current": current.entry.version.to_string(),
+            }));
+        }
+    }
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::json!({ "updated": updated }),
+        });
+    }
+    Ok(())
+}
+
+fn report_update(name: &str, previous: &Version, current: &Version) {
+    if events::json() {
+        return;
+    }
+    if previous == current {
+        println!("{name} is up to date ({current})");
+    } else {
+        println!("Updated {name} {previous} -> {current}");
+    }
+}
+
+fn resolve_version(distributor: &dyn Distributor, coordinate: &str) -> Result<Version> {
+    let resolved = handle_resolve_result(distributor.resolve(&DevResolveRequest {
+        coordinate: coordinate.to_string(),
+        intent: DevIntent::Dev,
+        platform: Some(default_platform()),
+        features: Vec::new(),
+    }))?;
+    if resolved.kind != DevArtifactKind::Component {
+        bail!(
+            "coordinate `{coordinate}` resolved to {:?}, expected component",
+            resolved.kind
+        );
+    }
+    let Some(version) = resolved.version else {
+        bail!("distributor did not report a version for `{coordinate}`");
+    };
+    Version::parse(&version).with_context(|| {
+        format!("distributor returned invalid version `{version}` for `{coordinate}`")
+    })
+}
+
+/// Local-path and OCI-pinned components are recorded in the manifest too but have nothing to
+/// re-resolve through the distributor.
+fn is_distributor_coordinate(coordinate: &str) -> bool {
+    coordinate.contains("://")
+        && !coordinate.starts_with("file://")
+        && !coordinate.starts_with(OCI_SCHEME)
+}
+
+/// Drop the `@<requirement>` suffix so the distributor returns its newest version.
+fn unversioned(coordinate: &str) -> String {
+    let scheme_end = coordinate.find("://").map(|idx| idx + 3).unwrap_or(0);
+    match coordinate[scheme_end..].rfind('@') {
+        Some(idx) => coordinate[..scheme_end + idx].to_string(),
+        None => coordinate.to_string(),
+    }
+}
+
+fn print_table(entries: &[OutdatedEntry]) {
+    let headers = ["NAME", "CURRENT", "COMPATIBLE", "LATEST", "PROFILE"];
+    let rows: Vec<[&str; 5]> = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.name.as_str(),
+                entry.current.as_str(),
+                entry.compatible.as_str(),
+                entry.latest.as_str(),
+                entry.profile.as_str(),
+            ]
+        })
+        .collect();
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let render = |cells: [&str; 5]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", render(headers));
+    for row in rows {
+        println!("{}", render(row));
+    }
+}
@@ -1,20 +1,39 @@
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
+use schemars::JsonSchema;
+use semver::VersionReq;
 use serde::Deserialize;
 
-#[derive(Debug, Default, Deserialize, Clone)]
+use crate::diagnostics::{self, Diagnostic, SourceSpan};
+
+/// greentic-dev `config.toml`. Unknown keys are ignored when loading; `config validate` reports
+/// them.
+#[derive(Debug, Default, Deserialize, Clone, JsonSchema)]
+#[schemars(title = "greentic-dev config", deny_unknown_fields)]
 pub struct GreenticConfig {
+    /// Paths and version requirements of the companion CLIs greentic-dev delegates to.
     #[serde(default)]
     pub tools: ToolsSection,
+    /// Defaults for scaffolding commands.
     #[serde(default)]
     pub defaults: DefaultsSection,
+    /// Distributor profiles and the default selection.
     #[serde(default)]
     pub distributor: DistributorSection,
-    /// Backward-compatible root-level [profiles.*] table used for distributor.
+    /// OCI registry settings.
+    #[serde(default)]
+    pub oci: OciSection,
+    /// Commands run around passthrough invocations, keyed by `<command>` or
+    /// `<command> <subcommand>` (e.g. "pack build").
+    #[serde(default)]
+    pub hooks: HashMap<String, HookEntry>,
+    /// Deprecated root-level [profiles.*] table, used only when no [distributor] profiles exist;
+    /// `config migrate` moves it to [distributor.profiles.*].
     #[serde(default, rename = "profiles")]
+    #[schemars(extend("deprecated" = true))]
     pub legacy_distributor_profiles: HashMap<String, DistributorProfileConfig>,
 }
 
@@ -28,32 +47,90 @@ impl GreenticConfig {
     }
 }
 
-#[derive(Debug, Default, Deserialize, Clone)]
+/// Companion CLIs configurable under [tools.<name>].
+pub const COMPANION_TOOLS: &[&str] = &[
+    "greentic-flow",
+    "greentic-pack",
+    "greentic-component",
+    "greentic-runner-cli",
+    "greentic-gui",
+    "greentic-secrets",
+];
+
+#[derive(Debug, Default, Deserialize, Clone, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ToolsSection {
+    #[serde(rename = "greentic-flow", default)]
+    pub greentic_flow: ToolEntry,
+    #[serde(rename = "greentic-pack", default)]
+    pub greentic_pack: ToolEntry,
     #[serde(rename = "greentic-component", default)]
     pub greentic_component: ToolEntry,
+    #[serde(rename = "greentic-runner-cli", default)]
+    pub greentic_runner_cli: ToolEntry,
+    #[serde(rename = "greentic-gui", default)]
+    pub greentic_gui: ToolEntry,
+    #[serde(rename = "greentic-secrets", default)]
+    pub greentic_secrets: ToolEntry,
+}
+
+impl ToolsSection {
+    /// Entry for one of [`COMPANION_TOOLS`].
+    pub fn entry(&self, name: &str) -> Option<&ToolEntry> {
+        match name {
+            "greentic-flow" => Some(&self.greentic_flow),
+            "greentic-pack" => Some(&self.greentic_pack),
+            "greentic-component" => Some(&self.greentic_component),
+            "greentic-runner-cli" => Some(&self.greentic_runner_cli),
+            "greentic-gui" => Some(&self.greentic_gui),
+            "greentic-secrets" => Some(&self.greentic_secrets),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Default, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Clone, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ToolEntry {
+    /// Binary to run instead of the one found on PATH.
     pub path: Option<PathBuf>,
+    /// Semver requirement the installed version must satisfy (e.g. "^0.4"), checked by
+    /// `tools doctor` and written by `tools pin`.
+    #[schemars(with = "Option<String>")]
+    pub version: Option<VersionReq>,
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Default, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Clone, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct HookEntry {
+    /// Shell commands run before the delegated command; the first failure aborts it.
+    #[serde(default)]
+    pub pre: Vec<String>,
+    /// Shell commands run after the delegated command exits, whatever its status.
+    #[serde(default)]
+    pub post: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct DefaultsSection {
     #[serde(default)]
     pub component: ComponentDefaults,
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Default, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Clone, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ComponentDefaults {
+    /// Organisation used for new components.
     pub org: Option<String>,
+    /// Template used for new components: a built-in kind (`plain`, `provider`,
+    /// `messaging-adapter`, `http`, `stateful`), a local template name, or a directory or tarball
+    /// path.
     pub template: Option<String>,
 }
 
-#[derive(Debug, Default, Deserialize, Clone)]
+/// Any other key is a deprecated [distributor.<name>] profile table.
+#[derive(Debug, Default, Deserialize, Clone, JsonSchema)]
 pub struct DistributorSection {
     /// Configures the default distributor profile by name or inline struct.
     #[serde(default)]
@@ -76,14 +153,15 @@ impl DistributorSection {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum DefaultProfileSelection {
     Name(String),
     Inline(DistributorProfileConfig),
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct DistributorProfileConfig {
     /// Optional profile name when provided inline.
     #[serde(default)]
@@ -93,8 +171,10 @@ pub struct DistributorProfileConfig {
     pub base_url: Option<String>,
     /// Deprecated alias for base_url.
     #[serde(default)]
+    #[schemars(extend("deprecated" = true))]
     pub url: Option<String>,
-    /// API token; allow env:VAR indirection.
+    /// API token; allow env:VAR, file:PATH and keyring:[SERVICE/]ACCOUNT indirection. When unset,
+    /// the token stored by `greentic-dev login` is used.
     #[serde(default)]
     pub token: Option<String>,
     /// Tenant identifier for distributor requests.
@@ -108,11 +188,61 @@ pub struct DistributorProfileConfig {
     pub headers: Option<HashMap<String, String>>,
 }
 
+#[derive(Debug, Default, Deserialize, Clone, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct OciSection {
+    /// Per-registry settings keyed by host (with port when not 443), e.g. [oci.registries."ghcr.io"].
+    #[serde(default)]
+    pub registries: HashMap<String, OciRegistryConfig>,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct OciRegistryConfig {
+    /// Registry username; used together with `password` for basic/token auth.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Password or access token; allow env:VAR, file:PATH and keyring:[SERVICE/]ACCOUNT indirection.
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Talk plain HTTP to this registry (local registries only).
+    #[serde(default)]
+    pub insecure: bool,
+}
+
+/// JSON schema of `config.toml`, as committed in `schemas/greentic-dev.config.schema.json`.
+pub fn schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(GreenticConfig))
+        .expect("config schema serializes to JSON")
+}
+
 #[derive(Debug, Clone)]
 pub struct LoadedGreenticConfig {
     pub config: GreenticConfig,
+    /// User-level config file.
     pub loaded_from: Option<PathBuf>,
     pub attempted_paths: Vec<PathBuf>,
+    /// Workspace `.greentic/config.toml` deep-merged over the user config.
+    pub project_path: Option<PathBuf>,
+    /// Raw contents of the project config, used to tell which values it sets.
+    pub project_table: Option<toml::Table>,
+}
+
+impl LoadedGreenticConfig {
+    /// Whether the project config sets the key at `path`.
+    pub fn project_sets(&self, path: &[&str]) -> bool {
+        let Some(mut current) = self.project_table.as_ref() else {
+            return false;
+        };
+        for (idx, segment) in path.iter().enumerate() {
+            match current.get(*segment) {
+                Some(toml::Value::Table(next)) => current = next,
+                Some(_) => return idx + 1 == path.len(),
+                None => return false,
+            }
+        }
+        true
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -136,20 +266,21 @@ pub fn load_from(path_override: Option<&str>) -> Result<GreenticConfig> {
     load_with_meta(path_override).map(|loaded| loaded.config)
 }
 
+/// Load the user config and, unless a path is given explicitly, deep-merge the nearest project
+/// `.greentic/config.toml` over it.
 pub fn load_with_meta(path_override: Option<&str>) -> Result<LoadedGreenticConfig> {
     let resolution = resolve_config_path(path_override);
     let forced_source = resolution.forced.clone();
     let attempted_paths = resolution.attempted.clone();
-
-    let Some(selected) = resolution.selected else {
-        return Ok(LoadedGreenticConfig {
-            config: GreenticConfig::default(),
-            loaded_from: None,
-            attempted_paths,
-        });
+    let project_path = if path_override.is_none() {
+        find_project_config(resolution.selected.as_deref())
+    } else {
+        None
     };
 
-    if !selected.exists() {
+    if let Some(selected) = &resolution.selected
+        && !selected.exists()
+    {
         let reason = match forced_source {
             Some(ConfigSource::Arg) => "explicit config override",
             Some(ConfigSource::Env(var)) => var,
@@ -163,18 +294,93 @@ pub fn load_with_meta(path_override: Option<&str>) -> Result<LoadedGreenticConfi
         );
     }
 
-    let raw = fs::read_to_string(&selected)
-        .with_context(|| format!("failed to read config at {}", selected.display()))?;
-    let config: GreenticConfig = toml::from_str(&raw)
-        .with_context(|| format!("failed to parse config at {}", selected.display()))?;
+    let mut merged = match &resolution.selected {
+        Some(selected) => read_table(selected)?,
+        None => toml::Table::new(),
+    };
+    let project_table = project_path.as_deref().map(read_table).transpose()?;
+    if let Some(project) = &project_table {
+        merge_tables(&mut merged, project.clone());
+    }
+
+    let config: GreenticConfig = toml::Value::Table(merged).try_into().map_err(|err| {
+        let layers = match (&resolution.selected, &project_path) {
+            (Some(user), Some(project)) => format!(
+                "failed to parse config at {} layered over {}",
+                project.display(),
+                user.display()
+            ),
+            (Some(path), None) | (None, Some(path)) => {
+                format!("failed to parse config at {}", path.display())
+            }
+            (None, None) => "failed to parse config".to_string(),
+        };
+        Diagnostic::new(
+            diagnostics::CONFIG_INVALID,
+            format!("{layers}: {}", err.message().trim()),
+        )
+        .with_help("run `greentic-dev config validate` to list every problem")
+    })?;
 
     Ok(LoadedGreenticConfig {
         config,
-        loaded_from: Some(selected),
+        loaded_from: resolution.selected,
         attempted_paths,
+        project_path,
+        project_table,
+    })
+}
+
+fn read_table(path: &Path) -> Result<toml::Table> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("failed to read config at {}", path.display()))?;
+    toml::from_str(&raw).map_err(|err| {
+        let diagnostic = Diagnostic::new(
+            diagnostics::CONFIG_SYNTAX,
+            format!(
+                "failed to parse config at {}: {}",
+                path.display(),
+                err.message()
+            ),
+        );
+        let diagnostic = match err.span() {
+            Some(span) => {
+                diagnostic.with_span(SourceSpan::from_offset(Some(path), &raw, span.start))
+            }
+            None => diagnostic,
+        };
+        diagnostic.into()
     })
 }
 
+/// Merge `overlay` into `base`: tables merge key by key, any other value replaces the base value.
+pub fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(overlay)) => {
+                merge_tables(existing, overlay)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Workspace config path relative to a project directory.
+pub const PROJECT_CONFIG: &str = ".greentic/config.toml";
+
+/// Nearest `.greentic/config.toml` in the current directory or its ancestors. The user-level
+/// `~/.greentic/config.toml` and the selected user config are not project configs.
+pub fn find_project_config(user_config: Option<&Path>) -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    let home_config = dirs::home_dir().map(|home| home.join(PROJECT_CONFIG));
+    cwd.ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG))
+        .filter(|path| Some(path.as_path()) != user_config && Some(path) != home_config.as_ref())
+        .find(|path| path.is_file())
+}
+
 fn format_attempted(paths: &[PathBuf]) -> String {
     if paths.is_empty() {
         return "(none)".to_string();
@@ -254,6 +460,50 @@ pub fn resolve_config_path(path_override: Option<&str>) -> ConfigResolution {
     }
 }
 
+/// Type expected by a settable config key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigValueKind {
+    String,
+    Bool,
+    /// Array of strings, e.g. the commands of a `hooks.<cmd>.pre` list.
+    Array,
+    /// Table of string values, e.g. a profile's `headers`.
+    StringTable,
+    /// Profile name or inline profile table (`distributor.default_profile`).
+    ProfileSelection,
+}
+
+/// Expected type of the config key at `path` (already split into segments), or `None` when the
+/// key is not part of the config schema.
+pub fn key_kind(path: &[&str]) -> Option<ConfigValueKind> {
+    match path {
+        ["tools", tool, "path" | "version"] if COMPANION_TOOLS.contains(tool) => {
+            Some(ConfigValueKind::String)
+        }
+        ["defaults", "component", "org" | "template"] => Some(ConfigValueKind::String),
+        ["distributor", "default_profile"] => Some(ConfigValueKind::ProfileSelection),
+        ["distributor", "profiles", _, field @ ..] | ["profiles", _, field @ ..] => {
+            profile_field_kind(field)
+        }
+        ["distributor", name, field @ ..] if *name != "profiles" => profile_field_kind(field),
+        ["oci", "registries", _, "username" | "password"] => Some(ConfigValueKind::String),
+        ["oci", "registries", _, "insecure"] => Some(ConfigValueKind::Bool),
+        ["hooks", _, "pre" | "post"] => Some(ConfigValueKind::Array),
+        _ => None,
+    }
+}
+
+fn profile_field_kind(field: &[&str]) -> Option<ConfigValueKind> {
+    match field {
+        ["name" | "base_url" | "url" | "token" | "tenant_id" | "environment_id"] => {
+            Some(ConfigValueKind::String)
+        }
+        ["headers"] => Some(ConfigValueKind::StringTable),
+        ["headers", _] => Some(ConfigValueKind::String),
+        _ => None,
+    }
+}
+
 pub fn config_path() -> Option<PathBuf> {
     resolve_config_path(None).attempted.into_iter().next()
 }
@@ -0,0 +1,350 @@
+//! `config validate` checks and `config migrate` rewrites for greentic-dev config files.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use toml_edit::{DocumentMut, Item, Key, Table, Value};
+
+use crate::config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One problem found in a config file, attached to a dotted key path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{severity}: {}: {}", self.key, self.message)
+    }
+}
+
+/// Validate the config file at `path`. Syntax errors are reported as a finding, not an `Err`.
+pub fn validate_file(path: &Path) -> Result<Vec<Finding>> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    match toml::from_str::<toml::Table>(&raw) {
+        Ok(table) => Ok(validate_table(&table)),
+        Err(err) => Ok(vec![Finding {
+            severity: Severity::Error,
+            key: "(file)".to_string(),
+            message: err.message().to_string(),
+        }]),
+    }
+}
+
+/// Check `table` against the config schema (unknown keys, wrong types), flag deprecated aliases
+/// and token references that cannot be resolved.
+pub fn validate_table(table: &toml::Table) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let schema = config::schema();
+    let validator = jsonschema::validator_for(&schema).expect("config schema compiles");
+    let instance = serde_json::to_value(table).unwrap_or_default();
+    for error in validator.iter_errors(&instance) {
+        findings.push(Finding {
+            severity: Severity::Error,
+            key: pointer_to_key(&error.instance_path().to_string()),
+            message: error.to_string(),
+        });
+    }
+
+    if let Some(toml::Value::Table(profiles)) = table.get("profiles") {
+        for name in profiles.keys() {
+            findings.push(deprecated(
+                format!("profiles.{name}"),
+                format!(
+                    "root [profiles.*] tables are deprecated; use [distributor.profiles.{name}] (`config migrate`)"
+                ),
+            ));
+        }
+    }
+    if let Some(toml::Value::Table(distributor)) = table.get("distributor") {
+        for name in legacy_distributor_names(distributor) {
+            findings.push(deprecated(
+                format!("distributor.{name}"),
+                format!(
+                    "[distributor.<name>] tables are deprecated; use [distributor.profiles.{name}] (`config migrate`)"
+                ),
+            ));
+        }
+    }
+
+    for (key, profile) in profile_tables(table) {
+        if profile.contains_key("url") {
+            findings.push(deprecated(
+                format!("{key}.url"),
+                "`url` is deprecated; use `base_url` (`config migrate`)".to_string(),
+            ));
+        }
+        if let Some(toml::Value::String(token)) = profile.get("token") {
+            findings.extend(check_reference(format!("{key}.token"), token));
+        }
+    }
+    if let Some(toml::Value::Table(registries)) =
+        table.get("oci").and_then(|oci| oci.get("registries"))
+    {
+        for (host, registry) in registries {
+            if let Some(toml::Value::String(password)) = registry.get("password") {
+                findings.extend(check_reference(
+                    format!("oci.registries.{}.password", Key::new(host).display_repr()),
+                    password,
+                ));
+            }
+        }
+    }
+    findings
+}
+
+/// Rewrite legacy layouts in place: root [profiles.*] and [distributor.<name>] tables move to
+/// [distributor.profiles.*] and `url` becomes `base_url`. Comments travel with the tables they
+/// decorate. Returns a description of each change; entries that cannot move are left untouched
+/// and reported.
+pub fn migrate_document(doc: &mut DocumentMut) -> Result<Vec<String>> {
+    let mut changes = Vec::new();
+    let root = doc.as_table_mut();
+
+    let mut moves = Vec::new();
+    if let Some(distributor) = root.get_mut("distributor").and_then(Item::as_table_mut) {
+        let legacy = distributor
+            .iter()
+            .filter(|(name, item)| !is_distributor_key(name) && item.is_table_like())
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        for name in legacy {
+            let item = distributor.remove(&name).expect("key listed above");
+            moves.push((format!("distributor.{name}"), name, item));
+        }
+    }
+    if let Some(Item::Table(profiles)) = root.get_mut("profiles") {
+        let legacy = profiles
+            .iter()
+            .filter(|(_, item)| item.is_table_like())
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        for name in legacy {
+            let item = profiles.remove(&name).expect("key listed above");
+            moves.push((format!("profiles.{name}"), name, item));
+        }
+        if profiles.is_empty() {
+            root.remove("profiles");
+        }
+    }
+
+    let mut kept = Vec::new();
+    if !moves.is_empty() {
+        let distributor = implicit_table(root, "distributor")?;
+        let profiles = implicit_table(distributor, "profiles")?;
+        for (from, name, item) in moves {
+            let to = format!(
+                "distributor.profiles.{}",
+                Key::new(name.as_str()).display_repr()
+            );
+            if profiles.contains_key(&name) {
+                changes.push(format!("kept [{from}]: [{to}] already exists"));
+                kept.push((from, name, item));
+                continue;
+            }
+            profiles.insert(&name, item);
+            changes.push(format!("moved [{from}] to [{to}]"));
+        }
+    }
+    for (from, name, item) in kept {
+        let parent = if from.starts_with("profiles.") {
+            implicit_table(root, "profiles")?
+        } else {
+            implicit_table(root, "distributor")?
+        };
+        parent.insert(&name, item);
+    }
+
+    if let Some(profiles) = root
+        .get_mut("distributor")
+        .and_then(|distributor| distributor.get_mut("profiles"))
+        .and_then(Item::as_table_like_mut)
+    {
+        for (name, profile) in profiles.iter_mut() {
+            let key = format!("distributor.profiles.{}", name.display_repr());
+            rename_url(&key, profile, &mut changes);
+        }
+    }
+    if let Some(profile) = root
+        .get_mut("distributor")
+        .and_then(|distributor| distributor.get_mut("default_profile"))
+    {
+        rename_url("distributor.default_profile", profile, &mut changes);
+    }
+    Ok(changes)
+}
+
+fn deprecated(key: String, message: String) -> Finding {
+    Finding {
+        severity: Severity::Warning,
+        key,
+        message,
+    }
+}
+
+/// `env:` and `file:` references must resolve; `keyring:` entries are not looked up here.
+fn check_reference(key: String, raw: &str) -> Option<Finding> {
+    let message = if let Some(var) = raw.strip_prefix("env:") {
+        std::env::var_os(var)
+            .is_none()
+            .then(|| format!("env var {var} is not set"))
+    } else if let Some(path) = raw.strip_prefix("file:") {
+        (!Path::new(path).is_file()).then(|| format!("token file {path} does not exist"))
+    } else {
+        None
+    }?;
+    Some(Finding {
+        severity: Severity::Error,
+        key,
+        message,
+    })
+}
+
+fn is_distributor_key(name: &str) -> bool {
+    name == "default_profile" || name == "profiles"
+}
+
+fn legacy_distributor_names(distributor: &toml::Table) -> Vec<&String> {
+    distributor
+        .iter()
+        .filter(|(name, value)| !is_distributor_key(name) && value.is_table())
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Every profile table with its dotted key, in all supported locations.
+fn profile_tables(table: &toml::Table) -> Vec<(String, &toml::Table)> {
+    fn named<'a>(prefix: &str, table: &'a toml::Table) -> Vec<(String, &'a toml::Table)> {
+        table
+            .iter()
+            .filter_map(|(name, value)| {
+                let key = format!("{prefix}.{}", Key::new(name.as_str()).display_repr());
+                value.as_table().map(|profile| (key, profile))
+            })
+            .collect()
+    }
+
+    let mut profiles = Vec::new();
+    if let Some(toml::Value::Table(distributor)) = table.get("distributor") {
+        if let Some(toml::Value::Table(nested)) = distributor.get("profiles") {
+            profiles.extend(named("distributor.profiles", nested));
+        }
+        if let Some(toml::Value::Table(inline)) = distributor.get("default_profile") {
+            profiles.push(("distributor.default_profile".to_string(), inline));
+        }
+        for name in legacy_distributor_names(distributor) {
+            if let Some(toml::Value::Table(profile)) = distributor.get(name) {
+                profiles.push((
+                    format!("distributor.{}", Key::new(name.as_str()).display_repr()),
+                    profile,
+                ));
+            }
+        }
+    }
+    if let Some(toml::Value::Table(root)) = table.get("profiles") {
+        profiles.extend(named("profiles", root));
+    }
+    profiles
+}
+
+fn pointer_to_key(pointer: &str) -> String {
+    let key = pointer
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .map(|segment| Key::new(segment).display_repr().into_owned())
+        .collect::<Vec<_>>()
+        .join(".");
+    if key.is_empty() {
+        "(root)".to_string()
+    } else {
+        key
+    }
+}
+
+fn implicit_table<'a>(parent: &'a mut Table, key: &str) -> Result<&'a mut Table> {
+    parent
+        .entry(key)
+        .or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        })
+        .as_table_mut()
+        .with_context(|| format!("`{key}` is not a table; migrate it by hand"))
+}
+
+/// Rename `url` to `base_url` in a profile table, keeping its position and the comments above it.
+fn rename_url(key: &str, profile: &mut Item, changes: &mut Vec<String>) {
+    if profile.get("url").is_none() {
+        return;
+    }
+    let has_base_url = profile.get("base_url").is_some();
+    match profile {
+        Item::Table(table) => {
+            let names = table
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>();
+            for name in names {
+                let (old_key, item) = table.remove_entry(&name).expect("key listed above");
+                match (name.as_str(), has_base_url) {
+                    ("url", true) => {}
+                    ("url", false) => {
+                        table.insert_formatted(&renamed(&old_key), item);
+                    }
+                    _ => {
+                        table.insert_formatted(&old_key, item);
+                    }
+                }
+            }
+        }
+        Item::Value(Value::InlineTable(table)) => {
+            let names = table
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>();
+            for name in names {
+                let (old_key, value) = table.remove_entry(&name).expect("key listed above");
+                match (name.as_str(), has_base_url) {
+                    ("url", true) => {}
+                    ("url", false) => {
+                        table.insert_formatted(&renamed(&old_key), value);
+                    }
+                    _ => {
+                        table.insert_formatted(&old_key, value);
+                    }
+                }
+            }
+        }
+        _ => return,
+    }
+    if has_base_url {
+        changes.push(format!("removed {key}.url: base_url is already set"));
+    } else {
+        changes.push(format!("renamed {key}.url to base_url"));
+    }
+}
+
+fn renamed(old_key: &Key) -> Key {
+    let mut key = Key::new("base_url");
+    *key.leaf_decor_mut() = old_key.leaf_decor().clone();
+    key
+}
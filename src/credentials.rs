@@ -0,0 +1,391 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ring::aead::{Aad, CHACHA20_POLY1305, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics;
+use crate::events;
+
+/// Overrides the location of the encrypted credentials file.
+pub const CREDENTIALS_FILE_ENV: &str = "GREENTIC_DEV_CREDENTIALS_FILE";
+/// Set to `0` to keep the credentials key in a file instead of the OS keyring.
+pub const KEYRING_ENV: &str = "GREENTIC_DEV_KEYRING";
+
+/// Service used for `keyring:<account>` tokens that do not name one.
+const DEFAULT_KEYRING_SERVICE: &str = "greentic-dev";
+const KEY_LEN: usize = 32;
+
+/// Distributor tokens stored by `greentic-dev login`, keyed by profile name. Each token is bound
+/// to the base URL it was stored for and is not handed out for any other URL.
+///
+/// Tokens are sealed with ChaCha20-Poly1305 under a random key kept in the OS keyring. Without a
+/// usable keyring the key falls back to a sibling `.key` file; both files are written with 0600
+/// permissions, so the encryption then only keeps tokens out of casual view of the JSON file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CredentialsFile {
+    #[serde(default)]
+    profiles: BTreeMap<String, SealedToken>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedToken {
+    /// Distributor URL the token was stored for; also part of the sealed data's AAD.
+    #[serde(default)]
+    base_url: Option<String>,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// `$GREENTIC_DEV_CREDENTIALS_FILE`, or `credentials.json` next to the default config.
+pub fn credentials_path() -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os(CREDENTIALS_FILE_ENV).filter(|path| !path.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(dirs::config_dir)
+        .ok_or_else(|| anyhow!("failed to resolve credentials path (no home directory found)"))?;
+    Ok(dir.join("greentic-dev").join("credentials.json"))
+}
+
+/// Encrypt and store `token` for `profile` at `base_url`, replacing any previous one.
+pub fn store_token(profile: &str, base_url: &str, token: &str) -> Result<PathBuf> {
+    let path = credentials_path()?;
+    let key = load_or_create_key(&path)?;
+    let mut file = load(&path)?;
+
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| anyhow!("failed to generate a nonce"))?;
+    let mut sealed = token.as_bytes().to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(token_aad(profile, base_url).as_bytes()),
+        &mut sealed,
+    )
+    .map_err(|_| anyhow!("failed to encrypt token"))?;
+    file.profiles.insert(
+        profile.to_string(),
+        SealedToken {
+            base_url: Some(base_url.to_string()),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(sealed),
+        },
+    );
+    save(&path, &file)?;
+    Ok(path)
+}
+
+/// Decrypt the token stored for `profile`, if any. A token stored for a URL other than
+/// `base_url` is withheld with a warning, so a config pointing the profile elsewhere never
+/// receives it.
+pub fn stored_token(profile: &str, base_url: &str) -> Result<Option<String>> {
+    let path = credentials_path()?;
+    let file = load(&path)?;
+    let Some(sealed) = file.profiles.get(profile) else {
+        return Ok(None);
+    };
+    if sealed.base_url.as_deref() != Some(base_url) {
+        let stored_for = sealed
+            .base_url
+            .as_deref()
+            .map_or_else(|| "an unrecorded URL".to_string(), |url| format!("`{url}`"));
+        events::warning(
+            diagnostics::STORED_TOKEN_URL_MISMATCH,
+            &format!(
+                "not sending the stored token for profile `{profile}`: it was stored for {stored_for}, but the profile now points at `{base_url}`; run `greentic-dev login --profile {profile}` to store a token for this URL"
+            ),
+        );
+        return Ok(None);
+    }
+    let Some(key) = load_key(&path)? else {
+        bail!(
+            "credentials key for {} is missing; run `greentic-dev login --profile {profile}` again",
+            path.display()
+        );
+    };
+    let nonce: [u8; NONCE_LEN] = STANDARD
+        .decode(&sealed.nonce)
+        .ok()
+        .and_then(|nonce| nonce.try_into().ok())
+        .ok_or_else(|| {
+            anyhow!(
+                "corrupt nonce for profile `{profile}` in {}",
+                path.display()
+            )
+        })?;
+    let mut data = STANDARD.decode(&sealed.ciphertext).with_context(|| {
+        format!(
+            "corrupt token for profile `{profile}` in {}",
+            path.display()
+        )
+    })?;
+    let plain = key
+        .open_in_place(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(token_aad(profile, base_url).as_bytes()),
+            &mut data,
+        )
+        .map_err(|_| {
+            anyhow!(
+                "failed to decrypt token for profile `{profile}` in {}; run `greentic-dev login --profile {profile}` again",
+                path.display()
+            )
+        })?;
+    String::from_utf8(plain.to_vec())
+        .map(Some)
+        .with_context(|| format!("stored token for profile `{profile}` is not UTF-8"))
+}
+
+/// Remove the token stored for `profile`. Returns whether one was stored.
+pub fn remove_token(profile: &str) -> Result<bool> {
+    let path = credentials_path()?;
+    let mut file = load(&path)?;
+    if file.profiles.remove(profile).is_none() {
+        return Ok(false);
+    }
+    save(&path, &file)?;
+    Ok(true)
+}
+
+/// Profiles that have a stored token, with the base URL each was stored for.
+pub fn stored_profiles() -> Result<BTreeMap<String, Option<String>>> {
+    Ok(load(&credentials_path()?)?
+        .profiles
+        .into_iter()
+        .map(|(profile, sealed)| (profile, sealed.base_url))
+        .collect())
+}
+
+/// Resolve a `file:<path>` token reference; surrounding whitespace is trimmed.
+pub fn read_token_file(path: &str) -> Result<String> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("failed to read token file {path}"))?;
+    let token = raw.trim();
+    if token.is_empty() {
+        bail!("token file {path} is empty");
+    }
+    Ok(token.to_string())
+}
+
+/// Resolve a `keyring:[<service>/]<account>` token reference from the OS keychain
+/// (`security` on macOS, `secret-tool` elsewhere).
+pub fn read_keyring(spec: &str) -> Result<String> {
+    let (service, account) = spec
+        .split_once('/')
+        .unwrap_or((DEFAULT_KEYRING_SERVICE, spec));
+    if account.is_empty() {
+        bail!("keyring reference `keyring:{spec}` is missing an account");
+    }
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        command.args(["find-generic-password", "-s", service, "-a", account, "-w"]);
+        command
+    } else if cfg!(windows) {
+        bail!("`keyring:` tokens are not supported on Windows; use `env:` or `file:` instead");
+    } else {
+        let mut command = Command::new("secret-tool");
+        command.args(["lookup", "service", service, "account", account]);
+        command
+    };
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command
+        .output()
+        .with_context(|| format!("failed to run `{program}` to read `keyring:{spec}`"))?;
+    if !output.status.success() {
+        bail!(
+            "`{program}` found no keyring entry for service `{service}`, account `{account}`: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let token = String::from_utf8(output.stdout)
+        .with_context(|| format!("keyring entry `keyring:{spec}` is not UTF-8"))?;
+    Ok(token.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Show `env:`, `file:` and `keyring:` references as-is and hide literal tokens.
+pub fn redact_token(raw: &str) -> String {
+    if ["env:", "file:", "keyring:"]
+        .iter()
+        .any(|prefix| raw.starts_with(prefix))
+    {
+        raw.to_string()
+    } else {
+        "<redacted>".to_string()
+    }
+}
+
+/// Additional data sealed with each token, so editing its recorded `base_url` breaks decryption.
+fn token_aad(profile: &str, base_url: &str) -> String {
+    format!("{profile}\n{base_url}")
+}
+
+fn key_path(credentials: &Path) -> PathBuf {
+    credentials.with_extension("key")
+}
+
+fn load(path: &Path) -> Result<CredentialsFile> {
+    if !path.exists() {
+        return Ok(CredentialsFile::default());
+    }
+    let data =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&data).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+fn save(path: &Path, file: &CredentialsFile) -> Result<()> {
+    let rendered = serde_json::to_vec_pretty(file).context("failed to render credentials")?;
+    write_private(path, &rendered)
+}
+
+/// The key sealing tokens in `credentials`: an existing `.key` file, else the keyring entry.
+fn load_key(credentials: &Path) -> Result<Option<LessSafeKey>> {
+    let key_path = key_path(credentials);
+    if key_path.exists() {
+        let bytes = fs::read(&key_path)
+            .with_context(|| format!("failed to read {}", key_path.display()))?;
+        return sealing_key(&bytes, &key_path.display().to_string()).map(Some);
+    }
+    if !keyring_enabled() {
+        return Ok(None);
+    }
+    let account = keyring_account(credentials);
+    let Ok(encoded) = read_keyring(&format!("{DEFAULT_KEYRING_SERVICE}/{account}")) else {
+        return Ok(None);
+    };
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .with_context(|| format!("keyring entry `{account}` is not a credentials key"))?;
+    sealing_key(&bytes, &format!("keyring entry `{account}`")).map(Some)
+}
+
+/// [`load_key`], creating a key in the keyring (or, failing that, the `.key` file) if there is none.
+fn load_or_create_key(credentials: &Path) -> Result<LessSafeKey> {
+    if let Some(key) = load_key(credentials)? {
+        return Ok(key);
+    }
+    let mut bytes = vec![0u8; KEY_LEN];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("failed to generate a credentials key"))?;
+    let key_path = key_path(credentials);
+    if keyring_enabled() {
+        match write_keyring(&keyring_account(credentials), &STANDARD.encode(&bytes)) {
+            Ok(()) => return sealing_key(&bytes, "in the keyring"),
+            Err(err) => eprintln!(
+                "warning: {err:#}; keeping the credentials key in {}, which only obscures the stored tokens from anyone who can read that directory",
+                key_path.display()
+            ),
+        }
+    }
+    write_private(&key_path, &bytes)?;
+    sealing_key(&bytes, &key_path.display().to_string())
+}
+
+fn sealing_key(bytes: &[u8], source: &str) -> Result<LessSafeKey> {
+    if bytes.len() != KEY_LEN {
+        bail!("credentials key {source} is corrupt");
+    }
+    let key = UnboundKey::new(&CHACHA20_POLY1305, bytes)
+        .map_err(|_| anyhow!("credentials key {source} is corrupt"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+fn keyring_enabled() -> bool {
+    !cfg!(windows) && std::env::var(KEYRING_ENV).map_or(true, |value| value != "0")
+}
+
+/// One keyring entry per credentials file, so `GREENTIC_DEV_CREDENTIALS_FILE` setups stay apart.
+fn keyring_account(credentials: &Path) -> String {
+    format!("credentials-key:{}", credentials.display())
+}
+
+/// Store `secret` under the default service (`security` on macOS, `secret-tool` elsewhere).
+///
+/// The secret goes through stdin so it never shows up in the process list: `secret-tool store`
+/// reads it from there, and `security -i` reads the whole `add-generic-password` command.
+fn write_keyring(account: &str, secret: &str) -> Result<()> {
+    let (mut command, input) = if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        command.arg("-i");
+        let input = format!(
+            "add-generic-password -U -s {} -a {} -w {}\n",
+            security_quote(DEFAULT_KEYRING_SERVICE),
+            security_quote(account),
+            security_quote(secret)
+        );
+        (command, input)
+    } else {
+        let mut command = Command::new("secret-tool");
+        command.args([
+            "store",
+            "--label=greentic-dev credentials key",
+            "service",
+            DEFAULT_KEYRING_SERVICE,
+            "account",
+            account,
+        ]);
+        (command, secret.to_string())
+    };
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("failed to run `{program}`"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .with_context(|| format!("failed to write to `{program}`"))?;
+    }
+    let status = child
+        .wait()
+        .with_context(|| format!("failed to run `{program}`"))?;
+    if !status.success() {
+        bail!("`{program}` could not store keyring entry `{account}`");
+    }
+    // `security -i` does not reliably report failures of the commands it reads.
+    let stored = read_keyring(&format!("{DEFAULT_KEYRING_SERVICE}/{account}")).ok();
+    if stored.as_deref() != Some(secret) {
+        bail!("`{program}` did not store keyring entry `{account}`");
+    }
+    Ok(())
+}
+
+/// Double-quote `value` for a command line read by `security -i`.
+fn security_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Atomically replace `path` with `bytes`, readable by the current user only.
+fn write_private(path: &Path, bytes: &[u8]) -> Result<()> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let mut temp = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("failed to create a temporary file in {}", dir.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        temp.as_file()
+            .set_permissions(fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("failed to restrict permissions for {}", path.display()))?;
+    }
+    temp.write_all(bytes)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    temp.persist(path)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
@@ -1,9 +1,9 @@
 use std::ffi::OsString;
 
-use anyhow::{Context, Result, anyhow, bail};
-use which::which;
+use anyhow::{Context, Result, bail};
 
-use crate::config::{self, GreenticConfig};
+use crate::config::GreenticConfig;
+use crate::passthrough::resolve_tool;
 use crate::util::process::{self, CommandOutput, CommandSpec, StreamMode};
 
 const TOOL_NAME: &str = "greentic-component";
@@ -14,9 +14,9 @@ pub struct ComponentDelegate {
 
 impl ComponentDelegate {
     pub fn from_config(config: &GreenticConfig) -> Result<Self> {
-        let resolved = resolve_program(config)?;
+        let resolved = resolve_tool(TOOL_NAME, &config.tools)?;
         Ok(Self {
-            program: resolved.program,
+            program: resolved.path.into_os_string(),
         })
     }
 
@@ -59,36 +59,3 @@ impl ComponentDelegate {
         );
     }
 }
-
-struct ResolvedProgram {
-    program: OsString,
-}
-
-fn resolve_program(config: &GreenticConfig) -> Result<ResolvedProgram> {
-    if let Some(custom) = config.tools.greentic_component.path.as_ref() {
-        if !custom.exists() {
-            bail!(
-                "configured greentic-component path `{}` does not exist",
-                custom.display()
-            );
-        }
-        return Ok(ResolvedProgram {
-            program: custom.as_os_str().to_os_string(),
-        });
-    }
-
-    match which(TOOL_NAME) {
-        Ok(path) => Ok(ResolvedProgram {
-            program: path.into_os_string(),
-        }),
-        Err(error) => {
-            let config_hint = config::config_path()
-                .map(|path| path.display().to_string())
-                .unwrap_or_else(|| "$XDG_CONFIG_HOME/greentic-dev/config.toml".to_string());
-            Err(anyhow!(
-                "failed to locate `{TOOL_NAME}` on PATH ({error}). Install it via `cargo install \
-                 greentic-component` or set [tools.greentic-component].path in {config_hint}."
-            ))
-        }
-    }
-}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -6,6 +7,7 @@ use serde_yaml_bw::Value as YamlValue;
 
 use super::registry::DescribeRegistry;
 use super::schema::{schema_id_from_json, validate_yaml_against_schema};
+use crate::diagnostics::{self, Diagnostic, SourceSpan};
 use crate::path_safety::normalize_under_root;
 
 #[derive(Clone, Debug, Default)]
@@ -107,9 +109,23 @@ where
                 path: path_ref.to_path_buf(),
                 error: std::io::Error::other(error.to_string()),
             })?;
-        let source = fs::read_to_string(&safe)
-            .map_err(|error| FlowValidationError::Io { path: safe, error })?;
-        self.validate_str(&source)
+        let source = fs::read_to_string(&safe).map_err(|error| FlowValidationError::Io {
+            path: safe.clone(),
+            error,
+        })?;
+        self.validate_str(&source).map_err(|error| match error {
+            FlowValidationError::YamlParse {
+                error,
+                span: Some(span),
+            } => FlowValidationError::YamlParse {
+                error,
+                span: Some(SourceSpan {
+                    path: Some(safe),
+                    ..span
+                }),
+            },
+            other => other,
+        })
     }
 
     pub fn validate_str(
@@ -118,6 +134,9 @@ where
     ) -> Result<Vec<ValidatedNode>, FlowValidationError> {
         let document: YamlValue = serde_yaml_bw::from_str(yaml_source).map_err(|error| {
             FlowValidationError::YamlParse {
+                span: error.location().map(|location| {
+                    SourceSpan::at(None, yaml_source, location.line(), location.column())
+                }),
                 error: error.to_string(),
             }
         })?;
@@ -214,6 +233,7 @@ pub enum FlowValidationError {
     },
     YamlParse {
         error: String,
+        span: Option<SourceSpan>,
     },
     MissingNodes,
     NodeNotMapping {
@@ -232,3 +252,64 @@ pub enum FlowValidationError {
         message: String,
     },
 }
+
+impl FlowValidationError {
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            FlowValidationError::Io { path, error } => Diagnostic::new(
+                diagnostics::FLOW_READ_FAILED,
+                format!("failed to read flow {}: {error}", path.display()),
+            ),
+            FlowValidationError::YamlParse { error, span } => {
+                let diagnostic = Diagnostic::new(
+                    diagnostics::FLOW_YAML_INVALID,
+                    format!("flow is not valid YAML: {error}"),
+                );
+                match span {
+                    Some(span) => diagnostic.with_span(span.clone()),
+                    None => diagnostic,
+                }
+            }
+            FlowValidationError::MissingNodes => Diagnostic::new(
+                diagnostics::FLOW_MISSING_NODES,
+                "flow has no `nodes` sequence",
+            )
+            .with_help("list the flow's nodes under a top-level `nodes:` key"),
+            FlowValidationError::NodeNotMapping { index } => Diagnostic::new(
+                diagnostics::FLOW_NODE_NOT_MAPPING,
+                format!("node #{index} is not a mapping"),
+            ),
+            FlowValidationError::MissingComponent { index } => Diagnostic::new(
+                diagnostics::FLOW_NODE_MISSING_COMPONENT,
+                format!("node #{index} has no `component`"),
+            )
+            .with_help("add `component: <name>` to the node"),
+            FlowValidationError::DescribeFailed { component, error } => Diagnostic::new(
+                diagnostics::COMPONENT_DESCRIBE_FAILED,
+                format!("failed to describe component `{component}`: {error}"),
+            ),
+            FlowValidationError::SchemaValidation {
+                component,
+                index,
+                message,
+            } => Diagnostic::new(
+                diagnostics::COMPONENT_SCHEMA_INVALID,
+                format!("node #{index} does not match the `{component}` schema: {message}"),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for FlowValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.diagnostic().message)
+    }
+}
+
+impl std::error::Error for FlowValidationError {}
+
+impl From<FlowValidationError> for Diagnostic {
+    fn from(error: FlowValidationError) -> Self {
+        error.diagnostic()
+    }
+}
@@ -0,0 +1,416 @@
+//! Typed errors with stable `GD` codes, optional source spans and help text. They render as
+//! `error[GDxxxx]` reports in text mode, carry their code into `--output json` events, and are
+//! documented by `greentic-dev explain <code>`.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+use crate::events::{self, Event};
+
+pub const GENERIC_ERROR: &str = "GD0001";
+pub const UNKNOWN_COMPONENT: &str = "GD0101";
+pub const COMPONENT_SCHEMA_INVALID: &str = "GD0102";
+pub const LICENSE_NOT_ACCEPTED: &str = "GD0103";
+pub const COMPONENT_DESCRIBE_FAILED: &str = "GD0104";
+pub const COMPONENT_VERSION_MISMATCH: &str = "GD0105";
+pub const COMPONENT_CAPABILITY_MISMATCH: &str = "GD0106";
+pub const COMPONENT_LIMIT_EXCEEDED: &str = "GD0107";
+pub const FLOW_READ_FAILED: &str = "GD0201";
+pub const FLOW_YAML_INVALID: &str = "GD0202";
+pub const FLOW_MISSING_NODES: &str = "GD0203";
+pub const FLOW_NODE_NOT_MAPPING: &str = "GD0204";
+pub const FLOW_NODE_MISSING_COMPONENT: &str = "GD0205";
+pub const PACK_VERIFY_WARNING: &str = "GD0301";
+pub const PACK_LIMITS_UNCHECKED: &str = "GD0302";
+pub const DISTRIBUTOR_UNREACHABLE: &str = "GD0401";
+pub const DISTRIBUTOR_LICENSE_REQUIRED: &str = "GD0402";
+pub const DISTRIBUTOR_STATUS: &str = "GD0403";
+pub const DISTRIBUTOR_INVALID_RESPONSE: &str = "GD0404";
+pub const STORED_TOKEN_URL_MISMATCH: &str = "GD0405";
+pub const CONFIG_INVALID: &str = "GD0501";
+pub const CONFIG_DEPRECATED: &str = "GD0502";
+pub const CONFIG_SYNTAX: &str = "GD0503";
+pub const MCP_TOOL_MAP_WARNING: &str = "GD0601";
+
+/// Documentation for one code, printed by `greentic-dev explain`.
+#[derive(Debug)]
+pub struct CodeInfo {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub explanation: &'static str,
+}
+
+/// Every code greentic-dev reports. Codes are never reused once published.
+pub const CODES: &[CodeInfo] = &[
+    CodeInfo {
+        code: GENERIC_ERROR,
+        title: "command failed",
+        explanation: "The command failed for a reason that has no more specific code. The \
+                      message names the cause; rerun with --verbose for passthrough commands.",
+    },
+    CodeInfo {
+        code: UNKNOWN_COMPONENT,
+        title: "unknown component",
+        explanation: "A flow node references a component that is not in the component \
+                      directory. Check the component name in the flow, pass --component-dir, \
+                      or fetch it with `greentic-dev component add <coordinate>`.",
+    },
+    CodeInfo {
+        code: COMPONENT_SCHEMA_INVALID,
+        title: "node config does not match the component schema",
+        explanation: "A node's config was validated against the config schema its component \
+                      declares and failed. Each listed pointer names the offending field; fix \
+                      the node in the flow or update the component.",
+    },
+    CodeInfo {
+        code: LICENSE_NOT_ACCEPTED,
+        title: "component license not accepted",
+        explanation: "A component bundled in the pack is commercial or trial software and no \
+                      acceptance is recorded in the workspace manifest. Run `greentic-dev \
+                      component add <coordinate> --accept-license <id>`.",
+    },
+    CodeInfo {
+        code: COMPONENT_DESCRIBE_FAILED,
+        title: "component describe failed",
+        explanation: "greentic-dev could not read a component's schema. Rebuild the component \
+                      and check that its manifest is valid.",
+    },
+    CodeInfo {
+        code: COMPONENT_VERSION_MISMATCH,
+        title: "component version does not satisfy the requirement",
+        explanation: "The component found locally has a version outside the range the flow \
+                      asks for. Update the component or relax the version requirement.",
+    },
+    CodeInfo {
+        code: COMPONENT_CAPABILITY_MISMATCH,
+        title: "component capabilities do not match its imports",
+        explanation: "The built component imports interfaces that need a capability its \
+                      provider.toml does not declare under [capabilities], for example \
+                      `wasi:http` without `network = true`. Declare the capability or drop the \
+                      import. Capabilities that are declared but never imported are reported \
+                      as warnings.",
+    },
+    CodeInfo {
+        code: COMPONENT_LIMIT_EXCEEDED,
+        title: "component exceeded its declared limits",
+        explanation: "Before `pack run` starts the runner, greentic-dev runs each component node \
+                      of the pack once with the `limits` from its manifest enforced. A node ran \
+                      past its `wall_time_ms` or grew past its `memory_mb`. Raise the limit in \
+                      the component manifest or fix the component; `pack profile` shows usage \
+                      per node.",
+    },
+    CodeInfo {
+        code: FLOW_READ_FAILED,
+        title: "flow file cannot be read",
+        explanation: "The flow file does not exist, is not readable, or lies outside the \
+                      workspace root. Flow paths must be relative to the current directory.",
+    },
+    CodeInfo {
+        code: FLOW_YAML_INVALID,
+        title: "flow is not valid YAML",
+        explanation: "The flow file failed to parse. The report points at the line and column \
+                      where the parser stopped.",
+    },
+    CodeInfo {
+        code: FLOW_MISSING_NODES,
+        title: "flow has no nodes",
+        explanation: "A flow document must be a sequence of nodes or a mapping with a `nodes` \
+                      sequence.",
+    },
+    CodeInfo {
+        code: FLOW_NODE_NOT_MAPPING,
+        title: "flow node is not a mapping",
+        explanation: "Every entry in `nodes` must be a mapping with at least a `component` \
+                      key.",
+    },
+    CodeInfo {
+        code: FLOW_NODE_MISSING_COMPONENT,
+        title: "flow node has no component",
+        explanation: "A node needs a `component` (or legacy `type`) key naming the component \
+                      it runs.",
+    },
+    CodeInfo {
+        code: PACK_VERIFY_WARNING,
+        title: "pack verification warning",
+        explanation: "`pack verify` accepted the pack but reported a problem such as a dev \
+                      signature or an incomplete SBOM. Packs for production must verify \
+                      without warnings.",
+    },
+    CodeInfo {
+        code: PACK_LIMITS_UNCHECKED,
+        title: "component limits not checked",
+        explanation: "`pack run` could not run the pack's component nodes against their \
+                      manifest limits, for example because the pack does not ship component \
+                      manifests or a flow could not be resolved. The runner starts anyway; the \
+                      message names the cause.",
+    },
+    CodeInfo {
+        code: DISTRIBUTOR_UNREACHABLE,
+        title: "distributor unreachable",
+        explanation: "The HTTP request to the distributor failed before a response arrived. \
+                      Check the profile's base_url, your network, or run `greentic-dev \
+                      distributor serve` for offline work.",
+    },
+    CodeInfo {
+        code: DISTRIBUTOR_LICENSE_REQUIRED,
+        title: "license required",
+        explanation: "The distributor answered 402: the tenant is not entitled to the \
+                      artifact. Buy or request access at the checkout URL, then retry.",
+    },
+    CodeInfo {
+        code: DISTRIBUTOR_STATUS,
+        title: "distributor rejected the request",
+        explanation: "The distributor answered with an error status. 401 and 403 mean the \
+                      token is missing or lacks access (`greentic-dev login`); 404 means the \
+                      coordinate is unknown to the selected profile.",
+    },
+    CodeInfo {
+        code: DISTRIBUTOR_INVALID_RESPONSE,
+        title: "invalid distributor response",
+        explanation: "The distributor's response body could not be decoded. The profile may \
+                      point at a service that is not a greentic distributor, or its version \
+                      is incompatible.",
+    },
+    CodeInfo {
+        code: STORED_TOKEN_URL_MISMATCH,
+        title: "stored token belongs to another distributor URL",
+        explanation: "`greentic-dev login` binds each stored token to the base URL the profile \
+                      had at the time. The profile now resolves to a different URL, for example \
+                      because a project config overrides `base_url`, so the token is not sent. \
+                      Run `greentic-dev login --profile <name>` to store a token for the new URL.",
+    },
+    CodeInfo {
+        code: CONFIG_INVALID,
+        title: "invalid config",
+        explanation: "A config file has an unknown key, a value of the wrong type, or an \
+                      env:/file: token reference that does not resolve. Run `greentic-dev \
+                      config validate` to list every problem.",
+    },
+    CodeInfo {
+        code: CONFIG_DEPRECATED,
+        title: "deprecated config alias",
+        explanation: "A config file uses a deprecated layout (`url`, [distributor.<name>] or \
+                      root [profiles.*]). It still works; `greentic-dev config migrate` \
+                      rewrites it.",
+    },
+    CodeInfo {
+        code: CONFIG_SYNTAX,
+        title: "config is not valid TOML",
+        explanation: "A config file failed to parse. The report points at the line and column \
+                      of the syntax error.",
+    },
+    CodeInfo {
+        code: MCP_TOOL_MAP_WARNING,
+        title: "MCP tool map warning",
+        explanation: "`mcp doctor` found a tool whose component is missing or whose settings \
+                      look wrong.",
+    },
+];
+
+pub fn lookup(code: &str) -> Option<&'static CodeInfo> {
+    CODES
+        .iter()
+        .find(|info| info.code.eq_ignore_ascii_case(code))
+}
+
+/// Location of a diagnostic in a YAML or TOML source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub path: Option<PathBuf>,
+    /// 1-based.
+    pub line: usize,
+    /// 1-based.
+    pub column: usize,
+    /// The source line, for the snippet under the location.
+    pub text: Option<String>,
+}
+
+impl SourceSpan {
+    /// Span at a 1-based `line` and `column` of `source`.
+    pub fn at(path: Option<&Path>, source: &str, line: usize, column: usize) -> Self {
+        Self {
+            path: path.map(Path::to_path_buf),
+            line,
+            column,
+            text: source
+                .lines()
+                .nth(line.saturating_sub(1))
+                .map(str::to_string),
+        }
+    }
+
+    /// Span at byte `offset` of `source`.
+    pub fn from_offset(path: Option<&Path>, source: &str, offset: usize) -> Self {
+        let before = &source[..offset.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        let column = before[line_start..].chars().count() + 1;
+        Self::at(path, source, line, column)
+    }
+}
+
+/// A user-facing error with a stable code. `Display` is the message alone, so it reads naturally
+/// inside an anyhow context chain; [`render`] adds the code, span and help.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub message: String,
+    pub span: Option<SourceSpan>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            span: None,
+            help: None,
+        }
+    }
+
+    pub fn with_span(mut self, span: SourceSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// The innermost diagnostic in `err`'s context chain.
+pub fn find(err: &anyhow::Error) -> Option<&Diagnostic> {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<Diagnostic>())
+        .last()
+}
+
+/// Code reported for `err`: its diagnostic's, or [`GENERIC_ERROR`].
+pub fn code(err: &anyhow::Error) -> &'static str {
+    find(err).map_or(GENERIC_ERROR, |diagnostic| diagnostic.code)
+}
+
+/// `error[GDxxxx]: <message chain>`, then the source snippet, help and an `explain` hint.
+pub fn render(err: &anyhow::Error) -> String {
+    let diagnostic = find(err);
+    let code = diagnostic.map_or(GENERIC_ERROR, |diagnostic| diagnostic.code);
+    let mut out = format!("error[{code}]: {err:#}\n");
+    let Some(diagnostic) = diagnostic else {
+        return out;
+    };
+    if let Some(span) = &diagnostic.span {
+        let location = match &span.path {
+            Some(path) => format!("{}:{}:{}", path.display(), span.line, span.column),
+            None => format!("line {}, column {}", span.line, span.column),
+        };
+        out.push_str(&format!("  --> {location}\n"));
+        if let Some(text) = &span.text {
+            let gutter = " ".repeat(span.line.to_string().len());
+            out.push_str(&format!("{gutter} |\n{} | {text}\n", span.line));
+            out.push_str(&format!(
+                "{gutter} | {}^\n",
+                " ".repeat(span.column.saturating_sub(1))
+            ));
+        }
+    }
+    if let Some(help) = &diagnostic.help {
+        out.push_str(&format!("  = help: {help}\n"));
+    }
+    out.push_str(&format!(
+        "  = note: run `greentic-dev explain {code}` for details\n"
+    ));
+    out
+}
+
+/// `greentic-dev explain [<code>]`: one code's explanation, or the list of all codes.
+pub fn run_explain(code: Option<&str>) -> Result<()> {
+    let Some(code) = code else {
+        if events::json() {
+            let codes = CODES
+                .iter()
+                .map(|info| serde_json::json!({ "code": info.code, "title": info.title }))
+                .collect::<Vec<_>>();
+            events::emit(Event::Result { data: codes.into() });
+            return Ok(());
+        }
+        for info in CODES {
+            println!("{}  {}", info.code, info.title);
+        }
+        return Ok(());
+    };
+    let Some(info) = lookup(code) else {
+        bail!("unknown code `{code}`; run `greentic-dev explain` to list all codes");
+    };
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::json!({
+                "code": info.code,
+                "title": info.title,
+                "explanation": info.explanation,
+            }),
+        });
+        return Ok(());
+    }
+    println!("{}: {}\n\n{}", info.code, info.title, info.explanation);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_unique_and_well_formed() {
+        for (idx, info) in CODES.iter().enumerate() {
+            assert!(
+                info.code.len() == 6
+                    && info.code.starts_with("GD")
+                    && info.code[2..].chars().all(|ch| ch.is_ascii_digit()),
+                "{}",
+                info.code
+            );
+            assert!(
+                CODES[..idx].iter().all(|other| other.code != info.code),
+                "duplicate {}",
+                info.code
+            );
+        }
+    }
+
+    #[test]
+    fn renders_span_snippet_and_help() {
+        let source = "a = 1\nb = = 2\n";
+        let err = anyhow::Error::new(
+            Diagnostic::new(CONFIG_SYNTAX, "invalid string")
+                .with_span(SourceSpan::from_offset(
+                    Some(Path::new("config.toml")),
+                    source,
+                    10,
+                ))
+                .with_help("quote the value"),
+        )
+        .context("failed to load config");
+        assert_eq!(
+            render(&err),
+            "error[GD0503]: failed to load config: invalid string\n  \
+             --> config.toml:2:5\n  \
+             |\n\
+             2 | b = = 2\n  \
+             |     ^\n  \
+             = help: quote the value\n  \
+             = note: run `greentic-dev explain GD0503` for details\n"
+        );
+    }
+}
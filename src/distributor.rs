@@ -1,13 +1,24 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
 use std::time::Duration;
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
 use reqwest::blocking::Client;
-use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
 
+use crate::artifact_store::{ArtifactStore, StoredArtifact};
 use crate::config::{
-    DefaultProfileSelection, DistributorProfileConfig, GreenticConfig, LoadedGreenticConfig,
+    self, DefaultProfileSelection, DistributorProfileConfig, GreenticConfig, LoadedGreenticConfig,
 };
+use crate::credentials;
+use crate::diagnostics::{self, Diagnostic};
+use crate::download::Downloader;
+use crate::oci::{OCI_SCHEME, OciArtifactKind, OciClient};
+
+/// Points at a JSON file answering every resolve without contacting a distributor.
+pub const RESOLVE_STUB_ENV: &str = "GREENTIC_DEV_RESOLVE_STUB";
+pub const OFFLINE_ENV: &str = "GREENTIC_DEV_OFFLINE";
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -45,15 +56,22 @@ pub struct DevLicenseInfo {
     pub id: Option<String>,
     pub requires_acceptance: bool,
     pub checkout_url: Option<String>,
+    /// License text shown before acceptance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terms: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct DevResolveResponse {
     pub kind: DevArtifactKind,
     pub name: String,
-    pub version: String,
+    /// Exact version; resolve stubs may leave it out.
+    #[serde(default)]
+    pub version: Option<String>,
     pub coordinate: String,
     pub artifact_id: String,
+    /// Empty when the artifact is only reachable through its `artifact_id` handle.
+    #[serde(default)]
     pub artifact_download_path: String,
     pub digest: Option<String>,
     pub license: DevLicenseInfo,
@@ -97,12 +115,64 @@ impl std::fmt::Display for DevDistributorError {
 
 impl std::error::Error for DevDistributorError {}
 
+impl DevDistributorError {
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            DevDistributorError::Http(err) => Diagnostic::new(
+                diagnostics::DISTRIBUTOR_UNREACHABLE,
+                format!("distributor request failed: {err}"),
+            )
+            .with_help(
+                "check the profile's base_url, or run `greentic-dev distributor serve` to work offline",
+            ),
+            DevDistributorError::LicenseRequired(body) => Diagnostic::new(
+                diagnostics::DISTRIBUTOR_LICENSE_REQUIRED,
+                format!(
+                    "license required for {}: {}\nCheckout URL: {}",
+                    body.coordinate, body.message, body.checkout_url
+                ),
+            )
+            .with_help("buy or request access at the checkout URL, then retry"),
+            DevDistributorError::Status(code, _) => {
+                let help = match code.as_u16() {
+                    401 | 403 => Some("run `greentic-dev login` or check the profile's token"),
+                    404 => Some("check the coordinate and the selected --profile"),
+                    _ => None,
+                };
+                let diagnostic =
+                    Diagnostic::new(diagnostics::DISTRIBUTOR_STATUS, self.to_string());
+                match help {
+                    Some(help) => diagnostic.with_help(help),
+                    None => diagnostic,
+                }
+            }
+            DevDistributorError::InvalidResponse(_) => Diagnostic::new(
+                diagnostics::DISTRIBUTOR_INVALID_RESPONSE,
+                self.to_string(),
+            ),
+        }
+    }
+}
+
 impl From<reqwest::Error> for DevDistributorError {
     fn from(value: reqwest::Error) -> Self {
         DevDistributorError::Http(value)
     }
 }
 
+/// Sent on every distributor request so gateways can route by tenant and environment.
+pub const TENANT_HEADER: &str = "x-greentic-tenant-id";
+pub const ENVIRONMENT_HEADER: &str = "x-greentic-environment-id";
+
+/// Used for profile fields the config leaves out.
+pub const DEFAULT_BASE_URL: &str = "http://localhost:8080";
+pub const DEFAULT_TENANT_ID: &str = "local";
+pub const DEFAULT_ENVIRONMENT_ID: &str = "dev";
+/// Profile used when neither `--profile`, the env var nor `distributor.default_profile` picks one.
+pub const DEFAULT_PROFILE: &str = "default";
+/// Selects the distributor profile when `--profile` is not given.
+pub const PROFILE_ENV: &str = "GREENTIC_DISTRIBUTOR_PROFILE";
+
 #[derive(Debug, Clone)]
 pub struct DistributorProfile {
     pub name: String,
@@ -115,20 +185,22 @@ pub struct DistributorProfile {
 
 impl DistributorProfile {
     fn from_pair(name: &str, cfg: &DistributorProfileConfig) -> Result<Self> {
-        let token = resolve_token(cfg.token.clone())?;
-        let base_url = cfg
-            .base_url
-            .as_ref()
-            .or(cfg.url.as_ref())
-            .map(|s| s.trim_end_matches('/').to_string())
-            .unwrap_or_else(|| "http://localhost:8080".to_string());
-        let tenant_id = cfg.tenant_id.clone().unwrap_or_else(|| "local".to_string());
+        let name = cfg.name.clone().unwrap_or_else(|| name.to_string());
+        let base_url = profile_base_url(cfg);
+        let token = match resolve_token(cfg.token.clone())? {
+            Some(token) => Some(token),
+            None => credentials::stored_token(&name, &base_url)?,
+        };
+        let tenant_id = cfg
+            .tenant_id
+            .clone()
+            .unwrap_or_else(|| DEFAULT_TENANT_ID.to_string());
         let environment_id = cfg
             .environment_id
             .clone()
-            .unwrap_or_else(|| "dev".to_string());
+            .unwrap_or_else(|| DEFAULT_ENVIRONMENT_ID.to_string());
         Ok(Self {
-            name: cfg.name.clone().unwrap_or_else(|| name.to_string()),
+            name,
             url: base_url,
             token,
             tenant_id,
@@ -136,20 +208,94 @@ impl DistributorProfile {
             headers: cfg.headers.clone(),
         })
     }
+
+    /// Headers for every request to this distributor: tenant and environment first, then the
+    /// profile's configured headers, which may override them. Names are lowercased.
+    pub fn request_headers(&self) -> BTreeMap<String, String> {
+        let mut headers = BTreeMap::from([
+            (TENANT_HEADER.to_string(), self.tenant_id.clone()),
+            (ENVIRONMENT_HEADER.to_string(), self.environment_id.clone()),
+        ]);
+        for (name, value) in self.headers.iter().flatten() {
+            headers.insert(name.to_ascii_lowercase(), value.clone());
+        }
+        headers
+    }
+
+    /// Apply `--header` overrides on top of the configured headers.
+    pub fn with_header_overrides(mut self, overrides: &[(String, String)]) -> Self {
+        if overrides.is_empty() {
+            return self;
+        }
+        let headers = self.headers.get_or_insert_with(HashMap::new);
+        for (name, value) in overrides {
+            headers.retain(|existing, _| !existing.eq_ignore_ascii_case(name));
+            headers.insert(name.clone(), value.clone());
+        }
+        self
+    }
+}
+
+/// The profile's `base_url` (or legacy `url`) without a trailing slash, else [`DEFAULT_BASE_URL`].
+pub fn profile_base_url(cfg: &DistributorProfileConfig) -> String {
+    cfg.base_url
+        .as_ref()
+        .or(cfg.url.as_ref())
+        .map(|s| s.trim_end_matches('/').to_string())
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+}
+
+/// Parse a `--header` value given as `Name: value` or `Name=value`.
+pub fn parse_header(raw: &str) -> Result<(String, String)> {
+    let split = raw
+        .find([':', '='])
+        .ok_or_else(|| anyhow!("expected `NAME: VALUE` or `NAME=VALUE`, got `{raw}`"))?;
+    let name = raw[..split].trim();
+    let value = raw[split + 1..].trim();
+    HeaderName::from_bytes(name.as_bytes())
+        .with_context(|| format!("invalid header name `{name}`"))?;
+    HeaderValue::from_str(value).with_context(|| format!("invalid value for header `{name}`"))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Build a validated header map from [`DistributorProfile::request_headers`].
+pub fn header_map(headers: &BTreeMap<String, String>) -> Result<HeaderMap> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("invalid header name `{name}` in profile"))?;
+        let value = HeaderValue::from_str(value)
+            .with_context(|| format!("invalid value for header `{name}` in profile"))?;
+        map.insert(name, value);
+    }
+    Ok(map)
 }
 
 pub fn resolve_profile(
     config: &LoadedGreenticConfig,
     profile_arg: Option<&str>,
 ) -> Result<DistributorProfile> {
-    let env_profile = std::env::var("GREENTIC_DISTRIBUTOR_PROFILE").ok();
+    let (name, cfg) = select_profile_config(config, profile_arg)?;
+    DistributorProfile::from_pair(&name, &cfg)
+}
+
+/// Pick the profile named by `--profile`, `GREENTIC_DISTRIBUTOR_PROFILE` or the config's default,
+/// without resolving its token. Returns the profile name and its raw configuration.
+pub fn select_profile_config(
+    config: &LoadedGreenticConfig,
+    profile_arg: Option<&str>,
+) -> Result<(String, DistributorProfileConfig)> {
+    let env_profile = std::env::var(PROFILE_ENV).ok();
     let map: HashMap<String, DistributorProfileConfig> = config.config.distributor_profiles();
     let selection = select_profile(profile_arg, env_profile.as_deref(), &config.config);
 
     match selection {
         ProfileSelection::Inline(cfg) => {
-            let name = cfg.name.clone().unwrap_or_else(|| "default".to_string());
-            DistributorProfile::from_pair(&name, &cfg)
+            let name = cfg
+                .name
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+            Ok((name, cfg))
         }
         ProfileSelection::Named(profile_name) => {
             let Some(profile_cfg) = map.get(&profile_name) else {
@@ -182,19 +328,27 @@ pub fn resolve_profile(
                     attempted
                 );
             };
-            DistributorProfile::from_pair(&profile_name, profile_cfg)
+            let name = profile_cfg
+                .name
+                .clone()
+                .unwrap_or_else(|| profile_name.clone());
+            Ok((name, profile_cfg.clone()))
         }
     }
 }
 
-fn resolve_token(raw: Option<String>) -> Result<Option<String>> {
+pub(crate) fn resolve_token(raw: Option<String>) -> Result<Option<String>> {
     let Some(raw) = raw else {
         return Ok(None);
     };
     if let Some(rest) = raw.strip_prefix("env:") {
         let value = std::env::var(rest)
-            .with_context(|| format!("failed to resolve env var {rest} for distributor token"))?;
+            .with_context(|| format!("failed to resolve env var {rest} for token"))?;
         Ok(Some(value))
+    } else if let Some(rest) = raw.strip_prefix("file:") {
+        credentials::read_token_file(rest).map(Some)
+    } else if let Some(rest) = raw.strip_prefix("keyring:") {
+        credentials::read_keyring(rest).map(Some)
     } else {
         Ok(Some(raw))
     }
@@ -217,7 +371,7 @@ fn select_profile(
             DefaultProfileSelection::Inline(cfg) => ProfileSelection::Inline(cfg.clone()),
         };
     }
-    ProfileSelection::Named("default".to_string())
+    ProfileSelection::Named(DEFAULT_PROFILE.to_string())
 }
 
 enum ProfileSelection {
@@ -225,11 +379,74 @@ enum ProfileSelection {
     Inline(DistributorProfileConfig),
 }
 
+/// Whether `GREENTIC_DEV_OFFLINE` forbids network access.
+pub fn offline() -> bool {
+    std::env::var(OFFLINE_ENV)
+        .ok()
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Source of component and pack resolutions: the configured distributor over HTTP
+/// ([`DevDistributorClient`]) or a local resolve stub ([`StubDistributor`]).
+pub trait Distributor {
+    /// Profile recorded in `greentic.lock` for artifacts resolved here; `None` for stubs.
+    fn profile_name(&self) -> Option<&str>;
+
+    fn resolve(&self, req: &DevResolveRequest) -> Result<DevResolveResponse, DevDistributorError>;
+
+    /// Download a resolved artifact into the shared store, verifying it against its digest.
+    fn download(
+        &self,
+        resolved: &DevResolveResponse,
+        store: &ArtifactStore,
+        file_name: &str,
+    ) -> Result<StoredArtifact>;
+
+    /// Reuse the shared cache entry when the resolved digest is already stored; otherwise
+    /// [`Distributor::download`] it.
+    fn fetch(
+        &self,
+        resolved: &DevResolveResponse,
+        store: &ArtifactStore,
+        file_name: &str,
+    ) -> Result<StoredArtifact> {
+        if let Some(digest) = &resolved.digest
+            && let Some(stored) = store.get(digest, file_name)?
+        {
+            return Ok(stored);
+        }
+        self.download(resolved, store, file_name)
+    }
+}
+
+/// Pick the distributor for this run: the resolve stub when `GREENTIC_DEV_RESOLVE_STUB` is set,
+/// otherwise the selected profile. Offline mode without a stub is an error.
+pub fn open(
+    profile: Option<&str>,
+    header_overrides: &[(String, String)],
+) -> Result<Box<dyn Distributor>> {
+    if let Ok(path) = std::env::var(RESOLVE_STUB_ENV) {
+        return Ok(Box::new(StubDistributor::load(PathBuf::from(path))?));
+    }
+    if offline() {
+        bail!(
+            "offline mode enabled ({OFFLINE_ENV}=1); provide a resolve stub via {RESOLVE_STUB_ENV} or use a local path"
+        );
+    }
+    let config = config::load_with_meta(None)?;
+    let profile = resolve_profile(&config, profile)?.with_header_overrides(header_overrides);
+    Ok(Box::new(DevDistributorClient::from_profile(profile)?))
+}
+
 #[derive(Debug, Clone)]
 pub struct DevDistributorClient {
+    profile_name: String,
     base_url: String,
     auth_token: Option<String>,
+    headers: HeaderMap,
     http: Client,
+    downloader: Downloader,
 }
 
 impl DevDistributorClient {
@@ -238,19 +455,62 @@ impl DevDistributorClient {
             .timeout(Duration::from_secs(30))
             .build()
             .context("failed to build HTTP client")?;
+        let downloader = Downloader::for_profile(&profile)?;
+        let headers = header_map(&profile.request_headers())?;
         Ok(Self {
+            profile_name: profile.name,
             base_url: profile.url,
             auth_token: profile.token,
+            headers,
             http: client,
+            downloader,
         })
     }
 
-    pub fn resolve(
+    /// Download a resolved artifact into the shared store, verifying it against `expected_digest`.
+    pub fn download_artifact(
+        &self,
+        download_path: &str,
+        expected_digest: Option<&str>,
+        store: &ArtifactStore,
+        file_name: &str,
+    ) -> Result<StoredArtifact> {
+        let trimmed_base = self.base_url.trim_end_matches('/');
+        let trimmed_path = download_path.trim_start_matches('/');
+        let url = format!("{trimmed_base}/{trimmed_path}");
+        self.downloader
+            .fetch_into_store(&url, expected_digest, store, file_name)
+    }
+
+    /// Download an artifact the distributor returned as an opaque handle (its `artifact_id`).
+    pub fn download_handle(
         &self,
-        req: &DevResolveRequest,
-    ) -> Result<DevResolveResponse, DevDistributorError> {
+        handle: &str,
+        expected_digest: Option<&str>,
+        store: &ArtifactStore,
+        file_name: &str,
+    ) -> Result<StoredArtifact> {
+        self.download_artifact(
+            &format!("/v1/artifact/{}", handle.trim_start_matches('/')),
+            expected_digest,
+            store,
+            file_name,
+        )
+    }
+}
+
+impl Distributor for DevDistributorClient {
+    fn profile_name(&self) -> Option<&str> {
+        Some(&self.profile_name)
+    }
+
+    fn resolve(&self, req: &DevResolveRequest) -> Result<DevResolveResponse, DevDistributorError> {
         let url = format!("{}/v1/resolve", self.base_url);
-        let mut builder = self.http.post(url).header(CONTENT_TYPE, "application/json");
+        let mut builder = self
+            .http
+            .post(url)
+            .headers(self.headers.clone())
+            .header(CONTENT_TYPE, "application/json");
         if let Some(token) = &self.auth_token {
             builder = builder.header(AUTHORIZATION, format!("Bearer {token}"));
         }
@@ -271,23 +531,180 @@ impl DevDistributorClient {
             .map_err(|err| DevDistributorError::InvalidResponse(err.into()))
     }
 
-    pub fn download_artifact(
+    fn download(
         &self,
-        download_path: &str,
-    ) -> Result<bytes::Bytes, DevDistributorError> {
-        let trimmed_base = self.base_url.trim_end_matches('/');
-        let trimmed_path = download_path.trim_start_matches('/');
-        let url = format!("{trimmed_base}/{trimmed_path}");
-        let mut builder = self.http.get(url);
-        if let Some(token) = &self.auth_token {
-            builder = builder.header(AUTHORIZATION, format!("Bearer {token}"));
+        resolved: &DevResolveResponse,
+        store: &ArtifactStore,
+        file_name: &str,
+    ) -> Result<StoredArtifact> {
+        let digest = resolved.digest.as_deref();
+        if resolved.artifact_download_path.is_empty() {
+            return self
+                .download_handle(&resolved.artifact_id, digest, store, file_name)
+                .with_context(|| {
+                    format!(
+                        "failed to download distributor artifact `{}`",
+                        resolved.artifact_id
+                    )
+                });
         }
-        let response = builder.send()?;
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().ok();
-            return Err(DevDistributorError::Status(status, body));
+        self.download_artifact(&resolved.artifact_download_path, digest, store, file_name)
+    }
+}
+
+/// Answers every resolve from the JSON file named by `GREENTIC_DEV_RESOLVE_STUB`.
+///
+/// The file holds either a full `/v1/resolve` response, a distributor `ResolveComponentResponse`,
+/// or a minimal `{ "artifact_path": ..., "digest": ..., "version": ..., "name": ..., "kind": ... }`
+/// where everything but `artifact_path` is optional. The artifact location may be a local path,
+/// `file://`, `http(s)://` or `oci://` reference.
+#[derive(Debug, Clone)]
+pub struct StubDistributor {
+    path: PathBuf,
+    response: StubResponse,
+}
+
+#[derive(Debug, Clone)]
+enum StubResponse {
+    Full(DevResolveResponse),
+    Component(greentic_distributor_client::ResolveComponentResponse),
+    Minimal(MinimalStub),
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct MinimalStub {
+    artifact_path: String,
+    digest: Option<String>,
+    version: Option<String>,
+    name: Option<String>,
+    kind: Option<DevArtifactKind>,
+}
+
+impl StubDistributor {
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let data = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read resolve stub {}", path.display()))?;
+        let response = if let Ok(full) = serde_json::from_str::<DevResolveResponse>(&data) {
+            StubResponse::Full(full)
+        } else if let Ok(component) =
+            serde_json::from_str::<greentic_distributor_client::ResolveComponentResponse>(&data)
+        {
+            StubResponse::Component(component)
+        } else {
+            let minimal = serde_json::from_str::<MinimalStub>(&data).with_context(|| {
+                format!(
+                    "failed to parse resolve stub {} (expected JSON with `artifact_path` pointing to the artifact)",
+                    path.display()
+                )
+            })?;
+            StubResponse::Minimal(minimal)
+        };
+        Ok(Self { path, response })
+    }
+}
+
+impl Distributor for StubDistributor {
+    fn profile_name(&self) -> Option<&str> {
+        None
+    }
+
+    fn resolve(&self, req: &DevResolveRequest) -> Result<DevResolveResponse, DevDistributorError> {
+        use greentic_distributor_client::ArtifactLocation;
+
+        let name = req
+            .coordinate
+            .rsplit_once('@')
+            .map_or(req.coordinate.as_str(), |(name, _)| name)
+            .to_string();
+        let stubbed = |kind, name, version, artifact_id: String, location: String, digest| {
+            DevResolveResponse {
+                kind,
+                name,
+                version,
+                coordinate: req.coordinate.clone(),
+                artifact_id,
+                artifact_download_path: location,
+                digest,
+                license: DevLicenseInfo {
+                    license_type: DevLicenseType::Free,
+                    id: None,
+                    requires_acceptance: false,
+                    checkout_url: None,
+                    terms: None,
+                },
+                metadata: serde_json::json!({ "stub": self.path.display().to_string() }),
+            }
+        };
+        Ok(match &self.response {
+            StubResponse::Full(full) => full.clone(),
+            StubResponse::Component(component) => {
+                let (artifact_id, location) = match &component.artifact {
+                    ArtifactLocation::FilePath { path } => (path.clone(), path.clone()),
+                    ArtifactLocation::OciReference { reference } => {
+                        (reference.clone(), reference.clone())
+                    }
+                    ArtifactLocation::DistributorInternal { handle } => {
+                        (handle.clone(), String::new())
+                    }
+                };
+                stubbed(
+                    DevArtifactKind::Component,
+                    name,
+                    None,
+                    artifact_id,
+                    location,
+                    Some(component.digest.0.clone()),
+                )
+            }
+            StubResponse::Minimal(minimal) => {
+                let kind = minimal.kind.clone().unwrap_or_else(|| {
+                    if minimal.artifact_path.ends_with(".gtpack") {
+                        DevArtifactKind::Pack
+                    } else {
+                        DevArtifactKind::Component
+                    }
+                });
+                stubbed(
+                    kind,
+                    minimal.name.clone().unwrap_or(name),
+                    minimal.version.clone(),
+                    minimal.artifact_path.clone(),
+                    minimal.artifact_path.clone(),
+                    minimal.digest.clone(),
+                )
+            }
+        })
+    }
+
+    fn download(
+        &self,
+        resolved: &DevResolveResponse,
+        store: &ArtifactStore,
+        file_name: &str,
+    ) -> Result<StoredArtifact> {
+        let location = resolved.artifact_download_path.as_str();
+        let digest = resolved.digest.as_deref();
+        if location.is_empty() {
+            bail!(
+                "resolve stub {} returned distributor handle `{}`, which cannot be downloaded without a distributor",
+                self.path.display(),
+                resolved.artifact_id
+            );
+        }
+        if location.starts_with("http://") || location.starts_with("https://") {
+            return Downloader::anonymous()?.fetch_into_store(location, digest, store, file_name);
+        }
+        if location.starts_with(OCI_SCHEME) {
+            let kind = match resolved.kind {
+                DevArtifactKind::Component => OciArtifactKind::Component,
+                DevArtifactKind::Pack => OciArtifactKind::Pack,
+            };
+            let pulled = OciClient::load()?.pull(location, kind)?;
+            return store.insert(digest, file_name, &pulled.bytes);
         }
-        response.bytes().map_err(DevDistributorError::Http)
+        let local = location.strip_prefix("file://").unwrap_or(location);
+        let bytes =
+            fs::read(local).with_context(|| format!("failed to read artifact at {local}"))?;
+        store.insert(digest, file_name, &bytes)
     }
 }
@@ -0,0 +1,513 @@
+use std::fs;
+use std::net::{SocketAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{AUTHORIZATION, CONTENT_RANGE, CONTENT_TYPE, RANGE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::runtime::Runtime;
+
+use crate::distributor::{
+    DevArtifactKind, DevLicenseInfo, DevLicenseRequiredErrorBody, DevLicenseType,
+    DevResolveRequest, DevResolveResponse, TENANT_HEADER,
+};
+use crate::events::{self, Event};
+use crate::path_safety::normalize_under_root;
+
+const ARTIFACT_PREFIX: &str = "/v1/artifact/";
+const LICENSE_FILE: &str = "license.toml";
+
+/// A directory of components and packs served like a distributor:
+///
+/// ```text
+/// <root>/components/<org>/<name>/<version>/*.wasm
+/// <root>/packs/<org>/<name>/<version>/*.gtpack
+/// ```
+///
+/// `component://org/name@^1` resolves to the newest matching version directory. An optional
+/// `license.toml` in the version directory (or the name directory, for every version) describes
+/// the license.
+#[derive(Debug, Clone)]
+pub struct LocalRegistry {
+    root: PathBuf,
+    token: Option<String>,
+}
+
+/// `license.toml` next to an artifact.
+#[derive(Debug, Clone, Deserialize)]
+struct LicenseFile {
+    license_type: DevLicenseType,
+    id: Option<String>,
+    #[serde(default)]
+    requires_acceptance: bool,
+    checkout_url: Option<String>,
+    terms: Option<String>,
+    /// Tenants (`x-greentic-tenant-id`) allowed to resolve a commercial artifact; everyone else
+    /// gets `402 Payment Required`.
+    #[serde(default)]
+    entitled_tenants: Vec<String>,
+}
+
+/// A failed request, rendered as a JSON error body.
+#[derive(Debug)]
+pub enum RegistryError {
+    BadRequest(String),
+    NotFound(String),
+    LicenseRequired(DevLicenseRequiredErrorBody),
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for RegistryError {
+    fn from(err: anyhow::Error) -> Self {
+        RegistryError::Internal(err)
+    }
+}
+
+impl LocalRegistry {
+    /// `token`, when set, is required as a bearer token on every request.
+    pub fn open(root: &Path, token: Option<String>) -> Result<Self> {
+        let root = root
+            .canonicalize()
+            .with_context(|| format!("registry root {} does not exist", root.display()))?;
+        if !root.is_dir() {
+            bail!("registry root {} is not a directory", root.display());
+        }
+        Ok(Self { root, token })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolve a coordinate the way `/v1/resolve` does for `tenant`.
+    pub fn resolve(
+        &self,
+        req: &DevResolveRequest,
+        tenant: Option<&str>,
+    ) -> Result<DevResolveResponse, RegistryError> {
+        let (kind, path, req_raw) = parse_coordinate(&req.coordinate)?;
+        let version_req = VersionReq::parse(req_raw).map_err(|err| {
+            RegistryError::BadRequest(format!("invalid version requirement `{req_raw}`: {err}"))
+        })?;
+        let name_dir = self.name_dir(kind_dir(&kind), path)?;
+        let version = newest_matching(&name_dir, &version_req).ok_or_else(|| {
+            RegistryError::NotFound(format!(
+                "no version of `{path}` matches `{version_req}` in {}",
+                name_dir.display()
+            ))
+        })?;
+        let version_dir = name_dir.join(version.to_string());
+        let artifact = artifact_file(&version_dir, &kind)?;
+        let license = self.check_license(&req.coordinate, &version_dir, tenant)?;
+        let bytes = fs::read(&artifact)
+            .with_context(|| format!("failed to read {}", artifact.display()))?;
+        let artifact_id = format!("{}/{path}/{version}", kind_dir(&kind));
+
+        Ok(DevResolveResponse {
+            kind,
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            version: Some(version.to_string()),
+            coordinate: req.coordinate.clone(),
+            artifact_download_path: format!("{ARTIFACT_PREFIX}{artifact_id}"),
+            artifact_id,
+            digest: Some(format!("sha256:{:x}", Sha256::digest(&bytes))),
+            license,
+            metadata: json!({}),
+        })
+    }
+
+    /// Bytes for an `artifact_id` returned by [`LocalRegistry::resolve`].
+    pub fn artifact(
+        &self,
+        artifact_id: &str,
+        tenant: Option<&str>,
+    ) -> Result<Vec<u8>, RegistryError> {
+        let (kind, rest) = match artifact_id.split_once('/') {
+            Some(("components", rest)) => (DevArtifactKind::Component, rest),
+            Some(("packs", rest)) => (DevArtifactKind::Pack, rest),
+            _ => {
+                return Err(RegistryError::NotFound(format!(
+                    "unknown artifact `{artifact_id}`"
+                )));
+            }
+        };
+        let Some((path, version)) = rest.rsplit_once('/') else {
+            return Err(RegistryError::NotFound(format!(
+                "unknown artifact `{artifact_id}`"
+            )));
+        };
+        let version_dir = self.name_dir(kind_dir(&kind), path)?.join(version);
+        if Version::parse(version).is_err() || !version_dir.is_dir() {
+            return Err(RegistryError::NotFound(format!(
+                "unknown artifact `{artifact_id}`"
+            )));
+        }
+        let coordinate = format!("{}://{path}@{version}", kind_scheme(&kind));
+        self.check_license(&coordinate, &version_dir, tenant)?;
+        let artifact = artifact_file(&version_dir, &kind)?;
+        Ok(
+            fs::read(&artifact)
+                .with_context(|| format!("failed to read {}", artifact.display()))?,
+        )
+    }
+
+    fn name_dir(&self, kind_dir: &str, path: &str) -> Result<PathBuf, RegistryError> {
+        if path.split('/').any(|segment| {
+            segment.is_empty() || segment == "." || segment == ".." || segment.contains('\\')
+        }) {
+            return Err(RegistryError::BadRequest(format!(
+                "invalid artifact path `{path}`"
+            )));
+        }
+        let dir = Path::new(kind_dir).join(path);
+        normalize_under_root(&self.root, &dir)
+            .map_err(|_| RegistryError::NotFound(format!("`{path}` is not in this registry")))
+    }
+
+    /// License for the artifact in `version_dir`; commercial artifacts need an entitled tenant.
+    fn check_license(
+        &self,
+        coordinate: &str,
+        version_dir: &Path,
+        tenant: Option<&str>,
+    ) -> Result<DevLicenseInfo, RegistryError> {
+        let Some(license) = read_license(version_dir)? else {
+            return Ok(DevLicenseInfo {
+                license_type: DevLicenseType::Free,
+                id: None,
+                requires_acceptance: false,
+                checkout_url: None,
+                terms: None,
+            });
+        };
+        let entitled = tenant.is_some_and(|tenant| {
+            license
+                .entitled_tenants
+                .iter()
+                .any(|allowed| allowed == tenant)
+        });
+        if matches!(license.license_type, DevLicenseType::Commercial) && !entitled {
+            return Err(RegistryError::LicenseRequired(
+                DevLicenseRequiredErrorBody {
+                    error: "license_required".to_string(),
+                    coordinate: coordinate.to_string(),
+                    message: format!(
+                        "tenant `{}` has no license{} for this artifact",
+                        tenant.unwrap_or("<none>"),
+                        license
+                            .id
+                            .as_deref()
+                            .map(|id| format!(" `{id}`"))
+                            .unwrap_or_default()
+                    ),
+                    checkout_url: license.checkout_url.clone().unwrap_or_default(),
+                },
+            ));
+        }
+        Ok(DevLicenseInfo {
+            license_type: license.license_type,
+            id: license.id,
+            requires_acceptance: license.requires_acceptance,
+            checkout_url: license.checkout_url,
+            terms: license.terms,
+        })
+    }
+
+    fn authorized(&self, req: &Request<Incoming>) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        req.headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|presented| presented == token)
+    }
+}
+
+/// A bound listener serving a [`LocalRegistry`] over HTTP.
+pub struct DistributorServer {
+    registry: Arc<LocalRegistry>,
+    listener: TcpListener,
+}
+
+impl DistributorServer {
+    pub fn bind(registry: LocalRegistry, listen: &str) -> Result<Self> {
+        let listener =
+            TcpListener::bind(listen).with_context(|| format!("failed to listen on {listen}"))?;
+        listener
+            .set_nonblocking(true)
+            .context("failed to configure listener")?;
+        Ok(Self {
+            registry: Arc::new(registry),
+            listener,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener
+            .local_addr()
+            .context("failed to read listener address")
+    }
+
+    /// Serve requests until the process exits.
+    pub fn serve(self) -> Result<()> {
+        let runtime = Runtime::new().context("failed to start tokio runtime for distributor")?;
+        runtime.block_on(async move {
+            let listener = tokio::net::TcpListener::from_std(self.listener)
+                .context("failed to register listener")?;
+            loop {
+                let (stream, _) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        eprintln!("warning: failed to accept connection: {err}");
+                        continue;
+                    }
+                };
+                let registry = self.registry.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |req| {
+                        let registry = registry.clone();
+                        async move { Ok::<_, hyper::Error>(handle(&registry, req).await) }
+                    });
+                    if let Err(err) = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                    {
+                        eprintln!("warning: connection error: {err}");
+                    }
+                });
+            }
+        })
+    }
+}
+
+/// `distributor serve`: serve `root` until interrupted.
+pub fn run_serve(root: &Path, listen: &str, token: Option<String>) -> Result<()> {
+    let registry = LocalRegistry::open(root, token)?;
+    let server = DistributorServer::bind(registry.clone(), listen)?;
+    let url = format!("http://{}", server.local_addr()?);
+    events::emit(Event::Result {
+        data: json!({ "root": registry.root(), "url": url }),
+    });
+    events::status_line(&format!(
+        "Serving {} at {url} (Ctrl-C to stop)",
+        registry.root().display()
+    ));
+    server.serve()
+}
+
+async fn handle(registry: &LocalRegistry, req: Request<Incoming>) -> Response<Full<Bytes>> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let response = if !registry.authorized(&req) {
+        json_response(
+            StatusCode::UNAUTHORIZED,
+            &json!({ "error": "unauthorized", "message": "missing or invalid bearer token" }),
+        )
+    } else {
+        let tenant = req
+            .headers()
+            .get(TENANT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        match (&method, path.as_str()) {
+            (&Method::POST, "/v1/resolve") => resolve(registry, req, tenant.as_deref()).await,
+            (&Method::GET, artifact) if artifact.starts_with(ARTIFACT_PREFIX) => {
+                let id = &artifact[ARTIFACT_PREFIX.len()..];
+                let range = req
+                    .headers()
+                    .get(RANGE)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+                match registry.artifact(id, tenant.as_deref()) {
+                    Ok(bytes) => artifact_response(bytes, range.as_deref()),
+                    Err(err) => error_response(err),
+                }
+            }
+            _ => json_response(
+                StatusCode::NOT_FOUND,
+                &json!({ "error": "not_found", "message": format!("no route for {method} {path}") }),
+            ),
+        }
+    };
+    events::status_line(&format!("{method} {path} -> {}", response.status()));
+    response
+}
+
+async fn resolve(
+    registry: &LocalRegistry,
+    req: Request<Incoming>,
+    tenant: Option<&str>,
+) -> Response<Full<Bytes>> {
+    let body = match req.into_body().collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(err) => {
+            return error_response(RegistryError::BadRequest(format!(
+                "failed to read request body: {err}"
+            )));
+        }
+    };
+    let request: DevResolveRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(err) => {
+            return error_response(RegistryError::BadRequest(format!(
+                "invalid resolve request: {err}"
+            )));
+        }
+    };
+    match registry.resolve(&request, tenant) {
+        Ok(resolved) => json_response(StatusCode::OK, &resolved),
+        Err(err) => error_response(err),
+    }
+}
+
+/// Serve `bytes`, honouring `Range: bytes=<start>-` so interrupted downloads can resume.
+fn artifact_response(bytes: Vec<u8>, range: Option<&str>) -> Response<Full<Bytes>> {
+    let total = bytes.len();
+    let start = range
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.strip_suffix('-'))
+        .and_then(|start| start.parse::<usize>().ok());
+    let mut builder = Response::builder().header(CONTENT_TYPE, "application/octet-stream");
+    let body = match start {
+        Some(start) if start >= total => {
+            return Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(CONTENT_RANGE, format!("bytes */{total}"))
+                .body(Full::new(Bytes::new()))
+                .expect("static response");
+        }
+        Some(start) => {
+            builder = builder.status(StatusCode::PARTIAL_CONTENT).header(
+                CONTENT_RANGE,
+                format!("bytes {start}-{}/{total}", total - 1),
+            );
+            Bytes::copy_from_slice(&bytes[start..])
+        }
+        None => Bytes::from(bytes),
+    };
+    builder.body(Full::new(body)).expect("static response")
+}
+
+fn error_response(err: RegistryError) -> Response<Full<Bytes>> {
+    match err {
+        RegistryError::BadRequest(message) => json_response(
+            StatusCode::BAD_REQUEST,
+            &json!({ "error": "bad_request", "message": message }),
+        ),
+        RegistryError::NotFound(message) => json_response(
+            StatusCode::NOT_FOUND,
+            &json!({ "error": "not_found", "message": message }),
+        ),
+        RegistryError::LicenseRequired(body) => json_response(StatusCode::PAYMENT_REQUIRED, &body),
+        RegistryError::Internal(err) => json_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &json!({ "error": "internal", "message": format!("{err:#}") }),
+        ),
+    }
+}
+
+fn json_response(status: StatusCode, body: &impl serde::Serialize) -> Response<Full<Bytes>> {
+    let body = serde_json::to_vec(body).unwrap_or_default();
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Full::new(Bytes::from(body)))
+        .expect("static response")
+}
+
+/// Split `component://org/name@^1` into kind, `org/name` and the version requirement.
+fn parse_coordinate(coordinate: &str) -> Result<(DevArtifactKind, &str, &str), RegistryError> {
+    let (kind, rest) = if let Some(rest) = coordinate.strip_prefix("component://") {
+        (DevArtifactKind::Component, rest)
+    } else if let Some(rest) = coordinate.strip_prefix("pack://") {
+        (DevArtifactKind::Pack, rest)
+    } else {
+        return Err(RegistryError::BadRequest(format!(
+            "unsupported coordinate `{coordinate}` (expected component:// or pack://)"
+        )));
+    };
+    Ok(match rest.rsplit_once('@') {
+        Some((path, req)) => (kind, path, req),
+        None => (kind, rest, "*"),
+    })
+}
+
+fn kind_dir(kind: &DevArtifactKind) -> &'static str {
+    match kind {
+        DevArtifactKind::Component => "components",
+        DevArtifactKind::Pack => "packs",
+    }
+}
+
+fn kind_scheme(kind: &DevArtifactKind) -> &'static str {
+    match kind {
+        DevArtifactKind::Component => "component",
+        DevArtifactKind::Pack => "pack",
+    }
+}
+
+fn newest_matching(name_dir: &Path, req: &VersionReq) -> Option<Version> {
+    fs::read_dir(name_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| Version::parse(&entry.file_name().to_string_lossy()).ok())
+        .filter(|version| req.matches(version))
+        .max()
+}
+
+/// The single `.wasm` (component) or `.gtpack` (pack) file in a version directory.
+fn artifact_file(version_dir: &Path, kind: &DevArtifactKind) -> Result<PathBuf, RegistryError> {
+    let extension = match kind {
+        DevArtifactKind::Component => "wasm",
+        DevArtifactKind::Pack => "gtpack",
+    };
+    let mut candidates: Vec<PathBuf> = fs::read_dir(version_dir)
+        .with_context(|| format!("failed to read {}", version_dir.display()))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == extension))
+        .collect();
+    candidates.sort();
+    match candidates.len() {
+        1 => Ok(candidates.remove(0)),
+        0 => Err(RegistryError::NotFound(format!(
+            "no .{extension} file in {}",
+            version_dir.display()
+        ))),
+        _ => Err(RegistryError::Internal(anyhow::anyhow!(
+            "{} holds more than one .{extension} file",
+            version_dir.display()
+        ))),
+    }
+}
+
+/// `license.toml` from the version directory, falling back to the name directory.
+fn read_license(version_dir: &Path) -> Result<Option<LicenseFile>> {
+    let candidates = [
+        Some(version_dir.join(LICENSE_FILE)),
+        version_dir.parent().map(|dir| dir.join(LICENSE_FILE)),
+    ];
+    for path in candidates.into_iter().flatten() {
+        if path.is_file() {
+            let raw = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let license = toml::from_str(&raw)
+                .with_context(|| format!("failed to parse {}", path.display()))?;
+            return Ok(Some(license));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,382 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow, bail};
+use reqwest::StatusCode;
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{AUTHORIZATION, HeaderMap, RANGE};
+use sha2::{Digest, Sha256};
+
+use crate::artifact_store::{ArtifactStore, StoredArtifact};
+use crate::cache::format_size;
+use crate::distributor::{DistributorProfile, header_map};
+
+const MAX_ATTEMPTS: u32 = 4;
+const BASE_BACKOFF: Duration = Duration::from_millis(250);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a single read may wait for bytes before the attempt is retried and resumed.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// HTTP artifact downloader used for distributor downloads and direct artifact URLs.
+///
+/// Bodies stream into a partial file under the artifact store, failed attempts are resumed with a
+/// `Range` request (also across runs), 5xx/429/connection failures are retried with exponential
+/// backoff, and the result is verified against the expected `sha256:` or `blake3:` digest before
+/// it is moved into the store.
+#[derive(Debug, Clone)]
+pub struct Downloader {
+    http: Client,
+    /// Only URLs under this base receive the profile token and headers.
+    auth_base: Option<String>,
+    token: Option<String>,
+    headers: HeaderMap,
+}
+
+/// Whether a failed attempt is worth repeating.
+enum Failure {
+    Retry(anyhow::Error),
+    Fatal(anyhow::Error),
+}
+
+impl Downloader {
+    pub fn anonymous() -> Result<Self> {
+        Ok(Self {
+            http: http_client(READ_TIMEOUT)?,
+            auth_base: None,
+            token: None,
+            headers: HeaderMap::new(),
+        })
+    }
+
+    /// Authenticate downloads from the profile's distributor with its token, tenant, environment
+    /// and extra headers.
+    pub fn for_profile(profile: &DistributorProfile) -> Result<Self> {
+        let headers = header_map(&profile.request_headers())?;
+        Ok(Self {
+            http: http_client(READ_TIMEOUT)?,
+            auth_base: Some(profile.url.trim_end_matches('/').to_string()),
+            token: profile.token.clone(),
+            headers,
+        })
+    }
+
+    /// Give up on a read that receives no bytes for `timeout`, instead of the default minute.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.http = http_client(timeout)?;
+        Ok(self)
+    }
+
+    /// Download `url` and publish it in `store`. Callers check [`ArtifactStore::get`] first; it is
+    /// checked again once the download lock is held.
+    pub fn fetch_into_store(
+        &self,
+        url: &str,
+        expected_digest: Option<&str>,
+        store: &ArtifactStore,
+        file_name: &str,
+    ) -> Result<StoredArtifact> {
+        let key = partial_key(url, expected_digest);
+        // Held until the artifact is published so concurrent runs never interleave writes to the
+        // same partial file; a run that waited picks up the entry the other one stored.
+        let _lock = store.lock_partial(&key)?;
+        // blake3 digests were checked while streaming; the store only knows sha256.
+        let store_digest = expected_digest.filter(|digest| digest.starts_with("sha256:"));
+        if let Some(digest) = store_digest
+            && let Some(stored) = store.get(digest, file_name)?
+        {
+            return Ok(stored);
+        }
+        let partial = store.partial_path(&key);
+        self.download(url, expected_digest, &partial)?;
+        store.insert_file(store_digest, file_name, &partial)
+    }
+
+    /// Download `url` into `partial`, resuming from whatever an earlier attempt left there.
+    pub fn download(&self, url: &str, expected_digest: Option<&str>, partial: &Path) -> Result<()> {
+        if let Some(parent) = partial.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let mut attempt = 1;
+        loop {
+            match self.attempt(url, expected_digest, partial) {
+                Ok(()) => return Ok(()),
+                Err(Failure::Retry(err)) if attempt < MAX_ATTEMPTS => {
+                    let delay = BASE_BACKOFF * 2u32.pow(attempt - 1);
+                    eprintln!(
+                        "warning: download of {url} failed ({err:#}); retrying in {}ms",
+                        delay.as_millis()
+                    );
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                Err(Failure::Retry(err)) => {
+                    return Err(err.context(format!(
+                        "failed to download {url} after {MAX_ATTEMPTS} attempts"
+                    )));
+                }
+                Err(Failure::Fatal(err)) => return Err(err),
+            }
+        }
+    }
+
+    fn attempt(
+        &self,
+        url: &str,
+        expected_digest: Option<&str>,
+        partial: &Path,
+    ) -> Result<(), Failure> {
+        let offset = fs::metadata(partial).map(|meta| meta.len()).unwrap_or(0);
+        let mut request = self.http.get(url);
+        if self
+            .auth_base
+            .as_deref()
+            .is_some_and(|base| url.starts_with(base))
+        {
+            request = request.headers(self.headers.clone());
+            if let Some(token) = &self.token {
+                request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+            }
+        }
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={offset}-"));
+        }
+        let mut response = request.send().map_err(|err| Failure::Retry(anyhow!(err)))?;
+
+        let status = response.status();
+        let resumed = match status {
+            StatusCode::PARTIAL_CONTENT if offset > 0 => true,
+            StatusCode::RANGE_NOT_SATISFIABLE => {
+                let _ = fs::remove_file(partial);
+                return Err(Failure::Retry(anyhow!(
+                    "server rejected resuming at byte {offset}"
+                )));
+            }
+            status if status.is_success() => false,
+            status
+                if status.is_server_error()
+                    || status == StatusCode::TOO_MANY_REQUESTS
+                    || status == StatusCode::REQUEST_TIMEOUT =>
+            {
+                return Err(Failure::Retry(status_error(url, status, response)));
+            }
+            status => return Err(Failure::Fatal(status_error(url, status, response))),
+        };
+
+        let mut verifier = Verifier::new(expected_digest);
+        let mut file = if resumed {
+            let mut existing = File::open(partial).map_err(|err| Failure::Fatal(err.into()))?;
+            io::copy(&mut existing, &mut verifier).map_err(|err| Failure::Fatal(err.into()))?;
+            OpenOptions::new().append(true).open(partial)
+        } else {
+            File::create(partial)
+        }
+        .with_context(|| format!("failed to open {}", partial.display()))
+        .map_err(Failure::Fatal)?;
+
+        let start = if resumed { offset } else { 0 };
+        let total = response.content_length().map(|len| len + start);
+        let mut progress = Progress::new(url, start, total);
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let read = response
+                .read(&mut buf)
+                .map_err(|err| Failure::Retry(anyhow!(err).context("connection interrupted")))?;
+            if read == 0 {
+                break;
+            }
+            file.write_all(&buf[..read])
+                .with_context(|| format!("failed to write {}", partial.display()))
+                .map_err(Failure::Fatal)?;
+            verifier.update(&buf[..read]);
+            progress.advance(read as u64);
+        }
+        progress.finish();
+        if let Some(total) = total
+            && progress.done < total
+        {
+            return Err(Failure::Retry(anyhow!(
+                "connection closed after {} of {} bytes",
+                progress.done,
+                total
+            )));
+        }
+
+        if let Err(err) = verifier.verify() {
+            let _ = fs::remove_file(partial);
+            // A resumed file may have been stitched from a different upload; start over once.
+            return Err(if resumed {
+                Failure::Retry(err)
+            } else {
+                Failure::Fatal(err)
+            });
+        }
+        Ok(())
+    }
+}
+
+fn http_client(read_timeout: Duration) -> Result<Client> {
+    // The blocking client applies `timeout` to the wait for response headers and to each read of
+    // the body, not to the transfer as a whole. A large artifact can take as long as it needs,
+    // while a stalled connection fails the read; the retry loop then resumes it with a Range.
+    Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(read_timeout)
+        .build()
+        .context("failed to build HTTP client")
+}
+
+fn status_error(url: &str, status: StatusCode, response: Response) -> anyhow::Error {
+    match response.text() {
+        Ok(body) if !body.trim().is_empty() => {
+            anyhow!("GET {url} returned {status}: {}", body.trim())
+        }
+        _ => anyhow!("GET {url} returned {status}"),
+    }
+}
+
+/// Partial files are named after the expected digest when there is one, so a resumed download
+/// can never be stitched onto bytes of a different artifact.
+fn partial_key(url: &str, expected_digest: Option<&str>) -> String {
+    match expected_digest.and_then(|digest| digest.split_once(':')) {
+        Some((algorithm, hex)) if hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            format!("{algorithm}-{hex}")
+        }
+        _ => format!("url-{:x}", Sha256::digest(url.as_bytes())),
+    }
+}
+
+enum Hasher {
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+/// Streaming digest check against `sha256:<hex>` or `blake3:<hex>`.
+struct Verifier {
+    expected: Option<String>,
+    hasher: Hasher,
+}
+
+impl Verifier {
+    fn new(expected: Option<&str>) -> Self {
+        let hasher = match expected {
+            Some(digest) if digest.starts_with("blake3:") => {
+                Hasher::Blake3(Box::new(blake3::Hasher::new()))
+            }
+            _ => Hasher::Sha256(Sha256::new()),
+        };
+        Self {
+            expected: expected.map(str::to_ascii_lowercase),
+            hasher,
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match &mut self.hasher {
+            Hasher::Sha256(hasher) => hasher.update(bytes),
+            Hasher::Blake3(hasher) => {
+                hasher.update(bytes);
+            }
+        }
+    }
+
+    fn verify(self) -> Result<()> {
+        let Some(expected) = self.expected else {
+            return Ok(());
+        };
+        let actual = match self.hasher {
+            Hasher::Sha256(hasher) => format!("sha256:{:x}", hasher.finalize()),
+            Hasher::Blake3(hasher) => format!("blake3:{}", hasher.finalize().to_hex()),
+        };
+        if !expected.starts_with("sha256:") && !expected.starts_with("blake3:") {
+            eprintln!(
+                "warning: cannot verify artifact digest `{expected}` (only sha256 and blake3 are supported)"
+            );
+            return Ok(());
+        }
+        if expected != actual {
+            bail!("downloaded artifact digest mismatch: expected {expected}, got {actual}");
+        }
+        Ok(())
+    }
+}
+
+impl Write for Verifier {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Single-line progress on stderr, shown only when stderr is a terminal.
+struct Progress {
+    label: String,
+    done: u64,
+    total: Option<u64>,
+    enabled: bool,
+    last_draw: Option<Instant>,
+}
+
+impl Progress {
+    fn new(url: &str, done: u64, total: Option<u64>) -> Self {
+        let label = url
+            .rsplit('/')
+            .find(|segment| !segment.is_empty())
+            .unwrap_or(url)
+            .to_string();
+        Self {
+            label,
+            done,
+            total,
+            enabled: io::stderr().is_terminal(),
+            last_draw: None,
+        }
+    }
+
+    fn advance(&mut self, bytes: u64) {
+        self.done += bytes;
+        if self
+            .last_draw
+            .is_some_and(|last| last.elapsed() < PROGRESS_INTERVAL)
+        {
+            return;
+        }
+        self.draw();
+    }
+
+    fn draw(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        self.last_draw = Some(Instant::now());
+        match self.total {
+            Some(total) if total > 0 => eprint!(
+                "\r\x1b[2KDownloading {} {} / {} ({}%)",
+                self.label,
+                format_size(self.done),
+                format_size(total),
+                self.done * 100 / total
+            ),
+            _ => eprint!(
+                "\r\x1b[2KDownloading {} {}",
+                self.label,
+                format_size(self.done)
+            ),
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.enabled && self.last_draw.is_some() {
+            self.draw();
+            eprintln!();
+        }
+    }
+}
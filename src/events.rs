@@ -0,0 +1,142 @@
+//! `--output json`: newline-delimited JSON events on stdout instead of human text.
+
+use std::io::Write;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::diagnostics;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    /// One JSON event per line on stdout.
+    Json,
+}
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// Select the output format for the process; later calls are ignored.
+pub fn set_format(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+/// Whether commands should emit events instead of human text on stdout.
+pub fn json() -> bool {
+    FORMAT.get().copied().unwrap_or_default() == OutputFormat::Json
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    Started {
+        command: &'a str,
+    },
+    StageStarted {
+        stage: &'a str,
+    },
+    StageFinished {
+        stage: &'a str,
+    },
+    /// A file the command produced or updated.
+    Artifact {
+        kind: &'a str,
+        path: &'a Path,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        hash: Option<&'a str>,
+    },
+    /// Structured output of commands that have a `--json` report in text mode.
+    Result {
+        data: Value,
+    },
+    Warning {
+        code: &'a str,
+        message: &'a str,
+    },
+    Error {
+        code: &'a str,
+        message: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        help: Option<&'a str>,
+    },
+    /// Always the last event; the process exits with `exit_code`.
+    Finished {
+        command: &'a str,
+        status: &'a str,
+        exit_code: i32,
+    },
+}
+
+/// Write `event` as one line of JSON on stdout in `--output json` mode; a no-op otherwise.
+pub fn emit(event: Event<'_>) {
+    if !json() {
+        return;
+    }
+    let line = serde_json::to_string(&event).expect("events serialize to JSON");
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{line}");
+    let _ = stdout.flush();
+}
+
+pub fn stage_started(stage: &str) {
+    emit(Event::StageStarted { stage });
+}
+
+pub fn stage_finished(stage: &str) {
+    emit(Event::StageFinished { stage });
+}
+
+pub fn artifact(kind: &str, path: &Path, hash: Option<&str>) {
+    emit(Event::Artifact { kind, path, hash });
+}
+
+/// Print a human-readable status line: on stdout in text mode, on stderr in `--output json` mode
+/// so long-running commands keep the event stream parseable.
+pub fn status_line(line: &str) {
+    if json() {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
+}
+
+/// Report a warning: an event in `--output json` mode, `warning: ...` on stderr otherwise.
+pub fn warning(code: &str, message: &str) {
+    if json() {
+        emit(Event::Warning { code, message });
+    } else {
+        eprintln!("warning: {message}");
+    }
+}
+
+/// Emit the final events for `command` and return the exit code they carry.
+pub fn finish(command: &str, result: &anyhow::Result<()>) -> i32 {
+    match result {
+        Ok(()) => {
+            emit(Event::Finished {
+                command,
+                status: "ok",
+                exit_code: 0,
+            });
+            0
+        }
+        Err(err) => {
+            let diagnostic = diagnostics::find(err);
+            emit(Event::Error {
+                code: diagnostics::code(err),
+                message: &format!("{err:#}"),
+                help: diagnostic.and_then(|diagnostic| diagnostic.help.as_deref()),
+            });
+            emit(Event::Finished {
+                command,
+                status: "error",
+                exit_code: 1,
+            });
+            1
+        }
+    }
+}
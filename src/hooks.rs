@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::process::ExitStatus;
+
+use anyhow::{Result, bail};
+
+use crate::config::HookEntry;
+use crate::util::process::{self, CommandSpec, StreamMode};
+
+/// Set for hook processes; passthroughs started from a hook do not run hooks again.
+pub const PHASE_ENV: &str = "GREENTIC_DEV_HOOK_PHASE";
+pub const COMMAND_ENV: &str = "GREENTIC_DEV_HOOK_COMMAND";
+pub const ARGS_ENV: &str = "GREENTIC_DEV_HOOK_ARGS";
+pub const ARGS_JSON_ENV: &str = "GREENTIC_DEV_HOOK_ARGS_JSON";
+pub const EXIT_STATUS_ENV: &str = "GREENTIC_DEV_HOOK_EXIT_STATUS";
+pub const EXE_ENV: &str = "GREENTIC_DEV_EXE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPhase {
+    Pre,
+    Post,
+}
+
+impl HookPhase {
+    fn as_str(self) -> &'static str {
+        match self {
+            HookPhase::Pre => "pre",
+            HookPhase::Post => "post",
+        }
+    }
+}
+
+/// Hooks configured for `greentic-dev <command> <args>`: those keyed by `<command>` run before
+/// those keyed by `<command> <subcommand>`.
+#[derive(Debug, Default)]
+pub struct Hooks {
+    command: String,
+    args: Vec<OsString>,
+    pre: Vec<String>,
+    post: Vec<String>,
+}
+
+impl Hooks {
+    pub fn for_command(
+        config: &HashMap<String, HookEntry>,
+        command: &str,
+        args: &[OsString],
+    ) -> Self {
+        let mut hooks = Hooks {
+            command: command.to_string(),
+            args: args.to_vec(),
+            ..Hooks::default()
+        };
+        if env::var_os(PHASE_ENV).is_some() {
+            return hooks;
+        }
+        let mut keys = vec![command.to_string()];
+        if let Some(subcommand) = args.first().and_then(|arg| arg.to_str()) {
+            keys.push(format!("{command} {subcommand}"));
+        }
+        for key in keys {
+            if let Some(entry) = config.get(&key) {
+                hooks.pre.extend(entry.pre.iter().cloned());
+                hooks.post.extend(entry.post.iter().cloned());
+            }
+        }
+        hooks
+    }
+
+    /// Run the pre hooks in order; the first failure aborts the passthrough.
+    pub fn run_pre(&self, verbose: bool) -> Result<()> {
+        for hook in &self.pre {
+            let status = self.run_hook(HookPhase::Pre, hook, None, verbose)?;
+            if !status.success() {
+                bail!(
+                    "pre hook `{hook}` for `{}` exited with code {}; aborting",
+                    self.label(),
+                    status.code().unwrap_or(1)
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Run every post hook with the delegated command's exit status. Fails if any hook failed.
+    pub fn run_post(&self, delegated: ExitStatus, verbose: bool) -> Result<()> {
+        let mut failed = Vec::new();
+        for hook in &self.post {
+            let status = self.run_hook(HookPhase::Post, hook, Some(delegated), verbose)?;
+            if !status.success() {
+                failed.push(format!("`{hook}` ({})", status.code().unwrap_or(1)));
+            }
+        }
+        if !failed.is_empty() {
+            bail!(
+                "post hook(s) for `{}` failed: {}",
+                self.label(),
+                failed.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        match self.args.first() {
+            Some(subcommand) => format!("{} {}", self.command, subcommand.to_string_lossy()),
+            None => self.command.clone(),
+        }
+    }
+
+    fn run_hook(
+        &self,
+        phase: HookPhase,
+        hook: &str,
+        delegated: Option<ExitStatus>,
+        verbose: bool,
+    ) -> Result<ExitStatus> {
+        if verbose {
+            eprintln!("greentic-dev: {} hook -> {hook}", phase.as_str());
+        }
+        let mut spec = if cfg!(windows) {
+            let mut spec = CommandSpec::new("cmd");
+            spec.args = vec!["/C".into(), hook.into()];
+            spec
+        } else {
+            let mut spec = CommandSpec::new("sh");
+            spec.args = vec!["-c".into(), hook.into()];
+            spec
+        };
+        spec.stdout = StreamMode::for_child_stdout();
+        let args: Vec<String> = self
+            .args
+            .iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        spec.env = vec![
+            (PHASE_ENV.into(), phase.as_str().into()),
+            (COMMAND_ENV.into(), self.command.clone().into()),
+            (ARGS_ENV.into(), args.join(" ").into()),
+            (
+                ARGS_JSON_ENV.into(),
+                serde_json::to_string(&args)
+                    .expect("string list serializes")
+                    .into(),
+            ),
+        ];
+        if let Some(status) = delegated {
+            spec.env.push((
+                EXIT_STATUS_ENV.into(),
+                status.code().unwrap_or(1).to_string().into(),
+            ));
+        }
+        if let Ok(exe) = env::current_exe() {
+            spec.env.push((EXE_ENV.into(), exe.into()));
+        }
+        Ok(process::run(spec)?.status)
+    }
+}
@@ -1,16 +1,31 @@
+pub mod artifact_store;
+pub mod cache;
 pub mod cbor_cmd;
 pub mod cli;
 pub mod cmd;
 pub mod component_add;
 pub mod component_cli;
 pub mod component_resolver;
+pub mod component_templates;
+pub mod component_update;
 pub mod config;
+pub mod config_check;
+pub mod credentials;
 pub mod delegate;
 pub mod dev_runner;
+pub mod diagnostics;
 pub mod distributor;
+pub mod distributor_server;
+pub mod download;
+pub mod events;
+pub mod hooks;
+pub mod license;
+pub mod lockfile;
 pub mod mcp_cmd;
+pub mod oci;
 pub mod pack_build;
 pub mod pack_init;
+pub mod pack_profile;
 pub mod pack_verify;
 pub mod passthrough;
 pub mod path_safety;
@@ -0,0 +1,152 @@
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::artifact_store::{ArtifactStore, StoredArtifact};
+use crate::distributor::{DevLicenseType, DevResolveResponse};
+use crate::lockfile::artifact_digest;
+use crate::pack_init::WorkspaceManifest;
+
+/// License recorded for a workspace component in `.greentic/manifest.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentLicense {
+    pub license_type: String,
+    pub id: String,
+    /// RFC 3339 time the license was accepted; `None` when it still needs acceptance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accepted_at: Option<String>,
+}
+
+impl ComponentLicense {
+    /// Commercial and trial components are expected to carry a recorded acceptance.
+    pub fn needs_acceptance(&self) -> bool {
+        matches!(self.license_type.as_str(), "commercial" | "trial")
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        self.accepted_at.is_some()
+    }
+}
+
+/// Make sure the license of a resolved component is accepted before it is downloaded.
+///
+/// A license already accepted in the workspace manifest stays accepted. Otherwise it is accepted
+/// when its id is in `accepted` (`--accept-license`), or interactively when stdin is a terminal.
+/// Returns the license to record, or `None` for free components that need no acceptance.
+pub fn ensure_accepted(
+    resolved: &DevResolveResponse,
+    accepted: &[String],
+    manifest: &WorkspaceManifest,
+) -> Result<Option<ComponentLicense>> {
+    let info = &resolved.license;
+    let license_type = license_type_name(&info.license_type);
+    let requires_acceptance =
+        info.requires_acceptance || !matches!(info.license_type, DevLicenseType::Free);
+    if !requires_acceptance {
+        return Ok(None);
+    }
+    let id = info.id.clone().unwrap_or_else(|| resolved.name.clone());
+
+    let previous = manifest
+        .component(&resolved.name)
+        .and_then(|component| component.license.as_ref())
+        .filter(|license| license.id == id && license.is_accepted());
+    if let Some(previous) = previous {
+        return Ok(Some(previous.clone()));
+    }
+
+    if !accepted.iter().any(|accepted| accepted == &id) && !prompt(resolved, &id)? {
+        let checkout = info
+            .checkout_url
+            .as_deref()
+            .map(|url| format!(" (details: {url})"))
+            .unwrap_or_default();
+        bail!(
+            "`{}` is distributed under {license_type} license `{id}`{checkout}, which has not been accepted; rerun with --accept-license {id}",
+            resolved.coordinate
+        );
+    }
+    let accepted_at = OffsetDateTime::now_utc()
+        .format(&Rfc3339)
+        .context("failed to format acceptance time")?;
+    Ok(Some(ComponentLicense {
+        license_type: license_type.to_string(),
+        id,
+        accepted_at: Some(accepted_at),
+    }))
+}
+
+/// Sidecar next to a store entry that remembers the license it was distributed under.
+const STORE_LICENSE_FILE: &str = "license.json";
+
+/// Remember `license` next to the stored artifact so `pack build` can flag the component even in
+/// workspaces that never recorded it. Acceptance is per workspace and is not stored.
+pub fn record_in_store(artifact: &StoredArtifact, license: &ComponentLicense) -> Result<()> {
+    let path = artifact.dir().join(STORE_LICENSE_FILE);
+    let license = ComponentLicense {
+        accepted_at: None,
+        ..license.clone()
+    };
+    let json = serde_json::to_vec_pretty(&license).context("failed to serialize license")?;
+    fs::write(&path, json).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// License recorded in the store for the artifact with the same bytes as `wasm_path`, if any.
+pub fn stored_license(store: &ArtifactStore, wasm_path: &Path) -> Result<Option<ComponentLicense>> {
+    let bytes =
+        fs::read(wasm_path).with_context(|| format!("failed to read {}", wasm_path.display()))?;
+    let digest = artifact_digest(&bytes);
+    let hex = digest.trim_start_matches("sha256:");
+    let path = store.entries_dir().join(hex).join(STORE_LICENSE_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let json =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let license = serde_json::from_str(&json)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    Ok(Some(license))
+}
+
+/// Show the license terms and ask for acceptance. Non-interactive sessions (no terminal on stdin,
+/// or `CI` set) never prompt.
+fn prompt(resolved: &DevResolveResponse, id: &str) -> Result<bool> {
+    if !io::stdin().is_terminal() || std::env::var_os("CI").is_some() {
+        return Ok(false);
+    }
+    let info = &resolved.license;
+    let mut stderr = io::stderr();
+    writeln!(
+        stderr,
+        "{} is distributed under a {} license ({id}).",
+        resolved.coordinate,
+        license_type_name(&info.license_type)
+    )?;
+    if let Some(terms) = &info.terms {
+        writeln!(stderr, "\n{}\n", terms.trim_end())?;
+    }
+    if let Some(url) = &info.checkout_url {
+        writeln!(stderr, "Details: {url}")?;
+    }
+    write!(stderr, "Accept license {id}? [y/N] ")?;
+    stderr.flush()?;
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .context("failed to read license answer")?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "Yes" | "YES"))
+}
+
+fn license_type_name(license_type: &DevLicenseType) -> &'static str {
+    match license_type {
+        DevLicenseType::Free => "free",
+        DevLicenseType::Commercial => "commercial",
+        DevLicenseType::Trial => "trial",
+    }
+}
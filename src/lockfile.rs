@@ -0,0 +1,172 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const LOCKFILE_NAME: &str = "greentic.lock";
+const LOCKFILE_VERSION: u32 = 1;
+const LOCKFILE_HEADER: &str = "# This file is generated by greentic-dev. Do not edit it by hand.\n";
+
+/// Workspace lockfile pinning every distributor-resolved component to an exact artifact.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorkspaceLock {
+    pub version: u32,
+    #[serde(default, rename = "component", skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<LockedComponent>,
+}
+
+impl Default for WorkspaceLock {
+    fn default() -> Self {
+        Self {
+            version: LOCKFILE_VERSION,
+            components: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LockedComponent {
+    pub name: String,
+    pub coordinate: String,
+    /// Exact version the coordinate resolved to.
+    pub version: String,
+    /// `sha256:<hex>` digest of the downloaded artifact bytes.
+    pub digest: String,
+    /// Distributor profile used for resolution; absent for stubbed/offline resolutions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Artifact size in bytes.
+    pub size: u64,
+}
+
+impl LockedComponent {
+    /// Describe how `other` differs from this entry, or `None` when the artifact is identical.
+    pub fn diff(&self, other: &LockedComponent) -> Option<String> {
+        let mut changes = Vec::new();
+        if self.version != other.version {
+            changes.push(format!("version {} -> {}", self.version, other.version));
+        }
+        if self.digest != other.digest {
+            changes.push(format!("digest {} -> {}", self.digest, other.digest));
+        }
+        if self.size != other.size {
+            changes.push(format!("size {} -> {}", self.size, other.size));
+        }
+        if changes.is_empty() {
+            None
+        } else {
+            Some(changes.join(", "))
+        }
+    }
+}
+
+impl WorkspaceLock {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let lock: WorkspaceLock =
+            toml::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))?;
+        if lock.version != LOCKFILE_VERSION {
+            bail!(
+                "{} has unsupported lockfile version {} (expected {LOCKFILE_VERSION})",
+                path.display(),
+                lock.version
+            );
+        }
+        Ok(lock)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut sorted = self.clone();
+        sorted.components.sort_by(|a, b| a.name.cmp(&b.name));
+        let body = toml::to_string_pretty(&sorted).context("failed to render lockfile")?;
+        fs::write(path, format!("{LOCKFILE_HEADER}{body}"))
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn component(&self, name: &str) -> Option<&LockedComponent> {
+        self.components.iter().find(|entry| entry.name == name)
+    }
+
+    pub fn upsert(&mut self, entry: LockedComponent) {
+        match self
+            .components
+            .iter_mut()
+            .find(|existing| existing.name == entry.name)
+        {
+            Some(existing) => *existing = entry,
+            None => self.components.push(entry),
+        }
+    }
+
+    /// Remove the entry for `name`, returning whether one was present.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.components.len();
+        self.components.retain(|entry| entry.name != name);
+        self.components.len() != before
+    }
+}
+
+pub fn lockfile_path() -> Result<PathBuf> {
+    let root = std::env::current_dir().context("unable to determine current directory")?;
+    Ok(root.join(LOCKFILE_NAME))
+}
+
+/// Digest recorded in the lockfile for an artifact.
+pub fn artifact_digest(bytes: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(bytes))
+}
+
+/// Check a freshly resolved component against the lockfile.
+///
+/// With `locked`, any difference (including a missing entry) is an error. Otherwise only a digest
+/// change for the same coordinate and version is rejected, since that means the artifact behind an
+/// already-pinned version changed.
+pub fn verify(lock: &WorkspaceLock, resolved: &LockedComponent, locked: bool) -> Result<()> {
+    let Some(existing) = lock.component(&resolved.name) else {
+        if locked {
+            bail!(
+                "`{}` is not recorded in {LOCKFILE_NAME}; rerun without --locked to add it",
+                resolved.name
+            );
+        }
+        return Ok(());
+    };
+
+    if locked {
+        if existing.coordinate != resolved.coordinate {
+            bail!(
+                "`{}` is locked to coordinate `{}` but `{}` was requested (--locked)",
+                resolved.name,
+                existing.coordinate,
+                resolved.coordinate
+            );
+        }
+        if let Some(changes) = existing.diff(resolved) {
+            bail!(
+                "resolution of `{}` would change {LOCKFILE_NAME} ({changes}); rerun without --locked to update it",
+                resolved.name
+            );
+        }
+        return Ok(());
+    }
+
+    if existing.coordinate == resolved.coordinate
+        && existing.version == resolved.version
+        && existing.digest != resolved.digest
+    {
+        bail!(
+            "downloaded artifact for `{}@{}` does not match {LOCKFILE_NAME} (expected {}, got {})",
+            resolved.name,
+            resolved.version,
+            existing.digest,
+            resolved.digest
+        );
+    }
+    Ok(())
+}
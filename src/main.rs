@@ -1,41 +1,113 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
 use anyhow::Result;
-use clap::Parser;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
 
 use greentic_dev::cli::McpCommand;
-use greentic_dev::cli::{Cli, Command};
-use greentic_dev::passthrough::{resolve_binary, run_passthrough};
+use greentic_dev::cli::{
+    CacheCommand, Cli, Command, ComponentNativeCommand, DistributorCommand, PackNativeCommand,
+};
+use greentic_dev::hooks::Hooks;
+use greentic_dev::passthrough::{resolve_tool, run_with_hooks};
 
+use greentic_dev::cache::{self, GcBudget};
 use greentic_dev::cbor_cmd;
-use greentic_dev::cmd::config;
+use greentic_dev::cmd::{config, doctor, login, tools};
+use greentic_dev::component_add;
+use greentic_dev::component_update;
+use greentic_dev::diagnostics;
+use greentic_dev::distributor_server;
+use greentic_dev::events::{self, Event};
 use greentic_dev::mcp_cmd;
+use greentic_dev::oci::{self, OciArtifactKind};
+use greentic_dev::pack_init::PackInitIntent;
+use greentic_dev::pack_profile;
 use greentic_dev::secrets_cli::run_secrets_command;
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let command = command_name(&matches);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    events::set_format(cli.output);
+    if !events::json() {
+        // Errors without a more specific code render as `error[GD0001]: ...`.
+        if let Err(err) = run(cli) {
+            eprint!("{}", diagnostics::render(&err));
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    events::emit(Event::Started { command: &command });
+    let result = run(cli);
+    std::process::exit(events::finish(&command, &result));
+}
+
+/// Subcommand path reported in events, e.g. `config set` or `component remove`.
+fn command_name(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    // `pack` and `component` take raw arguments; name their native subcommands too.
+    let native = match names.as_slice() {
+        ["pack"] => PackNativeCommand::SUBCOMMANDS,
+        ["component"] => ComponentNativeCommand::SUBCOMMANDS,
+        _ => &[],
+    };
+    let first_arg = current
+        .try_get_raw("args")
+        .ok()
+        .flatten()
+        .and_then(|mut args| args.next())
+        .and_then(|arg| arg.to_str());
+    if let Some(subcommand) = first_arg.filter(|arg| native.contains(arg)) {
+        names.push(subcommand);
+    }
+    names.join(" ")
+}
 
+fn run(cli: Cli) -> Result<()> {
     match cli.command {
         Command::Flow(args) => {
-            let bin = resolve_binary("greentic-flow")?;
-            let status = run_passthrough(&bin, &args.args, false)?;
-            std::process::exit(status.code().unwrap_or(1));
+            passthrough("flow", &args.args, "greentic-flow", &args.args, cli.verbose)
         }
         Command::Pack(args) => {
             let subcommand = args.args.first().and_then(|s| s.to_str());
             if subcommand == Some("run") {
-                let bin = resolve_binary("greentic-runner-cli")?;
-                let run_args = &args.args[1..];
-                let status = run_passthrough(&bin, run_args, false)?;
-                std::process::exit(status.code().unwrap_or(1));
+                if let Some(pack) = pack_run_target(&args.args[1..]) {
+                    pack_profile::enforce_pack_limits(&pack)?;
+                }
+                return passthrough(
+                    "pack",
+                    &args.args,
+                    "greentic-runner-cli",
+                    &args.args[1..],
+                    cli.verbose,
+                );
+            }
+            if subcommand.is_some_and(|name| PackNativeCommand::SUBCOMMANDS.contains(&name)) {
+                return run_pack_native(PackNativeCommand::parse_from(&args.args));
             }
 
-            let bin = resolve_binary("greentic-pack")?;
-            let status = run_passthrough(&bin, &args.args, false)?;
-            std::process::exit(status.code().unwrap_or(1));
+            passthrough("pack", &args.args, "greentic-pack", &args.args, cli.verbose)
         }
         Command::Component(args) => {
-            let bin = resolve_binary("greentic-component")?;
-            let status = run_passthrough(&bin, &args.args, false)?;
-            std::process::exit(status.code().unwrap_or(1));
+            let subcommand = args.args.first().and_then(|s| s.to_str());
+            if subcommand.is_some_and(|name| ComponentNativeCommand::SUBCOMMANDS.contains(&name)) {
+                return run_component_native(ComponentNativeCommand::parse_from(&args.args));
+            }
+
+            passthrough(
+                "component",
+                &args.args,
+                "greentic-component",
+                &args.args,
+                cli.verbose,
+            )
         }
         Command::Config(config_cmd) => config::run(config_cmd),
         Command::Cbor(args) => cbor_cmd::run(args),
@@ -43,10 +115,104 @@ fn main() -> Result<()> {
             McpCommand::Doctor(args) => mcp_cmd::doctor(&args.provider, args.json),
         },
         Command::Gui(args) => {
-            let bin = resolve_binary("greentic-gui")?;
-            let status = run_passthrough(&bin, &args.args, false)?;
-            std::process::exit(status.code().unwrap_or(1));
+            passthrough("gui", &args.args, "greentic-gui", &args.args, cli.verbose)
+        }
+        Command::Secrets(secrets) => run_secrets_command(secrets, cli.verbose),
+        Command::Cache(cache_cmd) => match cache_cmd {
+            CacheCommand::Ls(args) => cache::run_ls(args.json),
+            CacheCommand::Gc(args) => cache::run_gc(
+                GcBudget {
+                    max_age: args.max_age,
+                    max_size: args.max_size,
+                },
+                args.dry_run,
+            ),
+            CacheCommand::Clear(args) => cache::run_clear(args.dry_run),
+        },
+        Command::Distributor(DistributorCommand::Serve(args)) => {
+            distributor_server::run_serve(&args.root, &args.listen, args.token)
+        }
+        Command::Login(args) => login::run_login(args.profile.as_deref()),
+        Command::Logout(args) => login::run_logout(args.profile.as_deref()),
+        Command::Tools(tools_cmd) => tools::run(tools_cmd),
+        Command::Explain(args) => diagnostics::run_explain(args.code.as_deref()),
+        Command::Doctor(args) => doctor::run(&args),
+    }
+}
+
+/// Delegate `greentic-dev <command> <args>` to `tool` with `tool_args`, running the configured
+/// hooks around it, and exit with its status.
+fn passthrough(
+    command: &str,
+    args: &[OsString],
+    tool: &str,
+    tool_args: &[OsString],
+    verbose: bool,
+) -> Result<()> {
+    let config = greentic_dev::config::load()?;
+    let bin = resolve_tool(tool, &config.tools)?;
+    let hooks = Hooks::for_command(&config.hooks, command, args);
+    let status = run_with_hooks(&bin, tool_args, &hooks, verbose)?;
+    let exit_code = status.code().unwrap_or(1);
+    events::emit(Event::Finished {
+        command,
+        status: if status.success() { "ok" } else { "error" },
+        exit_code,
+    });
+    std::process::exit(exit_code);
+}
+
+/// The `--pack <file>` (or `--pack=<file>`) argument of `pack run`, if given.
+fn pack_run_target(args: &[OsString]) -> Option<PathBuf> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let Some(arg) = arg.to_str() else {
+            continue;
+        };
+        if arg == "--pack" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--pack=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+fn run_pack_native(command: PackNativeCommand) -> Result<()> {
+    match command {
+        PackNativeCommand::Profile(args) => {
+            pack_profile::run(&args.flow, args.component_dir.as_deref(), args.json)
+        }
+        PackNativeCommand::Push(args) => {
+            oci::run_push(&args.artifact, &args.reference, OciArtifactKind::Pack)
+        }
+    }
+}
+
+fn run_component_native(command: ComponentNativeCommand) -> Result<()> {
+    match command {
+        ComponentNativeCommand::Add(args) => component_add::run_component_add(
+            &args.coordinate,
+            args.profile.as_deref(),
+            &args.headers,
+            &args.accept_licenses,
+            PackInitIntent::Dev,
+            args.locked,
+        )
+        .map(|_| ()),
+        ComponentNativeCommand::Outdated(args) => {
+            component_update::run_outdated(args.profile.as_deref(), &args.headers, args.json)
+        }
+        ComponentNativeCommand::Update(args) => component_update::run_update(
+            args.name.as_deref(),
+            args.profile.as_deref(),
+            &args.headers,
+            &args.accept_licenses,
+        ),
+        ComponentNativeCommand::Remove(args) => component_add::run_component_remove(&args.name),
+        ComponentNativeCommand::Push(args) => {
+            oci::run_push(&args.artifact, &args.reference, OciArtifactKind::Component)
         }
-        Command::Secrets(secrets) => run_secrets_command(secrets),
     }
 }
@@ -5,6 +5,8 @@ use anyhow::{Context, Result, bail};
 use greentic_mcp::{ToolMap, load_tool_map_config};
 use serde::Serialize;
 
+use crate::diagnostics;
+use crate::events::{self, Event};
 use crate::path_safety::normalize_under_root;
 
 pub fn doctor(target: &str, json: bool) -> Result<()> {
@@ -18,7 +20,14 @@ pub fn doctor(target: &str, json: bool) -> Result<()> {
     let map = ToolMap::from_config(&config).context("tool map contains duplicate tool names")?;
     let report = ToolMapReport::from_map(&config_path, &map);
 
-    if json {
+    if events::json() {
+        for warning in &report.warnings {
+            events::warning(diagnostics::MCP_TOOL_MAP_WARNING, warning);
+        }
+        events::emit(Event::Result {
+            data: serde_json::to_value(&report).context("failed to encode JSON report")?,
+        });
+    } else if json {
         println!(
             "{}",
             serde_json::to_string_pretty(&report).context("failed to encode JSON report")?
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use oci_distribution::annotations::ORG_OPENCONTAINERS_IMAGE_VERSION;
+use oci_distribution::client::{ClientConfig, ClientProtocol, Config, ImageLayer};
+use oci_distribution::secrets::RegistryAuth;
+use oci_distribution::{Client, Reference};
+use tokio::runtime::Runtime;
+
+use crate::config::{self, GreenticConfig, OciRegistryConfig};
+use crate::distributor::resolve_token;
+use crate::events::{self, Event};
+use crate::lockfile::artifact_digest;
+
+/// Scheme accepted on component and pack coordinates, e.g. `oci://ghcr.io/acme/echo:1.0.0`.
+pub const OCI_SCHEME: &str = "oci://";
+
+/// What an OCI artifact carries; selects the layer media types used for push and pull.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OciArtifactKind {
+    Component,
+    Pack,
+}
+
+impl OciArtifactKind {
+    /// Artifact layer media types, most preferred first. The first one is used on push.
+    fn artifact_media_types(self) -> &'static [&'static str] {
+        match self {
+            Self::Component => &[
+                "application/vnd.wasm.component.v1+wasm",
+                "application/vnd.module.wasm.content.layer.v1+wasm",
+                "application/wasm",
+                "application/octet-stream",
+            ],
+            Self::Pack => &[
+                "application/vnd.greentic.gtpack.v1+zip",
+                "application/vnd.greentic.gtpack+zip",
+                "application/vnd.greentic.pack+zip",
+                "application/octet-stream",
+            ],
+        }
+    }
+
+    /// Metadata layers other publishers attach next to the artifact; tolerated and ignored.
+    fn metadata_media_types(self) -> &'static [&'static str] {
+        match self {
+            Self::Component => &["application/vnd.greentic.component.manifest+json"],
+            Self::Pack => &["application/vnd.greentic.pack+json"],
+        }
+    }
+
+    fn config_media_type(self) -> &'static str {
+        match self {
+            Self::Component => "application/vnd.greentic.component.config.v1+json",
+            Self::Pack => "application/vnd.greentic.pack.config.v1+json",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Component => "component",
+            Self::Pack => "pack",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PulledArtifact {
+    pub bytes: Vec<u8>,
+    /// `sha256:<hex>` digest of the artifact layer, verified against the image manifest.
+    pub digest: String,
+    pub media_type: String,
+    /// Digest of the image manifest the layer was listed in.
+    pub manifest_digest: Option<String>,
+    /// `org.opencontainers.image.version` annotation of the image manifest, if set.
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PushedArtifact {
+    /// `sha256:<hex>` digest of the uploaded artifact layer.
+    pub digest: String,
+    pub manifest_url: String,
+}
+
+/// Blocking OCI registry client configured from `[oci.registries."<host>"]`.
+pub struct OciClient {
+    client: Client,
+    registries: HashMap<String, OciRegistryConfig>,
+    runtime: Runtime,
+}
+
+impl OciClient {
+    pub fn load() -> Result<Self> {
+        Self::from_config(&config::load()?)
+    }
+
+    pub fn from_config(config: &GreenticConfig) -> Result<Self> {
+        let insecure = config
+            .oci
+            .registries
+            .iter()
+            .filter(|(_, registry)| registry.insecure)
+            .map(|(host, _)| host.clone())
+            .collect();
+        let client = Client::new(ClientConfig {
+            protocol: ClientProtocol::HttpsExcept(insecure),
+            ..Default::default()
+        });
+        let runtime = Runtime::new().context("failed to start tokio runtime for OCI client")?;
+        Ok(Self {
+            client,
+            registries: config.oci.registries.clone(),
+            runtime,
+        })
+    }
+
+    /// Resolve `reference` to its image manifest and download the artifact layer.
+    pub fn pull(&self, reference: &str, kind: OciArtifactKind) -> Result<PulledArtifact> {
+        let image_ref = parse_reference(reference)?;
+        let auth = self.auth_for(&image_ref)?;
+        let accepted = kind
+            .artifact_media_types()
+            .iter()
+            .chain(kind.metadata_media_types())
+            .copied()
+            .collect();
+        let image = self
+            .runtime
+            .block_on(self.client.pull(&image_ref, &auth, accepted))
+            .with_context(|| format!("failed to pull {} from {reference}", kind.label()))?;
+
+        if let (Some(pinned), Some(actual)) = (image_ref.digest(), image.digest.as_deref())
+            && !pinned.eq_ignore_ascii_case(actual)
+        {
+            bail!("registry returned manifest {actual} for {reference}, expected {pinned}");
+        }
+        let manifest = image
+            .manifest
+            .as_ref()
+            .ok_or_else(|| anyhow!("registry returned no image manifest for {reference}"))?;
+        let descriptor = kind
+            .artifact_media_types()
+            .iter()
+            .find_map(|media_type| {
+                manifest
+                    .layers
+                    .iter()
+                    .find(|layer| layer.media_type == *media_type)
+            })
+            .ok_or_else(|| anyhow!("{reference} has no {} layer", kind.label()))?;
+        let version = manifest
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations.get(ORG_OPENCONTAINERS_IMAGE_VERSION))
+            .cloned();
+        let layer = image
+            .layers
+            .into_iter()
+            .find(|layer| layer.media_type == descriptor.media_type)
+            .ok_or_else(|| anyhow!("registry did not return the {} layer", kind.label()))?;
+
+        let digest = artifact_digest(&layer.data);
+        if !descriptor.digest.eq_ignore_ascii_case(&digest) {
+            bail!(
+                "layer digest mismatch for {reference}: manifest lists {}, downloaded {digest}",
+                descriptor.digest
+            );
+        }
+        Ok(PulledArtifact {
+            bytes: layer.data,
+            digest,
+            media_type: layer.media_type,
+            manifest_digest: image.digest,
+            version,
+        })
+    }
+
+    /// Upload `bytes` as a single-layer artifact and tag it as `reference`.
+    pub fn push(
+        &self,
+        reference: &str,
+        kind: OciArtifactKind,
+        bytes: &[u8],
+    ) -> Result<PushedArtifact> {
+        let image_ref = parse_reference(reference)?;
+        let auth = self.auth_for(&image_ref)?;
+        let layer = ImageLayer::new(
+            bytes.to_vec(),
+            kind.artifact_media_types()[0].to_string(),
+            None,
+        );
+        let config = Config::new(b"{}".to_vec(), kind.config_media_type().to_string(), None);
+        let response = self
+            .runtime
+            .block_on(self.client.push(&image_ref, &[layer], config, &auth, None))
+            .with_context(|| format!("failed to push {} to {reference}", kind.label()))?;
+        Ok(PushedArtifact {
+            digest: artifact_digest(bytes),
+            manifest_url: response.manifest_url,
+        })
+    }
+
+    /// Credentials from the host's `[oci.registries]` entry. Distributor tokens are never sent to
+    /// registries; see docs/distributor.md.
+    fn auth_for(&self, reference: &Reference) -> Result<RegistryAuth> {
+        let Some(registry) = self.registries.get(reference.resolve_registry()) else {
+            return Ok(RegistryAuth::Anonymous);
+        };
+        let password = resolve_token(registry.password.clone())?;
+        if registry.username.is_none() && password.is_none() {
+            return Ok(RegistryAuth::Anonymous);
+        }
+        Ok(RegistryAuth::Basic(
+            registry.username.clone().unwrap_or_default(),
+            password.unwrap_or_default(),
+        ))
+    }
+}
+
+/// Publish a built component (`.wasm`) or pack (`.gtpack`) to an OCI registry.
+pub fn run_push(artifact: &Path, reference: &str, kind: OciArtifactKind) -> Result<()> {
+    let bytes =
+        fs::read(artifact).with_context(|| format!("failed to read {}", artifact.display()))?;
+    let (magic, expected) = match kind {
+        OciArtifactKind::Component => (&b"\0asm"[..], "a wasm component"),
+        OciArtifactKind::Pack => (&b"PK"[..], "a .gtpack archive"),
+    };
+    if !bytes.starts_with(magic) {
+        bail!("{} is not {expected}", artifact.display());
+    }
+
+    let pushed = OciClient::load()?.push(reference, kind, &bytes)?;
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::json!({
+                "reference": reference,
+                "digest": pushed.digest,
+                "manifest_url": pushed.manifest_url,
+            }),
+        });
+        return Ok(());
+    }
+    println!("Pushed {} to {reference}", artifact.display());
+    println!("Layer digest {}", pushed.digest);
+    println!("Manifest at {}", pushed.manifest_url);
+    Ok(())
+}
+
+/// Parse `oci://registry/repo:tag` (or `registry/repo@sha256:...`); the scheme is optional.
+pub fn parse_reference(raw: &str) -> Result<Reference> {
+    let trimmed = raw.strip_prefix(OCI_SCHEME).unwrap_or(raw);
+    Reference::try_from(trimmed).with_context(|| format!("invalid OCI reference `{raw}`"))
+}
+
+/// Artifact name (last repository segment) and tag for an OCI reference.
+pub fn name_and_tag(raw: &str) -> Result<(String, Option<String>)> {
+    let reference = parse_reference(raw)?;
+    let name = reference
+        .repository()
+        .rsplit('/')
+        .next()
+        .unwrap_or(reference.repository())
+        .to_string();
+    // References without an explicit tag parse as `latest`; only a digest pin leaves it unset.
+    let tag = reference.tag().map(str::to_string);
+    Ok((name, tag))
+}
@@ -8,9 +8,9 @@ use anyhow::{Context, Result, anyhow, bail};
 use greentic_flow::flow_bundle::{blake3_hex, canonicalize_json, load_and_validate_bundle};
 use greentic_pack::PackKind;
 use greentic_pack::builder::{
-    ComponentArtifact, ComponentDescriptor, ComponentPin as PackComponentPin, DistributionSection,
-    FlowBundle as PackFlowBundle, ImportRef, NodeRef as PackNodeRef, PACK_VERSION, PackBuilder,
-    PackMeta, Provenance, Signing,
+    BuildResult, ComponentArtifact, ComponentDescriptor, ComponentPin as PackComponentPin,
+    DistributionSection, FlowBundle as PackFlowBundle, ImportRef, NodeRef as PackNodeRef,
+    PACK_VERSION, PackBuilder, PackMeta, Provenance, Signing,
 };
 use greentic_pack::events::EventsSection;
 use greentic_pack::messaging::MessagingSection;
@@ -22,9 +22,14 @@ use serde_json::{Value as JsonValue, json};
 use time::OffsetDateTime;
 use time::format_description::well_known::Rfc3339;
 
+use crate::artifact_store::ArtifactStore;
 use crate::component_resolver::{
     ComponentResolver, NodeSchemaError, ResolvedComponent, ResolvedNode,
 };
+use crate::diagnostics::{self, Diagnostic};
+use crate::events;
+use crate::license;
+use crate::pack_init::WorkspaceManifest;
 use crate::path_safety::normalize_under_root;
 
 #[derive(Debug, Clone, Copy)]
@@ -61,14 +66,23 @@ pub fn run(
         .map(|dir| normalize_under_root(&workspace_root, dir))
         .transpose()?;
 
-    build_once(
+    let built = build_once(
         &safe_flow,
         output_path,
         signing,
         safe_meta.as_deref(),
         safe_component_dir.as_deref(),
     )?;
+    events::artifact("pack", &built.out_path, Some(&built.manifest_hash_blake3));
+    if !events::json() {
+        println!(
+            "✓ Pack built at {} (manifest hash {})",
+            built.out_path.display(),
+            built.manifest_hash_blake3
+        );
+    }
     if strict_mode_enabled() {
+        events::stage_started("determinism_check");
         verify_determinism(
             &safe_flow,
             output_path,
@@ -76,6 +90,7 @@ pub fn run(
             safe_meta.as_deref(),
             safe_component_dir.as_deref(),
         )?;
+        events::stage_finished("determinism_check");
     }
     Ok(())
 }
@@ -86,7 +101,8 @@ fn build_once(
     signing: PackSigning,
     meta_path: Option<&Path>,
     component_dir: Option<&Path>,
-) -> Result<()> {
+) -> Result<BuildResult> {
+    events::stage_started("validate_flow");
     let flow_source = fs::read_to_string(flow_path)
         .with_context(|| format!("failed to read {}", flow_path.display()))?;
     let mut flow_doc_json: JsonValue =
@@ -98,7 +114,9 @@ fn build_once(
         })?;
     let bundle = load_and_validate_bundle(&flow_source, Some(flow_path))
         .with_context(|| format!("flow validation failed for {}", flow_path.display()))?;
+    events::stage_finished("validate_flow");
 
+    events::stage_started("resolve_components");
     let mut resolver = ComponentResolver::new(component_dir.map(PathBuf::from));
     let mut resolved_nodes = Vec::new();
     let mut schema_errors = Vec::new();
@@ -128,6 +146,7 @@ fn build_once(
     ensure_node_operations(&mut flow_doc_json, &resolved_nodes)?;
 
     write_resolved_configs(&resolved_nodes)?;
+    events::stage_finished("resolve_components");
 
     let meta = load_pack_meta(meta_path, &bundle)?;
     let mut builder = PackBuilder::new(meta)
@@ -135,7 +154,9 @@ fn build_once(
         .with_signing(signing.into())
         .with_provenance(build_provenance());
 
-    for artifact in collect_component_artifacts(&resolved_nodes) {
+    let artifacts = collect_component_artifacts(&resolved_nodes);
+    warn_unaccepted_licenses(&artifacts)?;
+    for artifact in artifacts {
         builder = builder.with_component(artifact);
     }
 
@@ -146,16 +167,13 @@ fn build_once(
             .with_context(|| format!("failed to create {}", parent.display()))?;
     }
 
+    events::stage_started("build");
     let build_result = builder
         .build(output_path)
         .context("pack build failed (sign/build stage)")?;
-    println!(
-        "✓ Pack built at {} (manifest hash {})",
-        build_result.out_path.display(),
-        build_result.manifest_hash_blake3
-    );
+    events::stage_finished("build");
 
-    Ok(())
+    Ok(build_result)
 }
 
 fn strict_mode_enabled() -> bool {
@@ -188,7 +206,9 @@ fn verify_determinism(
     if expected != actual {
         bail!("LOCAL_CHECK_STRICT detected non-deterministic pack output");
     }
-    println!("LOCAL_CHECK_STRICT verified deterministic pack output");
+    if !events::json() {
+        println!("LOCAL_CHECK_STRICT verified deterministic pack output");
+    }
     Ok(())
 }
 
@@ -271,7 +291,7 @@ fn ensure_node_operations(flow_doc_json: &mut JsonValue, nodes: &[ResolvedNode])
     Ok(())
 }
 
-fn default_operation(component: &ResolvedComponent) -> Result<Option<String>> {
+pub(crate) fn default_operation(component: &ResolvedComponent) -> Result<Option<String>> {
     let manifest_json = component.manifest_json.as_deref().unwrap_or_default();
     let manifest: JsonValue =
         serde_json::from_str(manifest_json).context("invalid manifest JSON")?;
@@ -312,14 +332,63 @@ fn collect_component_artifacts(nodes: &[ResolvedNode]) -> Vec<ComponentArtifact>
     map.into_values().collect()
 }
 
-fn is_builtin_component(name: &str) -> bool {
+/// Warn about bundled commercial or trial components without a recorded license acceptance.
+fn warn_unaccepted_licenses(artifacts: &[ComponentArtifact]) -> Result<()> {
+    let manifest_path = env::current_dir()
+        .context("unable to determine current directory")?
+        .join(".greentic/manifest.json");
+    let manifest = WorkspaceManifest::load(&manifest_path)?;
+    let store = ArtifactStore::open()?;
+    for warning in unaccepted_licenses(artifacts, &manifest, &store)? {
+        events::warning(diagnostics::LICENSE_NOT_ACCEPTED, &warning);
+    }
+    Ok(())
+}
+
+/// The license comes from the workspace manifest record, or from the shared store for components
+/// that were copied into the workspace without `component add`.
+fn unaccepted_licenses(
+    artifacts: &[ComponentArtifact],
+    manifest: &WorkspaceManifest,
+    store: &ArtifactStore,
+) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+    for artifact in artifacts {
+        let recorded = manifest.components.iter().find(|component| {
+            artifact.hash_blake3.as_deref() == Some(component.entry.hash_blake3.as_str())
+                || (component.entry.name == artifact.name
+                    && component.entry.version == artifact.version)
+        });
+        let license = match recorded.and_then(|component| component.license.clone()) {
+            Some(license) => Some(license),
+            None => license::stored_license(store, &artifact.wasm_path)?,
+        };
+        let Some(license) = license else {
+            continue;
+        };
+        if !license.needs_acceptance() || license.is_accepted() {
+            continue;
+        }
+        let coordinate = recorded
+            .map(|component| component.coordinate.as_str())
+            .unwrap_or("<coordinate>");
+        warnings.push(format!(
+            "pack bundles {}@{} under {} license `{}` without a recorded acceptance; run `greentic-dev component add {} --accept-license {}`",
+            artifact.name, artifact.version, license.license_type, license.id, coordinate, license.id
+        ));
+    }
+    warnings.sort();
+    Ok(warnings)
+}
+
+pub(crate) fn is_builtin_component(name: &str) -> bool {
     name == "component.exec"
         || name == "flow.call"
         || name == "session.wait"
         || name.starts_with("emit")
 }
 
-fn resolve_component_exec_node(
+pub(crate) fn resolve_component_exec_node(
     resolver: &mut ComponentResolver,
     node: &greentic_flow::flow_bundle::NodeRef,
     flow_doc_json: &JsonValue,
@@ -389,7 +458,13 @@ fn report_schema_errors(errors: &[NodeSchemaError]) -> Result<()> {
             err.node_id, err.component, err.pointer, err.message
         ));
     }
-    bail!("component schema validation failed:\n{message}");
+    bail!(
+        Diagnostic::new(
+            diagnostics::COMPONENT_SCHEMA_INVALID,
+            format!("component schema validation failed:\n{message}"),
+        )
+        .with_help("fix the listed node config fields to match each component's config schema")
+    );
 }
 
 fn load_pack_meta(
@@ -547,3 +622,87 @@ struct ImportToml {
     pack_id: String,
     version_req: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::license::ComponentLicense;
+
+    fn artifact(wasm_path: PathBuf) -> ComponentArtifact {
+        ComponentArtifact {
+            name: "trial".to_string(),
+            version: Version::new(1, 0, 0),
+            wasm_path,
+            schema_json: None,
+            manifest_json: None,
+            capabilities: None,
+            world: None,
+            hash_blake3: Some("0".repeat(64)),
+        }
+    }
+
+    fn trial_license(accepted_at: Option<&str>) -> ComponentLicense {
+        ComponentLicense {
+            license_type: "trial".to_string(),
+            id: "acme-trial-30d".to_string(),
+            accepted_at: accepted_at.map(str::to_string),
+        }
+    }
+
+    fn manifest_with(license: ComponentLicense) -> WorkspaceManifest {
+        serde_json::from_value(json!({
+            "components": [{
+                "coordinate": "component://acme/trial@1",
+                "entry": {
+                    "name": "trial",
+                    "version": "1.0.0",
+                    "file_wasm": ".greentic/artifacts/trial.wasm",
+                    "hash_blake3": "0".repeat(64),
+                    "schema_file": null,
+                    "manifest_file": null,
+                    "world": null,
+                    "capabilities": null
+                },
+                "license": license
+            }]
+        }))
+        .expect("manifest")
+    }
+
+    #[test]
+    fn warns_about_licensed_components_without_acceptance() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let store = ArtifactStore::at(temp.path().join("store"));
+        let stored = store
+            .insert(None, "artifact.wasm", b"\0asm-trial")
+            .expect("insert");
+        license::record_in_store(&stored, &trial_license(Some("2026-10-18T09:30:00Z")))
+            .expect("record license");
+        // Copied into the workspace by hand, so the manifest has no record of it.
+        let wasm_path = temp.path().join("trial.wasm");
+        fs::write(&wasm_path, b"\0asm-trial").expect("write wasm");
+        let artifacts = [artifact(wasm_path)];
+
+        let warnings =
+            unaccepted_licenses(&artifacts, &WorkspaceManifest::default(), &store).unwrap();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(
+            warnings[0].contains("trial license `acme-trial-30d`")
+                && warnings[0].contains("--accept-license acme-trial-30d"),
+            "{}",
+            warnings[0]
+        );
+
+        let unaccepted = manifest_with(trial_license(None));
+        let warnings = unaccepted_licenses(&artifacts, &unaccepted, &store).unwrap();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("component add component://acme/trial@1"));
+
+        let accepted = manifest_with(trial_license(Some("2026-10-18T09:30:00Z")));
+        assert!(
+            unaccepted_licenses(&artifacts, &accepted, &store)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -2,18 +2,22 @@ use std::fs;
 use std::io::{Cursor, Read, Write};
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Context, Result, bail};
 use bytes::Bytes;
 use greentic_pack::builder::ComponentEntry;
-use semver::Version;
 use serde::{Deserialize, Serialize};
 use zip::ZipArchive;
 
-use crate::config;
+use crate::artifact_store::{ArtifactStore, link_into};
+use crate::component_add;
 use crate::distributor::{
-    DevArtifactKind, DevDistributorClient, DevDistributorError, DevIntent, DevResolveRequest,
-    DevResolveResponse, resolve_profile,
+    self, DevArtifactKind, DevDistributorError, DevIntent, DevResolveRequest, DevResolveResponse,
 };
+use crate::events;
+use crate::license::ComponentLicense;
+use crate::oci::{self, OCI_SCHEME, OciArtifactKind, OciClient};
+
+const PACK_FILE_NAME: &str = "bundle.gtpack";
 
 #[derive(Debug, Clone, Copy)]
 pub enum PackInitIntent {
@@ -22,29 +26,53 @@ pub enum PackInitIntent {
 }
 
 pub fn run(from: &str, profile: Option<&str>) -> Result<()> {
-    let config = config::load_with_meta(None)?;
-    let profile = resolve_profile(&config, profile)?;
-    let client = DevDistributorClient::from_profile(profile.clone())?;
+    events::stage_started("fetch");
+    let store = ArtifactStore::open()?;
+    let (name, version, stored) = if from.starts_with(OCI_SCHEME) {
+        if distributor::offline() {
+            bail!(
+                "offline mode enabled (GREENTIC_DEV_OFFLINE=1); cannot pull `{from}` from an OCI registry"
+            );
+        }
+        let (name, tag) = oci::name_and_tag(from)?;
+        let pulled = OciClient::load()?.pull(from, OciArtifactKind::Pack)?;
+        let stored = store.insert(Some(&pulled.digest), PACK_FILE_NAME, &pulled.bytes)?;
+        (name, tag.unwrap_or(pulled.digest), stored)
+    } else {
+        let distributor =
+            distributor::open(profile, &[]).with_context(|| format!("cannot resolve `{from}`"))?;
+        let resolve = distributor.resolve(&DevResolveRequest {
+            coordinate: from.to_string(),
+            intent: DevIntent::Dev,
+            platform: Some(default_platform()),
+            features: Vec::new(),
+        });
 
-    let resolve = client.resolve(&DevResolveRequest {
-        coordinate: from.to_string(),
-        intent: DevIntent::Dev,
-        platform: Some(default_platform()),
-        features: Vec::new(),
-    });
+        let resolved = handle_resolve_result(resolve)?;
+        if resolved.kind != DevArtifactKind::Pack {
+            bail!(
+                "coordinate `{}` resolved to {:?}, expected pack",
+                resolved.coordinate,
+                resolved.kind
+            );
+        }
+        let stored = distributor.fetch(&resolved, &store, PACK_FILE_NAME)?;
+        let version = resolved
+            .version
+            .or(resolved.digest)
+            .unwrap_or_else(|| "unversioned".to_string());
+        (resolved.name, version, stored)
+    };
 
-    let resolved = handle_resolve_result(resolve)?;
-    if resolved.kind != DevArtifactKind::Pack {
-        bail!(
-            "coordinate `{}` resolved to {:?}, expected pack",
-            resolved.coordinate,
-            resolved.kind
-        );
-    }
+    events::stage_finished("fetch");
+    events::artifact("pack", &stored.path, Some(&stored.digest));
 
-    let bytes = client.download_artifact(&resolved.artifact_download_path)?;
-    let cache_path = write_pack_to_cache(&resolved, &bytes)?;
-    let workspace_dir = slug_to_dir(&resolved.name)?;
+    events::stage_started("unpack");
+    let bytes = Bytes::from(
+        fs::read(&stored.path)
+            .with_context(|| format!("failed to read {}", stored.path.display()))?,
+    );
+    let workspace_dir = slug_to_dir(&name)?;
     fs::create_dir(&workspace_dir).with_context(|| {
         format!(
             "failed to create workspace directory {}",
@@ -52,127 +80,43 @@ pub fn run(from: &str, profile: Option<&str>) -> Result<()> {
         )
     })?;
 
-    let bundle_path = workspace_dir.join("bundle.gtpack");
-    fs::write(&bundle_path, &bytes)
-        .with_context(|| format!("failed to write {}", bundle_path.display()))?;
-    unpack_gtpack(&workspace_dir, bytes.clone())?;
-
-    println!(
-        "Initialized pack {}@{} in {} (cached at {})",
-        resolved.name,
-        resolved.version,
-        workspace_dir.display(),
-        cache_path.display()
-    );
+    link_into(&stored, &workspace_dir.join(PACK_FILE_NAME))?;
+    unpack_gtpack(&workspace_dir, bytes)?;
+    events::stage_finished("unpack");
+    events::artifact("workspace", &workspace_dir, None);
+
+    if !events::json() {
+        println!(
+            "Initialized pack {}@{} in {} (cached at {})",
+            name,
+            version,
+            workspace_dir.display(),
+            stored.path.display()
+        );
+    }
 
     Ok(())
 }
 
-/// Fetch a component via distributor and cache it locally.
+/// Fetch a component via the distributor and cache it locally. Same as `component add` without
+/// header overrides, `--accept-license` or `--locked`.
 /// Returns the cache directory containing the component artifact/flows.
 pub fn run_component_add(
     coordinate: &str,
     profile: Option<&str>,
     intent: PackInitIntent,
 ) -> Result<PathBuf> {
-    let config = config::load_with_meta(None)?;
-    let profile = resolve_profile(&config, profile)?;
-    let client = DevDistributorClient::from_profile(profile.clone())?;
-
-    let resolve = client.resolve(&DevResolveRequest {
-        coordinate: coordinate.to_string(),
-        intent: match intent {
-            PackInitIntent::Dev => DevIntent::Dev,
-            PackInitIntent::Runtime => DevIntent::Runtime,
-        },
-        platform: Some(default_platform()),
-        features: Vec::new(),
-    });
-    let resolved = handle_resolve_result(resolve)?;
-    if resolved.kind != DevArtifactKind::Component {
-        bail!(
-            "coordinate `{}` resolved to {:?}, expected component",
-            resolved.coordinate,
-            resolved.kind
-        );
-    }
-
-    let bytes = client.download_artifact(&resolved.artifact_download_path)?;
-    let (cache_dir, cache_path) = write_component_to_cache(&resolved, &bytes)?;
-    update_workspace_manifest(&resolved, &cache_path)?;
-
-    println!(
-        "Resolved {} -> {}@{}",
-        resolved.coordinate, resolved.name, resolved.version
-    );
-    println!("Cached component at {}", cache_path.display());
-    println!(
-        "Updated workspace manifest at {}",
-        manifest_path()?.display()
-    );
-    Ok(cache_dir)
+    component_add::run_component_add(coordinate, profile, &[], &[], intent, false)
 }
 
-fn default_platform() -> String {
+pub(crate) fn default_platform() -> String {
     "wasm32-wasip2".to_string()
 }
 
-fn handle_resolve_result(
+pub(crate) fn handle_resolve_result(
     result: Result<DevResolveResponse, DevDistributorError>,
 ) -> Result<DevResolveResponse> {
-    match result {
-        Ok(resp) => Ok(resp),
-        Err(DevDistributorError::LicenseRequired(body)) => bail!(
-            "license required for {}: {}\nCheckout URL: {}",
-            body.coordinate,
-            body.message,
-            body.checkout_url
-        ),
-        Err(other) => Err(anyhow!(other)),
-    }
-}
-
-fn cache_base_dir() -> Result<PathBuf> {
-    let mut base = dirs::home_dir().ok_or_else(|| anyhow!("unable to determine home directory"))?;
-    base.push(".greentic");
-    base.push("cache");
-    fs::create_dir_all(&base)
-        .with_context(|| format!("failed to create cache directory {}", base.display()))?;
-    Ok(base)
-}
-
-fn write_component_to_cache(
-    resolved: &DevResolveResponse,
-    bytes: &Bytes,
-) -> Result<(PathBuf, PathBuf)> {
-    let mut path = cache_base_dir()?;
-    path.push("components");
-    let slug = cache_slug(resolved);
-    path.push(slug);
-    fs::create_dir_all(&path).with_context(|| format!("failed to create {}", path.display()))?;
-    let file_path = path.join("artifact.wasm");
-    fs::write(&file_path, bytes)
-        .with_context(|| format!("failed to write {}", file_path.display()))?;
-    Ok((path, file_path))
-}
-
-fn write_pack_to_cache(resolved: &DevResolveResponse, bytes: &Bytes) -> Result<PathBuf> {
-    let mut path = cache_base_dir()?;
-    path.push("packs");
-    let slug = cache_slug(resolved);
-    path.push(slug);
-    fs::create_dir_all(&path).with_context(|| format!("failed to create {}", path.display()))?;
-    let file_path = path.join("bundle.gtpack");
-    fs::write(&file_path, bytes)
-        .with_context(|| format!("failed to write {}", file_path.display()))?;
-    Ok(file_path)
-}
-
-pub fn cache_slug(resolved: &DevResolveResponse) -> String {
-    if let Some(digest) = &resolved.digest {
-        return digest.replace(':', "-");
-    }
-    slugify(&format!("{}-{}", resolved.name, resolved.version))
+    result.map_err(|err| err.diagnostic().into())
 }
 
 pub fn slugify(raw: &str) -> String {
@@ -203,57 +147,36 @@ pub struct WorkspaceManifest {
     pub components: Vec<WorkspaceComponent>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WorkspaceComponent {
-    pub coordinate: String,
-    pub entry: ComponentEntry,
-}
-
-pub fn update_workspace_manifest(resolved: &DevResolveResponse, cache_path: &Path) -> Result<()> {
-    let manifest_path = manifest_path()?;
-    let mut manifest: WorkspaceManifest = if manifest_path.exists() {
-        let data = fs::read_to_string(&manifest_path)
-            .with_context(|| format!("failed to read {}", manifest_path.display()))?;
-        serde_json::from_str(&data)
-            .with_context(|| format!("failed to parse {}", manifest_path.display()))?
-    } else {
-        WorkspaceManifest::default()
-    };
-
-    let version = Version::parse(&resolved.version)
-        .with_context(|| format!("invalid semver version `{}`", resolved.version))?;
-    let entry = ComponentEntry {
-        name: resolved.name.clone(),
-        version,
-        file_wasm: cache_path.display().to_string(),
-        hash_blake3: resolved.digest.clone().unwrap_or_default(),
-        schema_file: None,
-        manifest_file: None,
-        world: None,
-        capabilities: None,
-    };
-
-    let mut replaced = false;
-    for existing in manifest.components.iter_mut() {
-        if existing.entry.name == entry.name {
-            existing.coordinate = resolved.coordinate.clone();
-            existing.entry = entry.clone();
-            replaced = true;
-            break;
+impl WorkspaceManifest {
+    /// Load the manifest at `path`, returning an empty manifest when the file does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
         }
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&data).with_context(|| format!("failed to parse {}", path.display()))
     }
-    if !replaced {
-        manifest.components.push(WorkspaceComponent {
-            coordinate: resolved.coordinate.clone(),
-            entry,
-        });
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let rendered =
+            serde_json::to_string_pretty(self).context("failed to render workspace manifest")?;
+        fs::write(path, rendered).with_context(|| format!("failed to write {}", path.display()))
     }
 
-    let rendered =
-        serde_json::to_string_pretty(&manifest).context("failed to render workspace manifest")?;
-    fs::write(&manifest_path, rendered)
-        .with_context(|| format!("failed to write {}", manifest_path.display()))?;
-    Ok(())
+    pub fn component(&self, name: &str) -> Option<&WorkspaceComponent> {
+        self.components
+            .iter()
+            .find(|component| component.entry.name == name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceComponent {
+    pub coordinate: String,
+    pub entry: ComponentEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<ComponentLicense>,
 }
 
 fn slug_to_dir(name: &str) -> Result<PathBuf> {
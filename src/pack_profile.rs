@@ -0,0 +1,481 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use greentic_component::limits::Limits;
+use greentic_component::test_harness::{HarnessConfig, HarnessError, TestHarness};
+use greentic_flow::flow_bundle::load_and_validate_bundle;
+use greentic_pack::reader::{SigningPolicy, open_pack};
+use greentic_types::{EnvId, TenantCtx, TenantId};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::component_resolver::{ComponentResolver, ResolvedNode};
+use crate::diagnostics::{self, Diagnostic};
+use crate::events::{self, Event};
+use crate::pack_build::{default_operation, is_builtin_component, resolve_component_exec_node};
+use crate::path_safety::normalize_under_root;
+
+/// Utilisation (in percent of the declared limit) above which a node is flagged.
+pub const UTILISATION_WARN_PERCENT: f64 = 80.0;
+
+/// Budgets applied to components whose manifest does not declare `limits`.
+const DEFAULT_WALL_TIME_MS: u64 = 30_000;
+const DEFAULT_MEMORY_MB: u32 = 256;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileReport {
+    pub flow: String,
+    pub nodes: Vec<NodeProfile>,
+    pub notes: Vec<String>,
+}
+
+impl ProfileReport {
+    pub fn has_failures(&self) -> bool {
+        self.nodes
+            .iter()
+            .any(|node| node.status != NodeRunStatus::Ok)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeProfile {
+    pub node_id: String,
+    pub component: String,
+    pub operation: String,
+    pub status: NodeRunStatus,
+    /// True when the manifest declared limits; false when defaults were applied.
+    pub limits_declared: bool,
+    pub wall_time_ms: Measurement,
+    pub memory_mb: Measurement,
+    pub fuel: Measurement,
+    pub files: Measurement,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "message")]
+pub enum NodeRunStatus {
+    Ok,
+    WallTimeExceeded,
+    MemoryExceeded,
+    Failed(String),
+}
+
+/// A measured value against its limit. `used` is `None` when the local harness cannot meter it.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Measurement {
+    pub used: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+impl Measurement {
+    pub fn utilisation_percent(&self) -> Option<f64> {
+        match (self.used, self.limit) {
+            (Some(used), Some(limit)) if limit > 0 => Some(used as f64 * 100.0 / limit as f64),
+            _ => None,
+        }
+    }
+
+    fn render(&self, unit: &str) -> String {
+        let used = self
+            .used
+            .map(|value| format!("{value}{unit}"))
+            .unwrap_or_else(|| "n/a".to_string());
+        let limit = self
+            .limit
+            .map(|value| format!("{value}{unit}"))
+            .unwrap_or_else(|| "unlimited".to_string());
+        match self.utilisation_percent() {
+            Some(pct) => format!("{used} / {limit} ({pct:.0}%)"),
+            None => format!("{used} / {limit}"),
+        }
+    }
+}
+
+/// Run every component node of `flow_path` once in the local harness with its manifest limits
+/// enforced, and print a per-node utilisation report.
+pub fn run(flow_path: &Path, component_dir: Option<&Path>, emit_json: bool) -> Result<()> {
+    let workspace_root = env::current_dir()
+        .context("failed to resolve workspace root")?
+        .canonicalize()
+        .context("failed to canonicalize workspace root")?;
+    let safe_flow = normalize_under_root(&workspace_root, flow_path)?;
+    let safe_component_dir = component_dir
+        .map(|dir| normalize_under_root(&workspace_root, dir))
+        .transpose()?;
+
+    let report = profile_flow(&safe_flow, safe_component_dir.as_deref())?;
+    if events::json() {
+        events::emit(Event::Result {
+            data: serde_json::to_value(&report).context("failed to encode profile report")?,
+        });
+    } else if emit_json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).context("failed to encode profile report")?
+        );
+    } else {
+        print_report(&report);
+    }
+
+    if report.has_failures() {
+        bail!("one or more nodes failed or exceeded their declared limits");
+    }
+    Ok(())
+}
+
+/// `pack run` pre-flight: run every component node of the pack's flows once in the harness with
+/// their manifest limits enforced, and refuse to start the runner when a node exceeds them. Nodes
+/// that fail for other reasons are left for the runner to report.
+pub fn enforce_pack_limits(pack_path: &Path) -> Result<()> {
+    let reports = match profile_pack(pack_path) {
+        Ok(reports) => reports,
+        Err(err) => {
+            events::warning(
+                diagnostics::PACK_LIMITS_UNCHECKED,
+                &format!("component limits not checked: {err:#}"),
+            );
+            return Ok(());
+        }
+    };
+    let exceeded = reports
+        .iter()
+        .flat_map(|report| {
+            report.nodes.iter().filter_map(move |node| {
+                let limit = match node.status {
+                    NodeRunStatus::WallTimeExceeded => "wall time",
+                    NodeRunStatus::MemoryExceeded => "memory",
+                    _ => return None,
+                };
+                Some(format!(
+                    "- {}/{} ({}): {limit} limit exceeded",
+                    report.flow, node.node_id, node.component
+                ))
+            })
+        })
+        .collect::<Vec<_>>();
+    if !exceeded.is_empty() {
+        bail!(
+            Diagnostic::new(
+                diagnostics::COMPONENT_LIMIT_EXCEEDED,
+                format!(
+                    "components exceeded their declared limits:\n{}",
+                    exceeded.join("\n")
+                ),
+            )
+            .with_help("raise the component's `limits` in its manifest, or fix the component")
+        );
+    }
+    Ok(())
+}
+
+/// Profile every flow bundled in a `.gtpack`, with the components and manifests it ships.
+pub fn profile_pack(pack_path: &Path) -> Result<Vec<ProfileReport>> {
+    let load = open_pack(pack_path, SigningPolicy::DevOk)
+        .map_err(|err| anyhow!("failed to open {}: {}", pack_path.display(), err.message))?;
+    let scratch = tempfile::tempdir().context("failed to create profiling directory")?;
+    let file = |name: &str| {
+        load.files
+            .get(name)
+            .ok_or_else(|| anyhow!("{} is missing `{name}`", pack_path.display()))
+    };
+
+    for component in &load.manifest.components {
+        let Some(manifest_file) = &component.manifest_file else {
+            continue;
+        };
+        let manifest_bytes = file(manifest_file)?;
+        let manifest: JsonValue = serde_json::from_slice(manifest_bytes)
+            .with_context(|| format!("invalid manifest for component `{}`", component.name))?;
+        let wasm_name = manifest
+            .pointer("/artifacts/component_wasm")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| {
+                anyhow!(
+                    "manifest for component `{}` has no artifacts.component_wasm",
+                    component.name
+                )
+            })?;
+        let dir = scratch.path().join(plain_segment(&component.name)?);
+        let wasm_path = dir.join(relative_path(wasm_name)?);
+        fs::create_dir_all(wasm_path.parent().unwrap_or(&dir))
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        fs::write(dir.join("component.manifest.json"), manifest_bytes)
+            .with_context(|| format!("failed to write manifest of `{}`", component.name))?;
+        fs::write(&wasm_path, file(&component.file_wasm)?)
+            .with_context(|| format!("failed to write {}", wasm_path.display()))?;
+    }
+
+    let flows_dir = scratch.path().join(".flows");
+    fs::create_dir_all(&flows_dir)
+        .with_context(|| format!("failed to create {}", flows_dir.display()))?;
+    let mut reports = Vec::with_capacity(load.manifest.flows.len());
+    for (index, flow) in load.manifest.flows.iter().enumerate() {
+        let flow_path = flows_dir.join(format!("{index}.ygtc"));
+        fs::write(&flow_path, file(&flow.file_yaml)?)
+            .with_context(|| format!("failed to write {}", flow_path.display()))?;
+        let mut report = profile_flow(&flow_path, Some(scratch.path()))
+            .with_context(|| format!("failed to profile flow `{}`", flow.id))?;
+        report.flow = flow.id.clone();
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+/// A component id used as a directory name; ids never contain separators.
+fn plain_segment(name: &str) -> Result<&str> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(name),
+        _ => bail!("component id `{name}` is not a plain name"),
+    }
+}
+
+/// A manifest-relative path that stays inside the component directory.
+fn relative_path(path: &str) -> Result<PathBuf> {
+    let relative = Path::new(path);
+    if relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(relative.to_path_buf())
+    } else {
+        bail!("artifact path `{path}` escapes the component directory")
+    }
+}
+
+pub fn profile_flow(flow_path: &Path, component_dir: Option<&Path>) -> Result<ProfileReport> {
+    let flow_source = fs::read_to_string(flow_path)
+        .with_context(|| format!("failed to read {}", flow_path.display()))?;
+    let flow_doc_json: JsonValue = serde_yaml_bw::from_str(&flow_source)
+        .with_context(|| format!("failed to parse {} for profiling", flow_path.display()))?;
+    let bundle = load_and_validate_bundle(&flow_source, Some(flow_path))
+        .with_context(|| format!("flow validation failed for {}", flow_path.display()))?;
+
+    let mut resolver = ComponentResolver::new(component_dir.map(PathBuf::from));
+    let mut resolved_nodes = Vec::new();
+    for node in &bundle.nodes {
+        if is_builtin_component(&node.component.name) {
+            if node.component.name == "component.exec"
+                && let Some(exec_node) =
+                    resolve_component_exec_node(&mut resolver, node, &flow_doc_json)?
+            {
+                resolved_nodes.push(exec_node);
+            }
+            continue;
+        }
+        resolved_nodes.push(resolver.resolve_node(node, &flow_doc_json)?);
+    }
+
+    let tenant_ctx = local_tenant_ctx()?;
+    let mut wasm: HashMap<PathBuf, Vec<u8>> = HashMap::new();
+    let mut unmetered = HashSet::new();
+    let mut nodes = Vec::with_capacity(resolved_nodes.len());
+
+    for node in &resolved_nodes {
+        let component = &node.component;
+        let declared = component.limits()?;
+        if let Some(limits) = &declared {
+            limits
+                .validate()
+                .map_err(|err| anyhow!("component `{}` limits: {err}", component.name))?;
+            if limits.fuel.is_some() || limits.files.is_some() {
+                unmetered.insert(component.name.clone());
+            }
+        }
+        let limits = declared.clone().unwrap_or(Limits {
+            memory_mb: DEFAULT_MEMORY_MB,
+            wall_time_ms: DEFAULT_WALL_TIME_MS,
+            fuel: None,
+            files: None,
+        });
+
+        if !wasm.contains_key(&component.wasm_path) {
+            let bytes = fs::read(&component.wasm_path)
+                .with_context(|| format!("failed to read {}", component.wasm_path.display()))?;
+            wasm.insert(component.wasm_path.clone(), bytes);
+        }
+        // The harness carries the node id in its exec context, so each node gets its own.
+        let harness = build_harness(
+            node,
+            &wasm[&component.wasm_path],
+            &limits,
+            &bundle.id,
+            &tenant_ctx,
+        );
+        nodes.push(profile_node(harness, node, &limits, declared.is_some())?);
+    }
+
+    let mut notes = Vec::new();
+    let mut unmetered = unmetered.into_iter().collect::<Vec<_>>();
+    unmetered.sort();
+    for name in unmetered {
+        notes.push(format!(
+            "component `{name}` declares fuel/files limits; the greentic-component test harness does not meter these yet"
+        ));
+    }
+
+    Ok(ProfileReport {
+        flow: flow_path.display().to_string(),
+        nodes,
+        notes,
+    })
+}
+
+fn build_harness(
+    node: &ResolvedNode,
+    wasm_bytes: &[u8],
+    limits: &Limits,
+    flow_id: &str,
+    tenant_ctx: &TenantCtx,
+) -> Result<TestHarness> {
+    let component = &node.component;
+    let max_memory_bytes = usize::try_from(u64::from(limits.memory_mb) * 1024 * 1024)
+        .context("memory limit does not fit in usize")?;
+    TestHarness::new(HarnessConfig {
+        wasm_bytes: wasm_bytes.to_vec(),
+        tenant_ctx: tenant_ctx.clone(),
+        flow_id: flow_id.to_string(),
+        node_id: Some(node.node_id.clone()),
+        state_prefix: format!("greentic-dev/profile/{}", component.name),
+        state_seeds: Vec::new(),
+        allow_state_read: true,
+        allow_state_write: true,
+        allow_state_delete: true,
+        allow_secrets: false,
+        allowed_secrets: HashSet::new(),
+        secrets: HashMap::new(),
+        wasi_preopens: Vec::new(),
+        config: None,
+        allow_http: false,
+        timeout_ms: limits.wall_time_ms,
+        max_memory_bytes,
+    })
+    .with_context(|| format!("failed to instantiate component `{}`", component.name))
+}
+
+fn profile_node(
+    harness: Result<TestHarness>,
+    node: &ResolvedNode,
+    limits: &Limits,
+    limits_declared: bool,
+) -> Result<NodeProfile> {
+    let operation = node_operation(node)?;
+    let mut wall_time = Measurement {
+        used: None,
+        limit: Some(limits.wall_time_ms),
+    };
+    let mut memory = Measurement {
+        used: None,
+        limit: Some(u64::from(limits.memory_mb)),
+    };
+
+    let input = node.config.get("input").unwrap_or(&node.config);
+    let status = match harness.and_then(|harness| harness.invoke(&operation, input)) {
+        Ok(outcome) => {
+            wall_time.used = Some(outcome.instantiate_ms + outcome.run_ms);
+            NodeRunStatus::Ok
+        }
+        Err(err) => match err.downcast_ref::<HarnessError>() {
+            Some(HarnessError::Timeout { timeout_ms }) => {
+                wall_time.used = Some(*timeout_ms);
+                NodeRunStatus::WallTimeExceeded
+            }
+            Some(HarnessError::MemoryLimit { .. }) => {
+                memory.used = memory.limit;
+                NodeRunStatus::MemoryExceeded
+            }
+            None => NodeRunStatus::Failed(format!("{err:#}")),
+        },
+    };
+
+    let mut warnings = Vec::new();
+    for (label, measurement) in [("wall time", &wall_time), ("memory", &memory)] {
+        if let Some(pct) = measurement.utilisation_percent()
+            && pct > UTILISATION_WARN_PERCENT
+        {
+            warnings.push(format!("{label} at {pct:.0}% of declared limit"));
+        }
+    }
+
+    Ok(NodeProfile {
+        node_id: node.node_id.clone(),
+        component: node.component.name.clone(),
+        operation,
+        status,
+        limits_declared,
+        wall_time_ms: wall_time,
+        memory_mb: memory,
+        fuel: Measurement {
+            used: None,
+            limit: limits.fuel,
+        },
+        files: Measurement {
+            used: None,
+            limit: limits.files.map(u64::from),
+        },
+        warnings,
+    })
+}
+
+fn node_operation(node: &ResolvedNode) -> Result<String> {
+    let explicit = ["operation", "op"].into_iter().find_map(|key| {
+        node.config
+            .get(key)
+            .and_then(|value| value.as_str())
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| value.to_string())
+    });
+    if let Some(op) = explicit {
+        return Ok(op);
+    }
+    default_operation(&node.component)?.ok_or_else(|| {
+        anyhow!(
+            "node `{}`: component `{}` declares no operations to invoke",
+            node.node_id,
+            node.component.name
+        )
+    })
+}
+
+fn local_tenant_ctx() -> Result<TenantCtx> {
+    let env = EnvId::try_from("dev").map_err(|err| anyhow!("invalid environment id: {err}"))?;
+    let tenant = TenantId::try_from("local").map_err(|err| anyhow!("invalid tenant id: {err}"))?;
+    Ok(TenantCtx::new(env, tenant))
+}
+
+fn print_report(report: &ProfileReport) {
+    println!("Profile: {}", report.flow);
+    for node in &report.nodes {
+        let status = match &node.status {
+            NodeRunStatus::Ok => "ok".to_string(),
+            NodeRunStatus::WallTimeExceeded => "wall time limit exceeded".to_string(),
+            NodeRunStatus::MemoryExceeded => "memory limit exceeded".to_string(),
+            NodeRunStatus::Failed(message) => format!("failed: {message}"),
+        };
+        println!(
+            "- {} ({} :: {}) {}",
+            node.node_id, node.component, node.operation, status
+        );
+        if !node.limits_declared {
+            println!("  limits: none declared (defaults applied)");
+        }
+        println!("  wall time: {}", node.wall_time_ms.render("ms"));
+        println!("  memory   : {}", node.memory_mb.render("MB"));
+        println!("  fuel     : {}", node.fuel.render(""));
+        println!("  files    : {}", node.files.render(""));
+        for warning in &node.warnings {
+            println!("  ⚠ {warning}");
+        }
+    }
+    if !report.notes.is_empty() {
+        println!("\nNotes:");
+        for note in &report.notes {
+            println!("  - {note}");
+        }
+    }
+}
@@ -4,6 +4,9 @@ use anyhow::Result;
 use greentic_pack::reader::{PackVerifyResult, SigningPolicy, open_pack};
 use serde_json::json;
 
+use crate::diagnostics;
+use crate::events::{self, Event};
+
 #[derive(Debug, Clone, Copy)]
 pub enum VerifyPolicy {
     Strict,
@@ -20,11 +23,24 @@ impl From<VerifyPolicy> for SigningPolicy {
 }
 
 pub fn run(pack_path: &Path, policy: VerifyPolicy, emit_json: bool) -> Result<()> {
+    events::stage_started("verify");
     let load = open_pack(pack_path, policy.into()).map_err(|err: PackVerifyResult| {
         anyhow::anyhow!("pack verification failed: {}", err.message)
     })?;
+    events::stage_finished("verify");
 
-    if emit_json {
+    if events::json() {
+        for warning in &load.report.warnings {
+            events::warning(diagnostics::PACK_VERIFY_WARNING, warning);
+        }
+        events::emit(Event::Result {
+            data: json!({
+                "pack": pack_path,
+                "signature_ok": load.report.signature_ok,
+                "sbom_ok": load.report.sbom_ok,
+            }),
+        });
+    } else if emit_json {
         let doc = json!({
             "manifest": load.manifest,
             "report": {
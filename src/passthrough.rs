@@ -1,54 +1,144 @@
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Result, anyhow, bail};
 use std::env;
 use std::ffi::OsString;
-use std::path::{Path, PathBuf};
+use std::fmt;
+use std::path::PathBuf;
 use std::process::{Command, ExitStatus, Stdio};
 
-/// Resolve a binary by name using env override, optional workspace target, then PATH.
-pub fn resolve_binary(name: &str) -> Result<PathBuf> {
-    let env_key = format!("GREENTIC_DEV_BIN_{}", name.replace('-', "_").to_uppercase());
-    if let Ok(path) = env::var(&env_key) {
+use crate::config::{self, ToolsSection};
+use crate::hooks::Hooks;
+use crate::util::process::StreamMode;
+
+/// Env var that overrides the binary used for `name` (e.g. `GREENTIC_DEV_BIN_GREENTIC_FLOW`).
+pub fn env_key(name: &str) -> String {
+    format!("GREENTIC_DEV_BIN_{}", name.replace('-', "_").to_uppercase())
+}
+
+/// Where [`resolve_tool`] found a companion binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinarySource {
+    /// `GREENTIC_DEV_BIN_*` override.
+    Env(String),
+    /// `[tools.<name>].path` in the greentic-dev config.
+    Config,
+    /// Found on PATH.
+    Path,
+    /// `target/debug` or `target/release` under the current directory.
+    WorkspaceTarget,
+}
+
+impl fmt::Display for BinarySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinarySource::Env(var) => write!(f, "env {var}"),
+            BinarySource::Config => f.write_str("config [tools.*].path"),
+            BinarySource::Path => f.write_str("PATH"),
+            BinarySource::WorkspaceTarget => f.write_str("workspace target/"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedBinary {
+    pub name: String,
+    pub path: PathBuf,
+    pub source: BinarySource,
+}
+
+/// Resolve a companion binary, first match wins: `GREENTIC_DEV_BIN_*`, `[tools.<name>].path`,
+/// PATH, then `target/debug` and `target/release` under the current directory.
+pub fn resolve_tool(name: &str, tools: &ToolsSection) -> Result<ResolvedBinary> {
+    let resolved = |path: PathBuf, source: BinarySource| ResolvedBinary {
+        name: name.to_string(),
+        path,
+        source,
+    };
+
+    let env_key = env_key(name);
+    if let Some(path) = env::var_os(&env_key) {
         let pb = PathBuf::from(path);
         if pb.exists() {
-            return Ok(pb);
+            return Ok(resolved(pb, BinarySource::Env(env_key)));
         }
         bail!("{env_key} points to non-existent binary: {}", pb.display());
     }
 
+    if let Some(path) = tools.entry(name).and_then(|entry| entry.path.as_ref()) {
+        if !path.exists() {
+            bail!("configured {name} path `{}` does not exist", path.display());
+        }
+        return Ok(resolved(path.clone(), BinarySource::Config));
+    }
+
     if let Ok(path) = which::which(name) {
-        return Ok(path);
+        return Ok(resolved(path, BinarySource::Path));
     }
 
-    // Optional workspace target resolution (debug and release) as a fallback.
     if let Ok(cwd) = env::current_dir() {
         for dir in ["target/debug", "target/release"] {
             let candidate = cwd.join(dir).join(name);
             if candidate.exists() {
-                return Ok(candidate);
+                return Ok(resolved(candidate, BinarySource::WorkspaceTarget));
             }
         }
     }
 
-    which::which(name).with_context(|| {
-        format!("failed to find `{name}` in PATH; set {env_key} or install {name}")
-    })
+    let config_hint = config::config_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "$XDG_CONFIG_HOME/greentic-dev/config.toml".to_string());
+    bail!(
+        "failed to find `{name}` in PATH; install {name}, set {env_key}, or set \
+         [tools.{name}].path in {config_hint}"
+    )
 }
 
-pub fn run_passthrough(bin: &Path, args: &[OsString], verbose: bool) -> Result<ExitStatus> {
+/// Run `bin` with `args`. In `--output json` mode its stdout goes to stderr so stdout only carries
+/// events.
+pub fn run_passthrough(
+    bin: &ResolvedBinary,
+    args: &[OsString],
+    verbose: bool,
+) -> Result<ExitStatus> {
+    let path = &bin.path;
     if verbose {
-        eprintln!("greentic-dev passthrough -> {} {:?}", bin.display(), args);
-        let _ = Command::new(bin)
+        eprintln!(
+            "greentic-dev: {} -> {} (from {})",
+            bin.name,
+            path.display(),
+            bin.source
+        );
+        eprintln!("greentic-dev passthrough -> {} {:?}", path.display(), args);
+        let _ = Command::new(path)
             .arg("--version")
-            .stdout(Stdio::inherit())
+            .stdout(StreamMode::for_child_stdout().stdio())
             .stderr(Stdio::inherit())
             .status();
     }
 
-    Command::new(bin)
+    Command::new(path)
         .args(args)
         .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
+        .stdout(StreamMode::for_child_stdout().stdio())
         .stderr(Stdio::inherit())
         .status()
-        .map_err(|e| anyhow!("failed to execute {}: {e}", bin.display()))
+        .map_err(|e| anyhow!("failed to execute {}: {e}", path.display()))
+}
+
+/// [`run_passthrough`] between the configured pre and post hooks. A failing pre hook aborts before
+/// the delegated command starts; a failing post hook is an error only when the command succeeded.
+pub fn run_with_hooks(
+    bin: &ResolvedBinary,
+    args: &[OsString],
+    hooks: &Hooks,
+    verbose: bool,
+) -> Result<ExitStatus> {
+    hooks.run_pre(verbose)?;
+    let status = run_passthrough(bin, args, verbose)?;
+    if let Err(err) = hooks.run_post(status, verbose) {
+        if status.success() {
+            return Err(err);
+        }
+        eprintln!("warning: {err:#}");
+    }
+    Ok(status)
 }
@@ -1,9 +1,13 @@
+use std::ffi::OsString;
 use std::path::PathBuf;
-use std::process::{Command, Stdio};
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Result, bail};
 use clap::{Args, Subcommand};
 
+use crate::config;
+use crate::hooks::Hooks;
+use crate::passthrough::{resolve_tool, run_with_hooks};
+
 #[derive(Subcommand, Debug)]
 pub enum SecretsCommand {
     /// Delegate to greentic-secrets to initialize secrets for a pack
@@ -20,26 +24,20 @@ pub struct SecretsInitArgs {
     pub passthrough: Vec<String>,
 }
 
-pub fn run_secrets_command(cmd: SecretsCommand) -> Result<()> {
+pub fn run_secrets_command(cmd: SecretsCommand, verbose: bool) -> Result<()> {
     match cmd {
-        SecretsCommand::Init(args) => run_init(&args),
+        SecretsCommand::Init(args) => run_init(&args, verbose),
     }
 }
 
-fn run_init(args: &SecretsInitArgs) -> Result<()> {
-    let mut command = Command::new("greentic-secrets");
-    command
-        .arg("init")
-        .arg("--pack")
-        .arg(&args.pack)
-        .args(&args.passthrough)
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit());
+fn run_init(args: &SecretsInitArgs, verbose: bool) -> Result<()> {
+    let config = config::load()?;
+    let bin = resolve_tool("greentic-secrets", &config.tools)?;
+    let mut argv: Vec<OsString> = vec!["init".into(), "--pack".into(), args.pack.clone().into()];
+    argv.extend(args.passthrough.iter().map(OsString::from));
 
-    let status = command
-        .status()
-        .with_context(|| "failed to execute greentic-secrets (is it on PATH?)")?;
+    let hooks = Hooks::for_command(&config.hooks, "secrets", &argv);
+    let status = run_with_hooks(&bin, &argv, &hooks, verbose)?;
     if !status.success() {
         bail!("greentic-secrets exited with status {}", status);
     }
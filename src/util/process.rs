@@ -8,6 +8,27 @@ use anyhow::{Context, Result};
 pub enum StreamMode {
     Inherit,
     Capture,
+    /// Write to this process's stderr, keeping stdout free for `--output json` events.
+    Stderr,
+}
+
+impl StreamMode {
+    /// Where a delegated process should write its stdout for the current output format.
+    pub fn for_child_stdout() -> Self {
+        if crate::events::json() {
+            StreamMode::Stderr
+        } else {
+            StreamMode::Inherit
+        }
+    }
+
+    pub fn stdio(self) -> Stdio {
+        match self {
+            StreamMode::Inherit => Stdio::inherit(),
+            StreamMode::Capture => Stdio::piped(),
+            StreamMode::Stderr => Stdio::from(std::io::stderr()),
+        }
+    }
 }
 
 pub struct CommandSpec {
@@ -50,18 +71,6 @@ pub fn run(spec: CommandSpec) -> Result<CommandOutput> {
     }
 
     match (spec.stdout, spec.stderr) {
-        (StreamMode::Inherit, StreamMode::Inherit) => {
-            command.stdout(Stdio::inherit());
-            command.stderr(Stdio::inherit());
-            let status = command
-                .status()
-                .with_context(|| format!("failed to spawn `{}`", spec.program.to_string_lossy()))?;
-            Ok(CommandOutput {
-                status,
-                stdout: None,
-                stderr: None,
-            })
-        }
         (StreamMode::Capture, StreamMode::Capture) => {
             command.stdout(Stdio::piped());
             command.stderr(Stdio::piped());
@@ -74,6 +83,20 @@ pub fn run(spec: CommandSpec) -> Result<CommandOutput> {
                 stderr: Some(output.stderr),
             })
         }
-        _ => anyhow::bail!("mixed capture/inherit mode is not supported yet"),
+        (StreamMode::Capture, _) | (_, StreamMode::Capture) => {
+            anyhow::bail!("mixed capture/inherit mode is not supported yet")
+        }
+        (stdout, stderr) => {
+            command.stdout(stdout.stdio());
+            command.stderr(stderr.stdio());
+            let status = command
+                .status()
+                .with_context(|| format!("failed to spawn `{}`", spec.program.to_string_lossy()))?;
+            Ok(CommandOutput {
+                status,
+                stdout: None,
+                stderr: None,
+            })
+        }
     }
 }
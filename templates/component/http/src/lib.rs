@@ -0,0 +1,192 @@
+use greentic_interfaces_guest::{
+    component,
+    component::exports::greentic::component::node,
+    http_client,
+    telemetry_logger,
+    types,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const DEFAULT_BASE_URL: &str = "{{base_url}}";
+
+struct Component;
+
+component::export!(Component);
+
+#[derive(Debug, Deserialize)]
+struct InvokeInput {
+    #[serde(default)]
+    base_url: Option<String>,
+    #[serde(default = "default_method")]
+    method: String,
+    #[serde(default)]
+    path: String,
+    #[serde(default)]
+    body: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct InvokeOutput {
+    status: u16,
+    body: Option<Value>,
+}
+
+impl node::Guest for Component {
+    fn get_manifest() -> String {
+        let schema: Value = serde_json::from_str(include_str!("../schemas/v1/config.schema.json"))
+            .expect("config schema json to be valid");
+        json!({
+            "name": "{{component_name}}",
+            "description": "{{description}}",
+            "capabilities": ["telemetry", "http"],
+            "exports": [
+                { "operation": "request" }
+            ],
+            "config_schema": schema,
+            "wit_compat": {
+                "package": "greentic:component",
+                "min": "{{component_world_version}}",
+                "max": "{{component_world_version}}"
+            }
+        })
+        .to_string()
+    }
+
+    fn on_start(_ctx: node::ExecCtx) -> Result<node::LifecycleStatus, String> {
+        Ok(node::LifecycleStatus::Ok)
+    }
+
+    fn on_stop(
+        _ctx: node::ExecCtx,
+        _reason: String,
+    ) -> Result<node::LifecycleStatus, String> {
+        Ok(node::LifecycleStatus::Ok)
+    }
+
+    fn invoke(
+        ctx: node::ExecCtx,
+        _operation: String,
+        input: String,
+    ) -> node::InvokeResult {
+        let payload: InvokeInput = match serde_json::from_str(&input) {
+            Ok(payload) => payload,
+            Err(err) => return node_error("invalid-input", err.to_string(), false),
+        };
+        let tenant_ctx = tenant_ctx_from_exec(&ctx);
+        let base_url = payload.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL);
+        let url = format!(
+            "{}/{}",
+            base_url.trim_end_matches('/'),
+            payload.path.trim_start_matches('/')
+        );
+        let body = payload
+            .body
+            .map(|body| serde_json::to_vec(&body).expect("serialize request body"));
+        let mut headers = vec![("accept".to_string(), "application/json".to_string())];
+        if body.is_some() {
+            headers.push(("content-type".to_string(), "application/json".to_string()));
+        }
+
+        let response = http_client::send(
+            http_client::Request {
+                method: payload.method.clone(),
+                url: url.clone(),
+                headers,
+                body,
+            },
+            Some(tenant_ctx.clone()),
+        )
+        .map_err(|err| err.to_string())
+        .and_then(|result| result.map_err(|err| err.message));
+        let response = match response {
+            Ok(response) => response,
+            Err(err) => {
+                return node_error(
+                    "http-failed",
+                    format!("{} {url} failed: {err}", payload.method),
+                    true,
+                );
+            }
+        };
+
+        let _ = telemetry_logger::log(
+            span_from_exec(&ctx),
+            vec![
+                ("event".to_string(), "http-request".to_string()),
+                ("url".to_string(), url),
+                ("status".to_string(), response.status.to_string()),
+            ],
+            Some(tenant_ctx),
+        );
+
+        let output = InvokeOutput {
+            status: response.status,
+            body: response
+                .body
+                .and_then(|bytes| serde_json::from_slice(&bytes).ok()),
+        };
+        node::InvokeResult::Ok(serde_json::to_string(&output).expect("serialize response"))
+    }
+
+    fn invoke_stream(
+        _ctx: node::ExecCtx,
+        _operation: String,
+        _input: String,
+    ) -> Vec<node::StreamEvent> {
+        Vec::new()
+    }
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+fn node_error(code: &str, message: String, retryable: bool) -> node::InvokeResult {
+    node::InvokeResult::Err(node::NodeError {
+        code: code.to_string(),
+        message,
+        retryable,
+        backoff_ms: None,
+        details: None,
+    })
+}
+
+fn tenant_ctx_from_exec(ctx: &node::ExecCtx) -> types::TenantCtx {
+    let tenant = ctx.tenant.tenant.clone();
+    types::TenantCtx {
+        env: tenant.clone(),
+        tenant: tenant.clone(),
+        tenant_id: tenant.clone(),
+        team: ctx.tenant.team.clone(),
+        team_id: ctx.tenant.team.clone(),
+        user: ctx.tenant.user.clone(),
+        user_id: ctx.tenant.user.clone(),
+        session_id: None,
+        flow_id: Some(ctx.flow_id.clone()),
+        node_id: ctx.node_id.clone(),
+        provider_id: None,
+        trace_id: ctx.tenant.trace_id.clone(),
+        correlation_id: ctx.tenant.correlation_id.clone(),
+        attributes: Vec::new(),
+        deadline_ms: ctx
+            .tenant
+            .deadline_unix_ms
+            .and_then(|d| i64::try_from(d).ok()),
+        attempt: ctx.tenant.attempt,
+        idempotency_key: ctx.tenant.idempotency_key.clone(),
+        impersonation: None,
+    }
+}
+
+fn span_from_exec(ctx: &node::ExecCtx) -> types::SpanContext {
+    types::SpanContext {
+        tenant: ctx.tenant.tenant.clone(),
+        session_id: None,
+        flow_id: ctx.flow_id.clone(),
+        node_id: ctx.node_id.clone(),
+        provider: "{{component_name}}".to_string(),
+        start_ms: None,
+        end_ms: None,
+    }
+}
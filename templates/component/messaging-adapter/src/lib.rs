@@ -0,0 +1,227 @@
+use greentic_interfaces_guest::{
+    component,
+    component::exports::greentic::component::node,
+    http_client,
+    secrets_store,
+    telemetry_logger,
+    types,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const CHANNEL: &str = "{{channel}}";
+const OUTBOUND_URL: &str = "{{outbound_url}}";
+const TOKEN_SECRET: &str = "{{token_secret}}";
+
+struct Component;
+
+component::export!(Component);
+
+/// Webhook payload posted by the channel; adjust the field names to the platform's format.
+#[derive(Debug, Deserialize)]
+struct InboundEvent {
+    conversation_id: String,
+    #[serde(default)]
+    sender: Option<String>,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    metadata: Value,
+}
+
+/// Channel-neutral message handed to the rest of the flow.
+#[derive(Debug, Serialize, Deserialize)]
+struct Message {
+    channel: String,
+    session_id: String,
+    #[serde(default)]
+    from: Option<String>,
+    text: String,
+    #[serde(default)]
+    metadata: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Reply {
+    conversation_id: String,
+    text: String,
+}
+
+impl node::Guest for Component {
+    fn get_manifest() -> String {
+        let schema: Value = serde_json::from_str(include_str!("../schemas/v1/config.schema.json"))
+            .expect("config schema json to be valid");
+        json!({
+            "name": "{{component_name}}",
+            "description": "{{description}}",
+            "capabilities": ["telemetry", "secrets", "http"],
+            "exports": [
+                { "operation": "ingress" },
+                { "operation": "egress" }
+            ],
+            "config_schema": schema,
+            "secrets": [TOKEN_SECRET],
+            "wit_compat": {
+                "package": "greentic:component",
+                "min": "{{component_world_version}}",
+                "max": "{{component_world_version}}"
+            }
+        })
+        .to_string()
+    }
+
+    fn on_start(_ctx: node::ExecCtx) -> Result<node::LifecycleStatus, String> {
+        Ok(node::LifecycleStatus::Ok)
+    }
+
+    fn on_stop(
+        _ctx: node::ExecCtx,
+        _reason: String,
+    ) -> Result<node::LifecycleStatus, String> {
+        Ok(node::LifecycleStatus::Ok)
+    }
+
+    fn invoke(
+        ctx: node::ExecCtx,
+        operation: String,
+        input: String,
+    ) -> node::InvokeResult {
+        match operation.as_str() {
+            "ingress" => ingress(&input),
+            "egress" => egress(&ctx, &input),
+            other => node_error(
+                "unknown-operation",
+                format!("unknown operation `{other}`"),
+                false,
+            ),
+        }
+    }
+
+    fn invoke_stream(
+        _ctx: node::ExecCtx,
+        _operation: String,
+        _input: String,
+    ) -> Vec<node::StreamEvent> {
+        Vec::new()
+    }
+}
+
+fn ingress(input: &str) -> node::InvokeResult {
+    let event: InboundEvent = match serde_json::from_str(input) {
+        Ok(event) => event,
+        Err(err) => return node_error("invalid-event", err.to_string(), false),
+    };
+    let message = Message {
+        channel: CHANNEL.to_string(),
+        session_id: event.conversation_id,
+        from: event.sender,
+        text: event.text,
+        metadata: event.metadata,
+    };
+    node::InvokeResult::Ok(serde_json::to_string(&message).expect("serialize message"))
+}
+
+fn egress(ctx: &node::ExecCtx, input: &str) -> node::InvokeResult {
+    let reply: Reply = match serde_json::from_str(input) {
+        Ok(reply) => reply,
+        Err(err) => return node_error("invalid-reply", err.to_string(), false),
+    };
+    let Some(token) = secrets_store::read(TOKEN_SECRET)
+        .ok()
+        .and_then(Result::ok)
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+    else {
+        return node_error(
+            "missing-secret",
+            format!("secret `{TOKEN_SECRET}` is not provisioned"),
+            false,
+        );
+    };
+
+    let tenant_ctx = tenant_ctx_from_exec(ctx);
+    let body = json!({ "conversation_id": reply.conversation_id, "text": reply.text });
+    let response = http_client::send(
+        http_client::Request {
+            method: "POST".to_string(),
+            url: OUTBOUND_URL.to_string(),
+            headers: vec![
+                ("authorization".to_string(), format!("Bearer {token}")),
+                ("content-type".to_string(), "application/json".to_string()),
+            ],
+            body: Some(serde_json::to_vec(&body).expect("serialize reply")),
+        },
+        Some(tenant_ctx.clone()),
+    )
+    .map_err(|err| err.to_string())
+    .and_then(|result| result.map_err(|err| err.message));
+    let status = match response {
+        Ok(response) if response.status < 400 => response.status,
+        Ok(response) => {
+            return node_error(
+                "channel-rejected",
+                format!("{OUTBOUND_URL} returned {}", response.status),
+                response.status >= 500,
+            );
+        }
+        Err(err) => return node_error("channel-unreachable", err, true),
+    };
+
+    let _ = telemetry_logger::log(
+        span_from_exec(ctx),
+        vec![
+            ("event".to_string(), "message-sent".to_string()),
+            ("channel".to_string(), CHANNEL.to_string()),
+        ],
+        Some(tenant_ctx),
+    );
+    node::InvokeResult::Ok(json!({ "sent": true, "status": status }).to_string())
+}
+
+fn node_error(code: &str, message: String, retryable: bool) -> node::InvokeResult {
+    node::InvokeResult::Err(node::NodeError {
+        code: code.to_string(),
+        message,
+        retryable,
+        backoff_ms: None,
+        details: None,
+    })
+}
+
+fn tenant_ctx_from_exec(ctx: &node::ExecCtx) -> types::TenantCtx {
+    let tenant = ctx.tenant.tenant.clone();
+    types::TenantCtx {
+        env: tenant.clone(),
+        tenant: tenant.clone(),
+        tenant_id: tenant.clone(),
+        team: ctx.tenant.team.clone(),
+        team_id: ctx.tenant.team.clone(),
+        user: ctx.tenant.user.clone(),
+        user_id: ctx.tenant.user.clone(),
+        session_id: None,
+        flow_id: Some(ctx.flow_id.clone()),
+        node_id: ctx.node_id.clone(),
+        provider_id: None,
+        trace_id: ctx.tenant.trace_id.clone(),
+        correlation_id: ctx.tenant.correlation_id.clone(),
+        attributes: Vec::new(),
+        deadline_ms: ctx
+            .tenant
+            .deadline_unix_ms
+            .and_then(|d| i64::try_from(d).ok()),
+        attempt: ctx.tenant.attempt,
+        idempotency_key: ctx.tenant.idempotency_key.clone(),
+        impersonation: None,
+    }
+}
+
+fn span_from_exec(ctx: &node::ExecCtx) -> types::SpanContext {
+    types::SpanContext {
+        tenant: ctx.tenant.tenant.clone(),
+        session_id: None,
+        flow_id: ctx.flow_id.clone(),
+        node_id: ctx.node_id.clone(),
+        provider: "{{component_name}}".to_string(),
+        start_ms: None,
+        end_ms: None,
+    }
+}
@@ -37,7 +37,7 @@ impl node::Guest for Component {
             .expect("config schema json to be valid");
         json!({
             "name": "{{component_name}}",
-            "description": "Generated Greentic component scaffold",
+            "description": "{{description}}",
             "capabilities": ["telemetry", "secrets", "state", "http"],
             "exports": [
                 { "operation": "invoke" }
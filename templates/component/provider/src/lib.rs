@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+
+use greentic_interfaces_guest::{
+    component,
+    component::exports::greentic::component::node,
+    http_client,
+    secrets_store,
+    telemetry_logger,
+    types,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const SERVICE_URL: &str = "{{service_url}}";
+const API_KEY_SECRET: &str = "{{api_key_secret}}";
+
+struct Component;
+
+component::export!(Component);
+
+#[derive(Debug, Deserialize)]
+struct InvokeInput {
+    resource: String,
+    #[serde(default)]
+    query: BTreeMap<String, String>,
+    #[serde(default)]
+    body: Option<Value>,
+}
+
+impl node::Guest for Component {
+    fn get_manifest() -> String {
+        let schema: Value = serde_json::from_str(include_str!("../schemas/v1/config.schema.json"))
+            .expect("config schema json to be valid");
+        json!({
+            "name": "{{component_name}}",
+            "description": "{{description}}",
+            "capabilities": ["telemetry", "secrets", "http"],
+            "exports": [
+                { "operation": "get" },
+                { "operation": "create" }
+            ],
+            "config_schema": schema,
+            "secrets": [API_KEY_SECRET],
+            "wit_compat": {
+                "package": "greentic:component",
+                "min": "{{component_world_version}}",
+                "max": "{{component_world_version}}"
+            }
+        })
+        .to_string()
+    }
+
+    fn on_start(_ctx: node::ExecCtx) -> Result<node::LifecycleStatus, String> {
+        Ok(node::LifecycleStatus::Ok)
+    }
+
+    fn on_stop(
+        _ctx: node::ExecCtx,
+        _reason: String,
+    ) -> Result<node::LifecycleStatus, String> {
+        Ok(node::LifecycleStatus::Ok)
+    }
+
+    fn invoke(
+        ctx: node::ExecCtx,
+        operation: String,
+        input: String,
+    ) -> node::InvokeResult {
+        let payload: InvokeInput = match serde_json::from_str(&input) {
+            Ok(payload) => payload,
+            Err(err) => return node_error("invalid-input", err.to_string(), false),
+        };
+        let method = match operation.as_str() {
+            "get" => "GET",
+            "create" => "POST",
+            other => {
+                return node_error(
+                    "unknown-operation",
+                    format!("unknown operation `{other}`"),
+                    false,
+                );
+            }
+        };
+        let Some(api_key) = secrets_store::read(API_KEY_SECRET)
+            .ok()
+            .and_then(Result::ok)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+        else {
+            return node_error(
+                "missing-secret",
+                format!("secret `{API_KEY_SECRET}` is not provisioned"),
+                false,
+            );
+        };
+
+        let tenant_ctx = tenant_ctx_from_exec(&ctx);
+        let query = payload
+            .query
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        let mut url = format!(
+            "{}/{}",
+            SERVICE_URL.trim_end_matches('/'),
+            payload.resource.trim_start_matches('/')
+        );
+        if !query.is_empty() {
+            url = format!("{url}?{query}");
+        }
+        let response = http_client::send(
+            http_client::Request {
+                method: method.to_string(),
+                url: url.clone(),
+                headers: vec![
+                    ("authorization".to_string(), format!("Bearer {api_key}")),
+                    ("content-type".to_string(), "application/json".to_string()),
+                ],
+                body: payload
+                    .body
+                    .map(|body| serde_json::to_vec(&body).expect("serialize request body")),
+            },
+            Some(tenant_ctx.clone()),
+        )
+        .map_err(|err| err.to_string())
+        .and_then(|result| result.map_err(|err| err.message));
+        let response = match response {
+            Ok(response) => response,
+            Err(err) => return node_error("upstream-unreachable", err, true),
+        };
+
+        let _ = telemetry_logger::log(
+            span_from_exec(&ctx),
+            vec![
+                ("event".to_string(), "upstream-call".to_string()),
+                ("operation".to_string(), operation),
+                ("status".to_string(), response.status.to_string()),
+            ],
+            Some(tenant_ctx),
+        );
+
+        let body = response
+            .body
+            .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+            .unwrap_or(Value::Null);
+        if response.status >= 400 {
+            return node_error(
+                "upstream-error",
+                format!("{url} returned {}: {body}", response.status),
+                response.status >= 500,
+            );
+        }
+        node::InvokeResult::Ok(body.to_string())
+    }
+
+    fn invoke_stream(
+        _ctx: node::ExecCtx,
+        _operation: String,
+        _input: String,
+    ) -> Vec<node::StreamEvent> {
+        Vec::new()
+    }
+}
+
+fn node_error(code: &str, message: String, retryable: bool) -> node::InvokeResult {
+    node::InvokeResult::Err(node::NodeError {
+        code: code.to_string(),
+        message,
+        retryable,
+        backoff_ms: None,
+        details: None,
+    })
+}
+
+fn tenant_ctx_from_exec(ctx: &node::ExecCtx) -> types::TenantCtx {
+    let tenant = ctx.tenant.tenant.clone();
+    types::TenantCtx {
+        env: tenant.clone(),
+        tenant: tenant.clone(),
+        tenant_id: tenant.clone(),
+        team: ctx.tenant.team.clone(),
+        team_id: ctx.tenant.team.clone(),
+        user: ctx.tenant.user.clone(),
+        user_id: ctx.tenant.user.clone(),
+        session_id: None,
+        flow_id: Some(ctx.flow_id.clone()),
+        node_id: ctx.node_id.clone(),
+        provider_id: None,
+        trace_id: ctx.tenant.trace_id.clone(),
+        correlation_id: ctx.tenant.correlation_id.clone(),
+        attributes: Vec::new(),
+        deadline_ms: ctx
+            .tenant
+            .deadline_unix_ms
+            .and_then(|d| i64::try_from(d).ok()),
+        attempt: ctx.tenant.attempt,
+        idempotency_key: ctx.tenant.idempotency_key.clone(),
+        impersonation: None,
+    }
+}
+
+fn span_from_exec(ctx: &node::ExecCtx) -> types::SpanContext {
+    types::SpanContext {
+        tenant: ctx.tenant.tenant.clone(),
+        session_id: None,
+        flow_id: ctx.flow_id.clone(),
+        node_id: ctx.node_id.clone(),
+        provider: "{{component_name}}".to_string(),
+        start_ms: None,
+        end_ms: None,
+    }
+}
@@ -0,0 +1,177 @@
+use greentic_interfaces_guest::{
+    component,
+    component::exports::greentic::component::node,
+    state_store,
+    telemetry_logger,
+    types,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const STATE_NAMESPACE: &str = "{{state_namespace}}";
+
+struct Component;
+
+component::export!(Component);
+
+#[derive(Debug, Deserialize)]
+struct InvokeInput {
+    key: String,
+    #[serde(default)]
+    value: Option<Value>,
+    #[serde(default = "default_step")]
+    step: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct InvokeOutput {
+    key: String,
+    value: Option<Value>,
+}
+
+impl node::Guest for Component {
+    fn get_manifest() -> String {
+        let schema: Value = serde_json::from_str(include_str!("../schemas/v1/config.schema.json"))
+            .expect("config schema json to be valid");
+        json!({
+            "name": "{{component_name}}",
+            "description": "{{description}}",
+            "capabilities": ["telemetry", "state"],
+            "exports": [
+                { "operation": "get" },
+                { "operation": "set" },
+                { "operation": "increment" }
+            ],
+            "config_schema": schema,
+            "wit_compat": {
+                "package": "greentic:component",
+                "min": "{{component_world_version}}",
+                "max": "{{component_world_version}}"
+            }
+        })
+        .to_string()
+    }
+
+    fn on_start(_ctx: node::ExecCtx) -> Result<node::LifecycleStatus, String> {
+        Ok(node::LifecycleStatus::Ok)
+    }
+
+    fn on_stop(
+        _ctx: node::ExecCtx,
+        _reason: String,
+    ) -> Result<node::LifecycleStatus, String> {
+        Ok(node::LifecycleStatus::Ok)
+    }
+
+    fn invoke(
+        ctx: node::ExecCtx,
+        operation: String,
+        input: String,
+    ) -> node::InvokeResult {
+        let payload: InvokeInput = match serde_json::from_str(&input) {
+            Ok(payload) => payload,
+            Err(err) => return node_error("invalid-input", err.to_string()),
+        };
+        let tenant_ctx = tenant_ctx_from_exec(&ctx);
+        let state_key: types::StateKey = format!("{STATE_NAMESPACE}:{}", payload.key).into();
+        let current = state_store::read(state_key.clone(), Some(tenant_ctx.clone()))
+            .ok()
+            .and_then(Result::ok)
+            .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok());
+
+        let value = match operation.as_str() {
+            "get" => current,
+            "set" => payload.value,
+            "increment" => {
+                let base = current.as_ref().and_then(Value::as_i64).unwrap_or(0);
+                Some(json!(base + payload.step))
+            }
+            other => return node_error("unknown-operation", format!("unknown operation `{other}`")),
+        };
+
+        if operation != "get" {
+            let bytes = serde_json::to_vec(&value).expect("serialize state");
+            let written = state_store::write(state_key, bytes, Some(tenant_ctx.clone()))
+                .map_err(|err| err.to_string())
+                .and_then(|result| result.map_err(|err| err.message));
+            if let Err(err) = written {
+                return node_error("state-write-failed", err);
+            }
+            let _ = telemetry_logger::log(
+                span_from_exec(&ctx),
+                vec![
+                    ("event".to_string(), "state-updated".to_string()),
+                    ("key".to_string(), payload.key.clone()),
+                ],
+                Some(tenant_ctx),
+            );
+        }
+
+        let output = InvokeOutput {
+            key: payload.key,
+            value,
+        };
+        node::InvokeResult::Ok(serde_json::to_string(&output).expect("serialize response"))
+    }
+
+    fn invoke_stream(
+        _ctx: node::ExecCtx,
+        _operation: String,
+        _input: String,
+    ) -> Vec<node::StreamEvent> {
+        Vec::new()
+    }
+}
+
+fn default_step() -> i64 {
+    1
+}
+
+fn node_error(code: &str, message: String) -> node::InvokeResult {
+    node::InvokeResult::Err(node::NodeError {
+        code: code.to_string(),
+        message,
+        retryable: false,
+        backoff_ms: None,
+        details: None,
+    })
+}
+
+fn tenant_ctx_from_exec(ctx: &node::ExecCtx) -> types::TenantCtx {
+    let tenant = ctx.tenant.tenant.clone();
+    types::TenantCtx {
+        env: tenant.clone(),
+        tenant: tenant.clone(),
+        tenant_id: tenant.clone(),
+        team: ctx.tenant.team.clone(),
+        team_id: ctx.tenant.team.clone(),
+        user: ctx.tenant.user.clone(),
+        user_id: ctx.tenant.user.clone(),
+        session_id: None,
+        flow_id: Some(ctx.flow_id.clone()),
+        node_id: ctx.node_id.clone(),
+        provider_id: None,
+        trace_id: ctx.tenant.trace_id.clone(),
+        correlation_id: ctx.tenant.correlation_id.clone(),
+        attributes: Vec::new(),
+        deadline_ms: ctx
+            .tenant
+            .deadline_unix_ms
+            .and_then(|d| i64::try_from(d).ok()),
+        attempt: ctx.tenant.attempt,
+        idempotency_key: ctx.tenant.idempotency_key.clone(),
+        impersonation: None,
+    }
+}
+
+fn span_from_exec(ctx: &node::ExecCtx) -> types::SpanContext {
+    types::SpanContext {
+        tenant: ctx.tenant.tenant.clone(),
+        session_id: None,
+        flow_id: ctx.flow_id.clone(),
+        node_id: ctx.node_id.clone(),
+        provider: "{{component_name}}".to_string(),
+        start_ms: None,
+        end_ms: None,
+    }
+}
@@ -0,0 +1,239 @@
+use std::fs::{self, File};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use greentic_dev::artifact_store::{ArtifactStore, StoredArtifact};
+use greentic_dev::cache::{
+    CacheKind, GcBudget, gc, list_entries, parse_age, parse_size, register_workspace,
+};
+use greentic_dev::component_add::run_component_remove;
+use greentic_dev::pack_init::{WorkspaceComponent, WorkspaceManifest};
+use greentic_pack::builder::ComponentEntry;
+use once_cell::sync::Lazy;
+use semver::Version;
+use tempfile::tempdir;
+
+static ENV_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn write_manifest(workspace: &Path, name: &str, wasm: &Path) {
+    let manifest = WorkspaceManifest {
+        components: vec![WorkspaceComponent {
+            coordinate: format!("component://demo/{name}@^1"),
+            entry: ComponentEntry {
+                name: name.to_string(),
+                version: Version::new(1, 0, 0),
+                file_wasm: wasm.display().to_string(),
+                hash_blake3: String::new(),
+                schema_file: None,
+                manifest_file: None,
+                world: None,
+                capabilities: None,
+            },
+            license: None,
+        }],
+    };
+    fs::create_dir_all(workspace.join(".greentic")).unwrap();
+    manifest
+        .save(&workspace.join(".greentic/manifest.json"))
+        .unwrap();
+}
+
+fn store_entry(store: &ArtifactStore, file: &str, len: usize, age: Duration) -> StoredArtifact {
+    let bytes: Vec<u8> = (0..len)
+        .map(|i| (i % 251) as u8)
+        .chain([len as u8])
+        .collect();
+    let stored = store.insert(None, file, &bytes).unwrap();
+    backdate(stored.dir(), &stored.path, age);
+    stored
+}
+
+fn backdate(dir: &Path, file: &Path, age: Duration) {
+    let mtime = SystemTime::now() - age;
+    File::options()
+        .write(true)
+        .open(file)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+    File::open(dir).unwrap().set_modified(mtime).unwrap();
+}
+
+#[test]
+fn gc_respects_references_and_budgets() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let home = tempdir()?;
+    unsafe { std::env::set_var("HOME", home.path()) };
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", home.path().join("cache")) };
+    let workspace = tempdir()?;
+    std::env::set_current_dir(workspace.path())?;
+
+    let store = ArtifactStore::open()?;
+    let kept = store_entry(&store, "artifact.wasm", 100, 90 * DAY);
+    let stale = store_entry(&store, "artifact.wasm", 200, 60 * DAY);
+    let fresh = store_entry(&store, "artifact.wasm", 300, Duration::ZERO);
+    let pack = store_entry(&store, "bundle.gtpack", 400, 10 * DAY);
+    write_manifest(workspace.path(), "kept", &kept.path);
+
+    // Caches written by older releases are listed and can be pruned.
+    let legacy = workspace.path().join(".greentic/components/old-1");
+    fs::create_dir_all(&legacy)?;
+    fs::write(legacy.join("artifact.wasm"), [0u8; 50])?;
+    backdate(&legacy, &legacy.join("artifact.wasm"), 120 * DAY);
+
+    let entries = list_entries()?;
+    assert_eq!(entries.len(), 5);
+    let referenced: Vec<_> = entries.iter().filter(|entry| entry.referenced).collect();
+    assert_eq!(referenced.len(), 1);
+    assert_eq!(referenced[0].path, kept.dir());
+    assert!(
+        entries
+            .iter()
+            .any(|entry| entry.kind == CacheKind::Pack && entry.path == pack.dir())
+    );
+    assert!(entries.iter().any(|entry| entry.kind == CacheKind::Legacy));
+
+    // Dry run reports without deleting.
+    let report = gc(
+        GcBudget {
+            max_age: Some(30 * DAY),
+            max_size: None,
+        },
+        true,
+    )?;
+    assert_eq!(report.removed.len(), 2);
+    assert!(stale.path.exists());
+
+    // Age budget: only unreferenced entries past 30 days go; the referenced one is older but kept.
+    let report = gc(
+        GcBudget {
+            max_age: Some(30 * DAY),
+            max_size: None,
+        },
+        false,
+    )?;
+    assert_eq!(report.freed, 201 + 50);
+    assert!(!stale.path.exists());
+    assert!(!legacy.exists());
+    assert!(kept.path.exists());
+
+    // Size budget: evict the oldest unreferenced entries until 803 bytes fit in 500.
+    let report = gc(
+        GcBudget {
+            max_age: None,
+            max_size: Some(500),
+        },
+        false,
+    )?;
+    assert_eq!(report.freed, 401);
+    assert_eq!(report.remaining, 402);
+    assert!(!pack.path.exists());
+    assert!(fresh.path.exists());
+
+    // The lockfile alone is enough to keep an entry.
+    fs::write(
+        workspace.path().join("greentic.lock"),
+        format!(
+            "version = 1\n\n[[component]]\nname = \"fresh\"\ncoordinate = \"component://demo/fresh@^1\"\nversion = \"1.0.0\"\ndigest = \"{}\"\nsize = 301\n",
+            fresh.digest
+        ),
+    )?;
+
+    // Once removed from the manifest, an unbounded gc prunes the component too.
+    run_component_remove("kept")?;
+    assert!(run_component_remove("kept").is_err());
+    let report = gc(GcBudget::default(), false)?;
+    assert_eq!(report.removed.len(), 1);
+    assert!(!kept.path.exists());
+    assert!(fresh.path.exists());
+    Ok(())
+}
+
+#[test]
+fn gc_keeps_entries_referenced_by_other_registered_workspaces() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let home = tempdir()?;
+    unsafe { std::env::set_var("HOME", home.path()) };
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", home.path().join("cache")) };
+    let first = tempdir()?;
+    let second = tempdir()?;
+
+    let store = ArtifactStore::open()?;
+    let by_path = store_entry(&store, "artifact.wasm", 100, 90 * DAY);
+    let by_digest = store_entry(&store, "artifact.wasm", 200, 90 * DAY);
+    let unused = store_entry(&store, "artifact.wasm", 300, 90 * DAY);
+
+    // The first workspace points at one entry by absolute path, the second pins one by digest.
+    write_manifest(first.path(), "by-path", &by_path.path);
+    register_workspace(first.path())?;
+    fs::write(
+        second.path().join("greentic.lock"),
+        format!(
+            "version = 1\n\n[[component]]\nname = \"by-digest\"\ncoordinate = \"component://demo/by-digest@^1\"\nversion = \"1.0.0\"\ndigest = \"{}\"\nsize = 201\n",
+            by_digest.digest
+        ),
+    )?;
+    register_workspace(second.path())?;
+    register_workspace(second.path())?;
+
+    // GC from an unrelated directory still honours both registered workspaces.
+    let elsewhere = tempdir()?;
+    std::env::set_current_dir(elsewhere.path())?;
+    let report = gc(GcBudget::default(), false)?;
+    assert_eq!(report.removed.len(), 1);
+    assert_eq!(report.removed[0].path, unused.dir());
+    assert!(by_path.path.exists());
+    assert!(by_digest.path.exists());
+
+    // A workspace that no longer exists stops protecting its entries.
+    fs::remove_dir_all(first.path().join(".greentic"))?;
+    let report = gc(GcBudget::default(), false)?;
+    assert_eq!(report.removed.len(), 1);
+    assert!(!by_path.path.exists());
+    assert!(by_digest.path.exists());
+    Ok(())
+}
+
+#[test]
+fn concurrent_registrations_are_all_kept() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let home = tempdir()?;
+    unsafe { std::env::set_var("HOME", home.path()) };
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", home.path().join("cache")) };
+    let workspaces = (0..8)
+        .map(|_| {
+            let workspace = tempdir()?;
+            fs::write(workspace.path().join("greentic.lock"), "version = 1\n")?;
+            Ok(workspace)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    std::thread::scope(|scope| {
+        for workspace in &workspaces {
+            scope.spawn(|| register_workspace(workspace.path()).unwrap());
+        }
+    });
+
+    let registry = fs::read_to_string(home.path().join("cache").join("workspaces.json"))?;
+    let registered: Vec<std::path::PathBuf> = serde_json::from_str(&registry)?;
+    assert_eq!(registered.len(), workspaces.len());
+    for workspace in &workspaces {
+        assert!(registered.contains(&workspace.path().canonicalize()?));
+    }
+    Ok(())
+}
+
+#[test]
+fn parses_size_and_age_budgets() {
+    assert_eq!(parse_size("4096").unwrap(), 4096);
+    assert_eq!(parse_size("500MB").unwrap(), 500_000_000);
+    assert_eq!(parse_size("2GiB").unwrap(), 2 << 30);
+    assert!(parse_size("10 parsecs").is_err());
+    assert_eq!(parse_age("30d").unwrap(), 30 * DAY);
+    assert_eq!(parse_age("12h").unwrap(), Duration::from_secs(12 * 60 * 60));
+    assert!(parse_age("soon").is_err());
+}
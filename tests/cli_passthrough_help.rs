@@ -147,3 +147,36 @@ fn runner_help_passthrough_via_pack_run() {
         &["RUNNER_HELP", "--pack", "--entry"],
     );
 }
+
+#[test]
+fn passthrough_resolves_config_tool_path_and_reports_source() {
+    let stubs = build_stubs();
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            "[tools.greentic-flow]\npath = \"{}\"\n",
+            stubs.flow.display().to_string().replace('\\', "\\\\")
+        ),
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-dev")
+        .env("GREENTIC_DEV_CONFIG_FILE", &config_path)
+        .env_remove("GREENTIC_DEV_BIN_GREENTIC_FLOW")
+        .args(["--verbose", "flow", "--help"])
+        .assert()
+        .success()
+        .stdout(contains("FLOW_HELP"))
+        .stderr(contains("(from config [tools.*].path)"));
+
+    cargo_bin_cmd!("greentic-dev")
+        .env("GREENTIC_DEV_CONFIG_FILE", &config_path)
+        .env("GREENTIC_DEV_BIN_GREENTIC_FLOW", &stubs.component)
+        .args(["--verbose", "flow", "--help"])
+        .assert()
+        .success()
+        .stdout(contains("COMPONENT_HELP"))
+        .stderr(contains("(from env GREENTIC_DEV_BIN_GREENTIC_FLOW)"));
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use greentic_dev::component_add::run_component_add;
+use greentic_dev::pack_init::{PackInitIntent, WorkspaceManifest};
+use httpmock::MockServer;
+use once_cell::sync::Lazy;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use tempfile::tempdir;
+
+static ENV_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+fn write_config(home: &Path, url: &str) {
+    let config_dir = home.join(".greentic");
+    fs::create_dir_all(&config_dir).unwrap();
+    let path = config_dir.join("config.toml");
+    fs::write(
+        &path,
+        format!(
+            r#"
+[distributor.default]
+url = "{url}"
+token = "env:GREENTIC_TEST_DISTRIBUTOR_TOKEN"
+"#
+        ),
+    )
+    .unwrap();
+    unsafe { std::env::set_var("GREENTIC_CONFIG", &path) };
+}
+
+/// A resolve answer without a download path: the artifact is only reachable by its handle.
+fn handle_response(digest: &str, handle: &str) -> Value {
+    json!({
+        "kind": "component",
+        "name": "handle-only",
+        "version": "1.2.0",
+        "coordinate": "component://greentic/handle-only@1.2.0",
+        "artifact_id": handle,
+        "digest": digest,
+        "license": {
+            "license_type": "free",
+            "id": null,
+            "requires_acceptance": false,
+            "checkout_url": null
+        },
+        "metadata": {}
+    })
+}
+
+#[test]
+fn component_add_downloads_distributor_handles_with_profile_token() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let home = tempdir()?;
+    unsafe { std::env::set_var("HOME", home.path()) };
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", home.path().join("cache")) };
+    unsafe { std::env::set_var("GREENTIC_TEST_DISTRIBUTOR_TOKEN", "handle-token") };
+    unsafe { std::env::remove_var("GREENTIC_DEV_OFFLINE") };
+    unsafe { std::env::remove_var("GREENTIC_DEV_RESOLVE_STUB") };
+    let workspace = tempdir()?;
+    std::env::set_current_dir(workspace.path())?;
+
+    let server = MockServer::start();
+    write_config(home.path(), &server.base_url());
+
+    let wasm = b"\0asm-served-by-handle";
+    let digest = format!("sha256:{:x}", Sha256::digest(wasm));
+    let handle = format!("artifacts/{digest}");
+    let mut resolve = server.mock(|when, then| {
+        when.method("POST")
+            .path("/v1/resolve")
+            .header("authorization", "Bearer handle-token");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(handle_response(&digest, &handle));
+    });
+    let download = server.mock(|when, then| {
+        when.method("GET")
+            .path(format!("/v1/artifact/{handle}"))
+            .header("authorization", "Bearer handle-token");
+        then.status(200).body(wasm);
+    });
+
+    let cache_dir = run_component_add(
+        "component://greentic/handle-only@1.2.0",
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )?;
+    resolve.assert();
+    download.assert();
+    assert_eq!(fs::read(cache_dir.join("artifact.wasm"))?, wasm);
+    let manifest = WorkspaceManifest::load(&workspace.path().join(".greentic/manifest.json"))?;
+    let component = manifest
+        .component("handle-only")
+        .expect("component recorded");
+    assert_eq!(component.entry.version.to_string(), "1.2.0");
+
+    // A handle the distributor refuses to serve surfaces the status instead of a bogus artifact.
+    resolve.delete();
+    server.mock(|when, then| {
+        when.method("POST").path("/v1/resolve");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(handle_response(&digest, "artifacts/expired"));
+    });
+    server.mock(|when, then| {
+        when.method("GET").path("/v1/artifact/artifacts/expired");
+        then.status(404).body("unknown handle");
+    });
+    fs::remove_dir_all(home.path().join("cache"))?;
+    let err = run_component_add(
+        "component://greentic/handle-only@1.2.0",
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )
+    .unwrap_err();
+    let message = format!("{err:#}");
+    assert!(
+        message.contains("artifacts/expired") && message.contains("404"),
+        "unexpected error: {message}"
+    );
+    Ok(())
+}
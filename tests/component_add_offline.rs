@@ -1,7 +1,9 @@
 use greentic_dev::component_add::run_component_add;
 use greentic_dev::pack_init::PackInitIntent;
 use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
 use std::fs;
+use std::path::Path;
 use std::sync::Mutex;
 
 static WORKDIR_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
@@ -14,6 +16,19 @@ fn remove_env(key: &str) {
     unsafe { std::env::remove_var(key) }
 }
 
+fn sha256(bytes: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(bytes))
+}
+
+fn write_stub(path: &Path, artifact: &Path, digest: &str) {
+    let stub = serde_json::json!({
+        "artifact_path": artifact.display().to_string(),
+        "digest": digest,
+        "version": "1.0.0"
+    });
+    fs::write(path, serde_json::to_string(&stub).unwrap()).unwrap();
+}
+
 #[test]
 fn component_add_uses_stub_when_offline_and_config_absent() {
     let tmp = tempfile::tempdir().unwrap();
@@ -23,7 +38,8 @@ fn component_add_uses_stub_when_offline_and_config_absent() {
 
     let stub = serde_json::json!({
         "artifact_path": artifact.display().to_string(),
-        "digest": "sha256:stub"
+        "digest": "sha256:stub",
+        "version": "1.0.0"
     });
     let stub_path = root.join("stub.json");
     fs::write(&stub_path, serde_json::to_string(&stub).unwrap()).unwrap();
@@ -36,6 +52,7 @@ fn component_add_uses_stub_when_offline_and_config_absent() {
     let prev_stub = std::env::var("GREENTIC_DEV_RESOLVE_STUB").ok();
     let prev_cfg = std::env::var("GREENTIC_DEV_CONFIG_FILE").ok();
     let prev_profile = std::env::var("GREENTIC_DISTRIBUTOR_PROFILE").ok();
+    let prev_cache = std::env::var("GREENTIC_DEV_CACHE_DIR").ok();
 
     set_env("GREENTIC_DEV_OFFLINE", "1");
     set_env(
@@ -44,11 +61,80 @@ fn component_add_uses_stub_when_offline_and_config_absent() {
     );
     remove_env("GREENTIC_DEV_CONFIG_FILE");
     remove_env("GREENTIC_DISTRIBUTOR_PROFILE");
+    set_env(
+        "GREENTIC_DEV_CACHE_DIR",
+        root.join("cache").to_string_lossy().as_ref(),
+    );
 
-    let cache_dir = run_component_add("component://greentic/example@^1", None, PackInitIntent::Dev)
-        .expect("stubbed resolve should succeed offline");
+    let cache_dir = run_component_add(
+        "component://greentic/example@^1",
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )
+    .expect("stubbed resolve should succeed offline");
     assert!(cache_dir.exists(), "cache dir should exist");
 
+    let manifest = fs::read_to_string(root.join(".greentic/manifest.json")).unwrap();
+    assert!(manifest.contains("\"version\": \"1.0.0\""), "{manifest}");
+    assert!(
+        !manifest.contains("\"hash_blake3\": \"\""),
+        "manifest should record the artifact hash: {manifest}"
+    );
+    let lock = fs::read_to_string(root.join("greentic.lock")).unwrap();
+    assert!(lock.contains("coordinate = \"component://greentic/example@^1\""));
+    assert!(lock.contains("size = 2"));
+
+    // --locked accepts an unchanged resolution...
+    run_component_add(
+        "component://greentic/example@^1",
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        true,
+    )
+    .expect("locked resolve should match the lockfile");
+
+    // A download that does not match the distributor digest never reaches the cache...
+    fs::write(&artifact, b"tampered").unwrap();
+    write_stub(&stub_path, &artifact, &sha256(b"expected"));
+    let err = run_component_add(
+        "component://greentic/example@^1",
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("digest mismatch"), "{err}");
+
+    // ...and a changed artifact for the same version is rejected by the lockfile.
+    write_stub(&stub_path, &artifact, &sha256(b"tampered"));
+    let err = run_component_add(
+        "component://greentic/example@^1",
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        true,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("greentic.lock"), "{err}");
+    let err = run_component_add(
+        "component://greentic/example@^1",
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("does not match"), "{err}");
+
     // restore env and cwd
     if let Some(val) = prev_offline {
         set_env("GREENTIC_DEV_OFFLINE", &val);
@@ -70,5 +156,10 @@ fn component_add_uses_stub_when_offline_and_config_absent() {
     } else {
         remove_env("GREENTIC_DISTRIBUTOR_PROFILE");
     }
+    if let Some(val) = prev_cache {
+        set_env("GREENTIC_DEV_CACHE_DIR", &val);
+    } else {
+        remove_env("GREENTIC_DEV_CACHE_DIR");
+    }
     std::env::set_current_dir(prev_dir).unwrap();
 }
@@ -0,0 +1,182 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use greentic_dev::component_update::{collect_outdated, run_update};
+use greentic_dev::pack_init::{PackInitIntent, run_component_add};
+use httpmock::MockServer;
+use once_cell::sync::Lazy;
+use serde_json::{Value, json};
+use std::sync::Mutex;
+use tempfile::tempdir;
+
+static ENV_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+const COORDINATE: &str = "component://greentic/component-llm-openai@^0.3";
+
+fn resolve_body(version: &str) -> Value {
+    json!({
+        "kind": "component",
+        "name": "component-llm-openai",
+        "version": version,
+        "coordinate": COORDINATE,
+        "artifact_id": format!("artifacts/sha256:{version}"),
+        "artifact_download_path": format!("/v1/artifact/artifacts/sha256:{version}"),
+        "digest": null,
+        "license": {
+            "license_type": "free",
+            "id": null,
+            "requires_acceptance": false,
+            "checkout_url": null
+        },
+        "metadata": {}
+    })
+}
+
+fn write_config(home: &Path, url: &str) {
+    let config_dir = home.join(".greentic");
+    fs::create_dir_all(&config_dir).unwrap();
+    let path = config_dir.join("config.toml");
+    fs::write(
+        &path,
+        format!(
+            r#"
+[distributor.default]
+url = "{url}"
+token = ""
+"#
+        ),
+    )
+    .unwrap();
+    unsafe { std::env::set_var("GREENTIC_CONFIG", &path) };
+}
+
+#[test]
+fn outdated_reports_compatible_and_latest_versions() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let temp_home = tempdir().unwrap();
+    unsafe { std::env::set_var("HOME", temp_home.path()) };
+    unsafe { std::env::remove_var("GREENTIC_DEV_OFFLINE") };
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", temp_home.path().join("cache")) };
+    let workspace = tempdir().unwrap();
+    std::env::set_current_dir(workspace.path()).unwrap();
+
+    let server = MockServer::start();
+    write_config(temp_home.path(), &server.base_url());
+
+    let mut initial = server.mock(|when, then| {
+        when.method("POST").path("/v1/resolve");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(resolve_body("0.3.2"));
+    });
+    server.mock(|when, then| {
+        when.method("GET")
+            .path("/v1/artifact/artifacts/sha256:0.3.2");
+        then.status(200).body("wasm-bytes");
+    });
+    run_component_add(COORDINATE, None, PackInitIntent::Dev)?;
+    initial.delete();
+
+    let compatible = server.mock(|when, then| {
+        when.method("POST")
+            .path("/v1/resolve")
+            .body_includes("@^0.3");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(resolve_body("0.3.5"));
+    });
+    let latest = server.mock(|when, then| {
+        when.method("POST")
+            .path("/v1/resolve")
+            .body_excludes("@^0.3");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(resolve_body("1.1.0"));
+    });
+
+    let entries = collect_outdated(None, &[])?;
+    compatible.assert();
+    latest.assert();
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!(entry.name, "component-llm-openai");
+    assert_eq!(entry.current, "0.3.2");
+    assert_eq!(entry.compatible, "0.3.5");
+    assert_eq!(entry.latest, "1.1.0");
+    assert!(entry.is_outdated());
+
+    let err = run_update(Some("missing-component"), None, &[], &[]).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("not recorded in the workspace manifest"),
+        "unexpected error: {err:#}"
+    );
+    Ok(())
+}
+
+#[test]
+fn outdated_honours_header_overrides_and_resolve_stub() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let temp_home = tempdir().unwrap();
+    unsafe { std::env::set_var("HOME", temp_home.path()) };
+    unsafe { std::env::remove_var("GREENTIC_DEV_OFFLINE") };
+    unsafe { std::env::remove_var("GREENTIC_DEV_RESOLVE_STUB") };
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", temp_home.path().join("cache")) };
+    let workspace = tempdir().unwrap();
+    std::env::set_current_dir(workspace.path()).unwrap();
+
+    let server = MockServer::start();
+    write_config(temp_home.path(), &server.base_url());
+    let mut initial = server.mock(|when, then| {
+        when.method("POST").path("/v1/resolve");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(resolve_body("0.3.2"));
+    });
+    server.mock(|when, then| {
+        when.method("GET")
+            .path("/v1/artifact/artifacts/sha256:0.3.2");
+        then.status(200).body("wasm-bytes");
+    });
+    run_component_add(COORDINATE, None, PackInitIntent::Dev)?;
+    initial.delete();
+
+    let with_header = server.mock(|when, then| {
+        when.method("POST")
+            .path("/v1/resolve")
+            .header("x-greentic-channel", "beta");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(resolve_body("0.3.4"));
+    });
+    let entries = collect_outdated(None, &[("X-Greentic-Channel".into(), "beta".into())])?;
+    with_header.assert_calls(2);
+    assert_eq!(entries[0].compatible, "0.3.4");
+    assert_eq!(entries[0].profile, "default");
+
+    // Offline runs answer from the resolve stub instead of failing.
+    let stub = workspace.path().join("stub.json");
+    fs::write(&stub, resolve_body("0.3.9").to_string())?;
+    unsafe { std::env::set_var("GREENTIC_DEV_RESOLVE_STUB", &stub) };
+    unsafe { std::env::set_var("GREENTIC_DEV_OFFLINE", "1") };
+    let entries = collect_outdated(None, &[]);
+    unsafe { std::env::remove_var("GREENTIC_DEV_RESOLVE_STUB") };
+    unsafe { std::env::remove_var("GREENTIC_DEV_OFFLINE") };
+    let entries = entries?;
+    assert_eq!(entries[0].compatible, "0.3.9");
+    assert_eq!(entries[0].latest, "0.3.9");
+    assert_eq!(entries[0].profile, "stub");
+    with_header.assert_calls(2);
+    Ok(())
+}
@@ -166,6 +166,93 @@ environment_id = "valid-env"
     Ok(())
 }
 
+#[test]
+fn project_config_is_merged_over_user_config() -> Result<()> {
+    let _guard = ENV_GUARD.lock().unwrap();
+    let env = TestEnv::new()?;
+    let user_path = env.write_xdg_config(
+        r#"
+[distributor.profiles.default]
+base_url = "https://user.example"
+tenant_id = "user-tenant"
+headers = { x-region = "eu" }
+
+[defaults.component]
+template = "rust-wasi"
+"#,
+    );
+    let workspace = env.home.join("repo");
+    let project_path = workspace.join(".greentic/config.toml");
+    fs::create_dir_all(project_path.parent().unwrap())?;
+    fs::write(
+        &project_path,
+        r#"
+[distributor.profiles.default]
+tenant_id = "team"
+headers = { x-team = "core" }
+
+[defaults.component]
+org = "ai.greentic.team"
+"#,
+    )?;
+    let nested = workspace.join("crates/app");
+    fs::create_dir_all(&nested)?;
+    let previous_dir = std::env::current_dir()?;
+    std::env::set_current_dir(&nested)?;
+
+    let loaded = config::load_with_meta(None);
+    std::env::set_current_dir(&previous_dir)?;
+    let loaded = loaded?;
+    assert_eq!(loaded.loaded_from.as_deref(), Some(user_path.as_path()));
+    assert_eq!(loaded.project_path.as_deref(), Some(project_path.as_path()));
+    assert!(loaded.project_sets(&["distributor", "profiles", "default", "tenant_id"]));
+    assert!(!loaded.project_sets(&["distributor", "profiles", "default", "base_url"]));
+
+    let profile = distributor::resolve_profile(&loaded, None)?;
+    assert_eq!(profile.url, "https://user.example");
+    assert_eq!(profile.tenant_id, "team");
+    let headers = profile.headers.unwrap();
+    assert_eq!(headers["x-region"], "eu");
+    assert_eq!(headers["x-team"], "core");
+    let defaults = &loaded.config.defaults.component;
+    assert_eq!(defaults.org.as_deref(), Some("ai.greentic.team"));
+    assert_eq!(defaults.template.as_deref(), Some("rust-wasi"));
+
+    let output = assert_cmd::cargo::cargo_bin_cmd!("greentic-dev")
+        .args(["config", "show", "--resolved"])
+        .current_dir(&nested)
+        .env(
+            "GREENTIC_DEV_CREDENTIALS_FILE",
+            env.home.join("credentials.json"),
+        )
+        .output()?;
+    assert!(output.status.success());
+    let resolved = String::from_utf8(output.stdout)?;
+    let line = |key: &str| {
+        resolved
+            .lines()
+            .find(|line| line.starts_with(key))
+            .unwrap_or_else(|| panic!("missing {key} in {resolved}"))
+            .to_string()
+    };
+    assert!(
+        line("distributor.profiles.default.tenant_id")
+            .ends_with(&project_path.display().to_string())
+    );
+    assert!(
+        line("distributor.profiles.default.base_url").ends_with(&user_path.display().to_string())
+    );
+    assert!(line("defaults.component.org").ends_with(&project_path.display().to_string()));
+
+    // The home directory's own .greentic/config.toml is a user config, never a project layer.
+    env.write_legacy_config("[defaults.component]\norg = \"legacy\"\n");
+    std::env::set_current_dir(&env.home)?;
+    let loaded = config::load_with_meta(None);
+    std::env::set_current_dir(&previous_dir)?;
+    assert_eq!(loaded?.project_path, None);
+    Ok(())
+}
+
 struct TestEnv {
     _temp: TempDir,
     home: PathBuf,
@@ -0,0 +1,152 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_dev::config;
+use greentic_dev::config_check::{self, Severity};
+use tempfile::tempdir;
+use toml_edit::DocumentMut;
+
+const LEGACY: &str = r#"# greentic-dev settings
+
+[distributor]
+default_profile = "staging"
+
+# staging distributor, shared with the team
+[distributor.staging]
+# moved to the new host last week
+url = "https://staging.example"
+tenant_id = "acme"
+
+[profiles.prod]
+url = "https://prod.example" # keep in sync with ops
+token = "env:GREENTIC_CONFIG_VALIDATE_UNSET_TOKEN"
+"#;
+
+#[test]
+fn committed_schema_matches_config_types() {
+    let committed: serde_json::Value =
+        serde_json::from_str(include_str!("../schemas/greentic-dev.config.schema.json"))
+            .expect("committed schema is JSON");
+    assert_eq!(
+        committed,
+        config::schema(),
+        "regenerate with `greentic-dev config schema > schemas/greentic-dev.config.schema.json`"
+    );
+}
+
+#[test]
+fn validate_flags_unknown_keys_deprecated_aliases_and_unset_env_tokens() -> Result<()> {
+    let mut table: toml::Table = toml::from_str(LEGACY)?;
+    table.insert("colour".into(), toml::Value::String("blue".into()));
+    let findings = config_check::validate_table(&table);
+    let keys = |severity| {
+        findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .map(|finding| finding.key.as_str())
+            .collect::<Vec<_>>()
+    };
+
+    let errors = keys(Severity::Error);
+    assert!(errors.contains(&"(root)"), "{findings:#?}");
+    assert!(errors.contains(&"profiles.prod.token"), "{findings:#?}");
+    let warnings = keys(Severity::Warning);
+    for key in [
+        "distributor.staging",
+        "distributor.staging.url",
+        "profiles.prod",
+        "profiles.prod.url",
+    ] {
+        assert!(warnings.contains(&key), "missing {key}: {findings:#?}");
+    }
+
+    let clean: toml::Table = toml::from_str(
+        r#"
+[distributor.profiles.default]
+base_url = "https://example"
+token = "keyring:greentic-dev/default"
+"#,
+    )?;
+    assert_eq!(config_check::validate_table(&clean), Vec::new());
+    Ok(())
+}
+
+#[test]
+fn migrate_moves_legacy_profiles_and_keeps_comments() -> Result<()> {
+    let mut doc = LEGACY.parse::<DocumentMut>()?;
+    let changes = config_check::migrate_document(&mut doc)?;
+    assert_eq!(changes.len(), 4, "{changes:#?}");
+    let migrated = doc.to_string();
+
+    for expected in [
+        "# greentic-dev settings",
+        "# staging distributor, shared with the team\n[distributor.profiles.staging]",
+        "# moved to the new host last week\nbase_url = \"https://staging.example\"",
+        "[distributor.profiles.prod]",
+        "base_url = \"https://prod.example\" # keep in sync with ops",
+    ] {
+        assert!(
+            migrated.contains(expected),
+            "missing {expected:?}:\n{migrated}"
+        );
+    }
+    assert!(!migrated.contains("[profiles"), "{migrated}");
+
+    let table: toml::Table = toml::from_str(&migrated)?;
+    let warnings = config_check::validate_table(&table)
+        .into_iter()
+        .filter(|finding| finding.severity == Severity::Warning)
+        .collect::<Vec<_>>();
+    assert_eq!(warnings, Vec::new(), "{migrated}");
+
+    let mut again = migrated.parse::<DocumentMut>()?;
+    assert_eq!(
+        config_check::migrate_document(&mut again)?,
+        Vec::<String>::new()
+    );
+    Ok(())
+}
+
+#[test]
+fn config_validate_and_migrate_commands() -> Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join("config.toml");
+    fs::write(&path, LEGACY)?;
+    let greentic_dev = || {
+        let mut cmd = cargo_bin_cmd!("greentic-dev");
+        cmd.env("GREENTIC_DEV_CONFIG_FILE", &path)
+            .env_remove("GREENTIC_CONFIG_VALIDATE_UNSET_TOKEN")
+            .current_dir(dir.path());
+        cmd
+    };
+
+    let output = greentic_dev().args(["config", "validate"]).output()?;
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(
+        stdout.contains(
+            "error: profiles.prod.token: env var GREENTIC_CONFIG_VALIDATE_UNSET_TOKEN is not set"
+        ),
+        "{stdout}"
+    );
+
+    let dry_run = greentic_dev()
+        .args(["config", "migrate", "--dry-run"])
+        .output()?;
+    assert!(dry_run.status.success());
+    assert!(String::from_utf8(dry_run.stdout)?.contains("[distributor.profiles.staging]"));
+    assert_eq!(fs::read_to_string(&path)?, LEGACY);
+
+    greentic_dev()
+        .args(["config", "migrate"])
+        .assert()
+        .success();
+    assert!(fs::read_to_string(&path)?.contains("[distributor.profiles.prod]"));
+    greentic_dev()
+        .args(["config", "validate"])
+        .env("GREENTIC_CONFIG_VALIDATE_UNSET_TOKEN", "set")
+        .assert()
+        .success();
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_dev::config;
+use greentic_dev::credentials;
+use greentic_dev::distributor;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use tempfile::tempdir;
+
+static ENV_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+const CONFIG: &str = r#"
+[distributor.profiles.staging]
+base_url = "https://staging.example"
+
+[distributor.profiles.files]
+base_url = "https://files.example"
+token = "file:TOKEN_PATH"
+"#;
+
+#[test]
+fn login_stores_encrypted_token_used_by_profiles() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    // Keep the key in the sibling `.key` file rather than the keyring of the machine running tests.
+    unsafe { std::env::set_var("GREENTIC_DEV_KEYRING", "0") };
+    let home = tempdir()?;
+    let config_path = home.path().join("config.toml");
+    let token_path = home.path().join("token.txt");
+    fs::write(&token_path, "from-file\n")?;
+    fs::write(
+        &config_path,
+        CONFIG.replace("TOKEN_PATH", &token_path.display().to_string()),
+    )?;
+    let credentials_path = home.path().join("credentials.json");
+    let greentic_dev = || {
+        let mut cmd = cargo_bin_cmd!("greentic-dev");
+        cmd.env("GREENTIC_DEV_CONFIG_FILE", &config_path)
+            .env("GREENTIC_DEV_CREDENTIALS_FILE", &credentials_path)
+            .env("GREENTIC_DEV_KEYRING", "0")
+            .env_remove("GREENTIC_DISTRIBUTOR_PROFILE");
+        cmd
+    };
+
+    greentic_dev()
+        .args(["login", "--profile", "staging"])
+        .write_stdin("st4ging-secret\n")
+        .assert()
+        .success();
+    let stored = fs::read_to_string(&credentials_path)?;
+    assert!(stored.contains("staging"));
+    assert!(!stored.contains("st4ging-secret"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        for path in [&credentials_path, &credentials_path.with_extension("key")] {
+            assert_eq!(fs::metadata(path)?.permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+    let shown = greentic_dev().args(["config", "show"]).output()?;
+    assert!(shown.status.success());
+    let shown = String::from_utf8(shown.stdout)?;
+    assert!(shown.contains("# staging = <redacted>"), "{shown}");
+    assert!(!shown.contains("st4ging-secret"));
+
+    // The stored token backs profiles without a `token`; `file:` references are read as-is.
+    unsafe { std::env::set_var("GREENTIC_DEV_CREDENTIALS_FILE", &credentials_path) };
+    let loaded = config::load_with_meta(Some(config_path.to_str().unwrap()))?;
+    let staging = distributor::resolve_profile(&loaded, Some("staging"))?;
+    assert_eq!(staging.token.as_deref(), Some("st4ging-secret"));
+    let files = distributor::resolve_profile(&loaded, Some("files"))?;
+    assert_eq!(files.token.as_deref(), Some("from-file"));
+    assert_eq!(
+        credentials::stored_profiles()?
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![(
+            "staging".to_string(),
+            Some("https://staging.example".to_string())
+        )]
+    );
+
+    // A config pointing the profile at another distributor does not get the stored token.
+    let moved_path = home.path().join("moved.toml");
+    fs::write(
+        &moved_path,
+        "[distributor.profiles.staging]\nbase_url = \"https://attacker.example\"\n",
+    )?;
+    let moved = config::load_with_meta(Some(moved_path.to_str().unwrap()))?;
+    let staging_moved = distributor::resolve_profile(&moved, Some("staging"))?;
+    assert_eq!(staging_moved.token, None);
+    let resolved = greentic_dev()
+        .env("GREENTIC_DEV_CONFIG_FILE", &moved_path)
+        .args(["config", "show", "--resolved"])
+        .output()?;
+    assert!(resolved.status.success());
+    assert!(!String::from_utf8(resolved.stdout)?.contains("staging.token"));
+
+    greentic_dev()
+        .args(["logout", "--profile", "staging"])
+        .assert()
+        .success();
+    let staging = distributor::resolve_profile(&loaded, Some("staging"))?;
+    assert_eq!(staging.token, None);
+
+    let missing = greentic_dev()
+        .args(["login", "--profile", "nope"])
+        .write_stdin("x\n")
+        .output()?;
+    assert!(!missing.status.success());
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn credentials_key_lives_in_the_keyring_when_available() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let _guard = ENV_LOCK.lock().unwrap();
+    let home = tempdir()?;
+    // Stand-in `secret-tool` keeping one secret per account in a directory.
+    let bin = home.path().join("bin");
+    let secrets = home.path().join("secrets");
+    fs::create_dir_all(&bin)?;
+    fs::create_dir_all(&secrets)?;
+    let secret_tool = bin.join("secret-tool");
+    fs::write(
+        &secret_tool,
+        format!(
+            "#!/bin/sh
+entry=\"{}/$(echo \"$*\" | sed 's/.*account //' | tr '/:' '__')\"\ncase \"$1\" in\n  store) cat > \"$entry\" ;;\n  lookup) cat \"$entry\" ;;\n  *) exit 2 ;;\nesac\n",
+            secrets.display()
+        ),
+    )?;
+    fs::set_permissions(&secret_tool, fs::Permissions::from_mode(0o755))?;
+
+    let credentials_path = home.path().join("credentials.json");
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.clone()];
+    paths.extend(std::env::split_paths(&path));
+    unsafe { std::env::set_var("PATH", std::env::join_paths(paths)?) };
+    unsafe { std::env::set_var("GREENTIC_DEV_CREDENTIALS_FILE", &credentials_path) };
+    unsafe { std::env::remove_var("GREENTIC_DEV_KEYRING") };
+
+    let stored = credentials::store_token("staging", "https://staging.example", "st4ging-secret");
+    let token = credentials::stored_token("staging", "https://staging.example");
+    unsafe { std::env::set_var("PATH", path) };
+    stored?;
+    assert_eq!(token?.as_deref(), Some("st4ging-secret"));
+    assert!(!credentials_path.with_extension("key").exists());
+    assert_eq!(fs::read_dir(&secrets)?.count(), 1);
+    Ok(())
+}
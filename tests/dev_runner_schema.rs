@@ -9,3 +9,22 @@ fn schema_validation_passes_for_examples() {
     let schema = r#"{"type":"object"}"#; // placeholder schema
     assert!(dev_runner::schema::validate_yaml_against_schema(&doc, schema).is_ok());
 }
+
+#[test]
+fn flow_yaml_errors_carry_a_code_and_source_span() {
+    let validator = dev_runner::FlowValidator::new(
+        dev_runner::StaticComponentDescriber::new(),
+        dev_runner::DescribeRegistry::new(),
+    );
+    let err = validator
+        .validate_str("nodes:\n  - component: [unclosed\n")
+        .unwrap_err();
+    let diagnostic = err.diagnostic();
+    assert_eq!(diagnostic.code, "GD0202");
+    let span = diagnostic.span.expect("YAML errors have a location");
+    assert_eq!(span.line, 3);
+    assert!(err.to_string().starts_with("flow is not valid YAML"));
+
+    let err = validator.validate_str("name: demo\n").unwrap_err();
+    assert_eq!(err.diagnostic().code, "GD0203");
+}
@@ -0,0 +1,34 @@
+use std::fs;
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::str::contains;
+use tempfile::tempdir;
+
+#[test]
+fn config_syntax_errors_render_code_span_and_explain_hint() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("config.toml");
+    fs::write(&config_path, "[tools\n").unwrap();
+
+    cargo_bin_cmd!("greentic-dev")
+        .current_dir(dir.path())
+        .env("GREENTIC_DEV_CONFIG_FILE", &config_path)
+        .args(["config", "list"])
+        .assert()
+        .code(1)
+        .stderr(contains("error[GD0503]: failed to parse config"))
+        .stderr(contains("config.toml:1:7"))
+        .stderr(contains("1 | [tools"))
+        .stderr(contains("greentic-dev explain GD0503"));
+
+    cargo_bin_cmd!("greentic-dev")
+        .args(["explain", "GD0503"])
+        .assert()
+        .success()
+        .stdout(contains("GD0503: config is not valid TOML"));
+    cargo_bin_cmd!("greentic-dev")
+        .args(["explain", "GD9999"])
+        .assert()
+        .code(1)
+        .stderr(contains("error[GD0001]: unknown code `GD9999`"));
+}
@@ -7,11 +7,16 @@ use greentic_dev::pack_init::{PackInitIntent, run, run_component_add};
 use httpmock::MockServer;
 use once_cell::sync::Lazy;
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use std::sync::Mutex;
 use tempfile::tempdir;
 
 static ENV_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
 
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
 fn write_config(base: &Path, url: &str) -> PathBuf {
     let config_dir = base.join(".greentic");
     fs::create_dir_all(&config_dir).unwrap();
@@ -42,11 +47,14 @@ fn distributor_component_and_pack_flows() -> Result<()> {
     {
         let temp_home = tempdir().unwrap();
         unsafe { std::env::set_var("HOME", temp_home.path()) };
+        let cache_root = temp_home.path().join("cache");
+        unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", &cache_root) };
         let config_path = write_config(&PathBuf::from(temp_home.path()), "http://localhost:5000");
         unsafe { std::env::set_var("GREENTIC_CONFIG", &config_path) };
         let workspace = tempdir().unwrap();
         std::env::set_current_dir(workspace.path()).unwrap();
 
+        let digest_hex = sha256_hex(b"wasm-bytes");
         let server = MockServer::start();
         let resolve_body = json!({
             "kind": "component",
@@ -55,7 +63,7 @@ fn distributor_component_and_pack_flows() -> Result<()> {
             "coordinate": "component://greentic/component-llm-openai@^0.3",
             "artifact_id": "artifacts/sha256:abc123",
             "artifact_download_path": "/v1/artifact/artifacts/sha256:abc123",
-            "digest": "sha256:abc123",
+            "digest": format!("sha256:{digest_hex}"),
             "license": {
                 "license_type": "free",
                 "id": null,
@@ -70,7 +78,7 @@ fn distributor_component_and_pack_flows() -> Result<()> {
                 .header("content-type", "application/json")
                 .json_body(resolve_body.clone());
         });
-        server.mock(|when, then| {
+        let download = server.mock(|when, then| {
             when.method("GET")
                 .path("/v1/artifact/artifacts/sha256:abc123");
             then.status(200).body("wasm-bytes");
@@ -103,21 +111,58 @@ token = ""
             "manifest should include component entry"
         );
 
-        let cache_path = temp_home
-            .path()
-            .join(".greentic/cache/components/sha256-abc123/artifact.wasm");
+        let cache_path = cache_root
+            .join("sha256")
+            .join(&digest_hex)
+            .join("artifact.wasm");
         assert!(cache_path.exists(), "cached artifact should exist");
+        assert!(
+            manifest_raw
+                .contains("\"file_wasm\": \".greentic/artifacts/component-llm-openai.wasm\""),
+            "manifest should point at the workspace link: {manifest_raw}"
+        );
+        let linked = workspace
+            .path()
+            .join(".greentic/artifacts/component-llm-openai.wasm");
+        assert_eq!(fs::read(&linked).unwrap(), fs::read(&cache_path).unwrap());
+
+        // A second workspace reuses the cached artifact instead of downloading it again.
+        let second = tempdir().unwrap();
+        std::env::set_current_dir(second.path()).unwrap();
+        run_component_add(
+            "component://greentic/component-llm-openai@^0.3",
+            None,
+            PackInitIntent::Dev,
+        )
+        .unwrap();
+        download.assert_calls(1);
+
+        // Clearing the shared store leaves the workspace copies intact.
+        fs::remove_dir_all(cache_path.parent().unwrap()).unwrap();
+        assert!(linked.exists());
     }
 
     // pack init scenario
     {
         let temp_home = tempdir().unwrap();
         unsafe { std::env::set_var("HOME", temp_home.path()) };
+        unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", temp_home.path().join("cache")) };
         let config_path = write_config(&PathBuf::from(temp_home.path()), "http://localhost:5000");
         unsafe { std::env::set_var("GREENTIC_CONFIG", &config_path) };
         let workspace = tempdir().unwrap();
         std::env::set_current_dir(workspace.path()).unwrap();
 
+        let mut data: Vec<u8> = Vec::new();
+        {
+            let cursor = std::io::Cursor::new(&mut data);
+            let mut zip = zip::ZipWriter::new(cursor);
+            let opts = zip::write::FileOptions::<()>::default();
+            zip.add_directory("flows/", opts).unwrap();
+            zip.start_file("flows/demo/flow.ygtc", opts).unwrap();
+            zip.write_all(b"flow").unwrap();
+            zip.finish().unwrap();
+        }
+
         let server = MockServer::start();
         let resolve_body = json!({
             "kind": "pack",
@@ -126,7 +171,7 @@ token = ""
             "coordinate": "pack://org/demo-pack@1.0.0",
             "artifact_id": "artifacts/sha256:pack123",
             "artifact_download_path": "/v1/artifact/artifacts/sha256:pack123",
-            "digest": "sha256:pack123",
+            "digest": format!("sha256:{}", sha256_hex(&data)),
             "license": {
                 "license_type": "free",
                 "id": null,
@@ -142,17 +187,6 @@ token = ""
                 .json_body(resolve_body.clone());
         });
 
-        let mut data: Vec<u8> = Vec::new();
-        {
-            let cursor = std::io::Cursor::new(&mut data);
-            let mut zip = zip::ZipWriter::new(cursor);
-            let opts = zip::write::FileOptions::<()>::default();
-            zip.add_directory("flows/", opts).unwrap();
-            zip.start_file("flows/demo/flow.ygtc", opts).unwrap();
-            zip.write_all(b"flow").unwrap();
-            zip.finish().unwrap();
-        }
-
         server.mock(|when, then| {
             when.method("GET")
                 .path("/v1/artifact/artifacts/sha256:pack123");
@@ -186,3 +220,120 @@ token = ""
     }
     Ok(())
 }
+
+#[test]
+fn download_digest_mismatch_is_rejected() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let temp_home = tempdir().unwrap();
+    unsafe { std::env::set_var("HOME", temp_home.path()) };
+    let cache_root = temp_home.path().join("cache");
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", &cache_root) };
+    let workspace = tempdir().unwrap();
+    std::env::set_current_dir(workspace.path()).unwrap();
+
+    let server = MockServer::start();
+    let config_path = write_config(&PathBuf::from(temp_home.path()), &server.base_url());
+    unsafe { std::env::set_var("GREENTIC_CONFIG", &config_path) };
+    server.mock(|when, then| {
+        when.method("POST").path("/v1/resolve");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(json!({
+                "kind": "component",
+                "name": "component-llm-openai",
+                "version": "0.3.2",
+                "coordinate": "component://greentic/component-llm-openai@^0.3",
+                "artifact_id": "artifacts/sha256:abc123",
+                "artifact_download_path": "/v1/artifact/artifacts/sha256:abc123",
+                "digest": format!("sha256:{}", sha256_hex(b"expected-bytes")),
+                "license": {
+                    "license_type": "free",
+                    "id": null,
+                    "requires_acceptance": false,
+                    "checkout_url": null
+                },
+                "metadata": {}
+            }));
+    });
+    server.mock(|when, then| {
+        when.method("GET")
+            .path("/v1/artifact/artifacts/sha256:abc123");
+        then.status(200).body("tampered-bytes");
+    });
+
+    let err = run_component_add(
+        "component://greentic/component-llm-openai@^0.3",
+        None,
+        PackInitIntent::Dev,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("digest mismatch"), "{err:#}");
+    assert!(
+        !workspace.path().join(".greentic/manifest.json").exists(),
+        "manifest must not record an unverified artifact"
+    );
+    assert!(
+        !cache_root
+            .join("sha256")
+            .join(sha256_hex(b"tampered-bytes"))
+            .exists()
+    );
+    Ok(())
+}
+
+#[test]
+fn pack_init_uses_resolve_stub_offline() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let temp_home = tempdir().unwrap();
+    unsafe { std::env::set_var("HOME", temp_home.path()) };
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", temp_home.path().join("cache")) };
+    let workspace = tempdir().unwrap();
+    std::env::set_current_dir(workspace.path()).unwrap();
+
+    let mut data: Vec<u8> = Vec::new();
+    {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(&mut data));
+        let opts = zip::write::FileOptions::<()>::default();
+        zip.start_file("flows/demo/flow.ygtc", opts).unwrap();
+        zip.write_all(b"flow").unwrap();
+        zip.finish().unwrap();
+    }
+    let bundle = temp_home.path().join("demo.gtpack");
+    fs::write(&bundle, &data)?;
+    let stub = temp_home.path().join("stub.json");
+    fs::write(
+        &stub,
+        json!({
+            "artifact_path": bundle.display().to_string(),
+            "digest": format!("sha256:{}", sha256_hex(&data)),
+            "name": "stubbed-pack",
+            "version": "0.1.0"
+        })
+        .to_string(),
+    )?;
+    unsafe { std::env::set_var("GREENTIC_DEV_OFFLINE", "1") };
+    unsafe { std::env::set_var("GREENTIC_DEV_RESOLVE_STUB", &stub) };
+
+    let result = run("pack://org/stubbed-pack@0.1.0", None);
+    unsafe { std::env::remove_var("GREENTIC_DEV_RESOLVE_STUB") };
+    let offline_err = run("pack://org/other-pack@0.1.0", None).unwrap_err();
+    unsafe { std::env::remove_var("GREENTIC_DEV_OFFLINE") };
+
+    result?;
+    let dest = workspace.path().join("stubbed-pack");
+    assert_eq!(
+        fs::read_to_string(dest.join("flows/demo/flow.ygtc"))?,
+        "flow"
+    );
+    assert!(dest.join("bundle.gtpack").exists());
+    assert!(
+        format!("{offline_err:#}").contains("GREENTIC_DEV_RESOLVE_STUB"),
+        "unexpected error: {offline_err:#}"
+    );
+    Ok(())
+}
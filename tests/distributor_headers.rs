@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::fs;
+
+use anyhow::Result;
+use greentic_dev::artifact_store::ArtifactStore;
+use greentic_dev::component_add::run_component_add;
+use greentic_dev::distributor::{
+    DevDistributorClient, DevIntent, DevResolveRequest, Distributor, DistributorProfile,
+    parse_header,
+};
+use greentic_dev::pack_init::PackInitIntent;
+use httpmock::MockServer;
+use once_cell::sync::Lazy;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use tempfile::tempdir;
+
+static ENV_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[test]
+fn parse_header_accepts_colon_and_equals_forms() {
+    assert_eq!(
+        parse_header("X-Gateway: edge").unwrap(),
+        ("X-Gateway".to_string(), "edge".to_string())
+    );
+    assert_eq!(
+        parse_header("x-trace=a:b").unwrap(),
+        ("x-trace".to_string(), "a:b".to_string())
+    );
+    assert!(parse_header("no-separator").is_err());
+    assert!(parse_header("bad name: value").is_err());
+}
+
+#[test]
+fn dev_client_sends_tenant_environment_and_profile_headers() -> Result<()> {
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let server = MockServer::start();
+    let wasm = b"\0asm-tenant";
+    let digest = format!("sha256:{:x}", Sha256::digest(wasm));
+    let resolve = server.mock(|when, then| {
+        when.method("POST")
+            .path("/v1/resolve")
+            .header("x-greentic-tenant-id", "acme")
+            .header("x-greentic-environment-id", "staging")
+            .header("x-gateway", "override");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(json!({
+                "kind": "component",
+                "name": "echo",
+                "version": "1.0.0",
+                "coordinate": "component://acme/echo@1.0.0",
+                "artifact_id": "artifacts/echo",
+                "artifact_download_path": "/v1/artifact/artifacts/echo",
+                "digest": digest,
+                "license": {
+                    "license_type": "free",
+                    "id": null,
+                    "requires_acceptance": false,
+                    "checkout_url": null
+                },
+                "metadata": {}
+            }));
+    });
+    let download = server.mock(|when, then| {
+        when.method("GET")
+            .path("/v1/artifact/artifacts/echo")
+            .header("x-greentic-tenant-id", "acme")
+            .header("x-greentic-environment-id", "staging")
+            .header("x-gateway", "override");
+        then.status(200).body(wasm);
+    });
+
+    let profile = DistributorProfile {
+        name: "gateway".into(),
+        url: server.base_url(),
+        token: None,
+        tenant_id: "acme".into(),
+        environment_id: "staging".into(),
+        headers: Some(HashMap::from([("X-Gateway".into(), "edge".into())])),
+    }
+    .with_header_overrides(&[("x-gateway".into(), "override".into())]);
+    let client = DevDistributorClient::from_profile(profile)?;
+    let resolved = client.resolve(&DevResolveRequest {
+        coordinate: "component://acme/echo@1.0.0".into(),
+        intent: DevIntent::Dev,
+        platform: None,
+        features: Vec::new(),
+    })?;
+    let cache = tempdir()?;
+    let store = ArtifactStore::at(cache.path());
+    let stored = client.download_artifact(
+        &resolved.artifact_download_path,
+        Some(&digest),
+        &store,
+        "artifact.wasm",
+    )?;
+    resolve.assert();
+    download.assert();
+    assert_eq!(fs::read(stored.path)?, wasm);
+    Ok(())
+}
+
+#[test]
+fn component_add_applies_profile_headers_and_cli_overrides() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let home = tempdir()?;
+    unsafe { std::env::set_var("HOME", home.path()) };
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", home.path().join("cache")) };
+    unsafe { std::env::remove_var("GREENTIC_DEV_OFFLINE") };
+    unsafe { std::env::remove_var("GREENTIC_DEV_RESOLVE_STUB") };
+    let workspace = tempdir()?;
+    std::env::set_current_dir(workspace.path())?;
+
+    let server = MockServer::start();
+    let config_dir = home.path().join(".greentic");
+    fs::create_dir_all(&config_dir)?;
+    let config_path = config_dir.join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+[distributor.default]
+url = "{}"
+tenant_id = "acme"
+environment_id = "staging"
+headers = {{ "x-gateway" = "edge", "x-region" = "eu" }}
+"#,
+            server.base_url()
+        ),
+    )?;
+    unsafe { std::env::set_var("GREENTIC_CONFIG", &config_path) };
+
+    let wasm = b"\0asm-gateway";
+    let digest = format!("sha256:{:x}", Sha256::digest(wasm));
+    let handle = "artifacts/gateway";
+    let response = json!({
+        "kind": "component",
+        "name": "gateway",
+        "version": "1.0.0",
+        "coordinate": "component://acme/gateway@1.0.0",
+        "artifact_id": handle,
+        "digest": digest,
+        "license": {
+            "license_type": "free",
+            "id": null,
+            "requires_acceptance": false,
+            "checkout_url": null
+        },
+        "metadata": {}
+    });
+    let resolve = server.mock(|when, then| {
+        when.method("POST")
+            .path("/v1/resolve")
+            .header("x-greentic-tenant-id", "acme")
+            .header("x-greentic-environment-id", "staging")
+            .header("x-gateway", "canary")
+            .header("x-region", "eu");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(response);
+    });
+    let download = server.mock(|when, then| {
+        when.method("GET")
+            .path(format!("/v1/artifact/{handle}"))
+            .header("x-greentic-tenant-id", "acme")
+            .header("x-greentic-environment-id", "staging")
+            .header("x-gateway", "canary")
+            .header("x-region", "eu");
+        then.status(200).body(wasm);
+    });
+
+    let cache_dir = run_component_add(
+        "component://acme/gateway@1.0.0",
+        None,
+        &[parse_header("X-Gateway: canary")?],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )?;
+    resolve.assert();
+    download.assert();
+    assert_eq!(fs::read(cache_dir.join("artifact.wasm"))?, wasm);
+    Ok(())
+}
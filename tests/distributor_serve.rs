@@ -0,0 +1,193 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use greentic_dev::component_add::run_component_add;
+use greentic_dev::distributor_server::{DistributorServer, LocalRegistry};
+use greentic_dev::pack_init::{PackInitIntent, WorkspaceManifest, run};
+use once_cell::sync::Lazy;
+use tempfile::tempdir;
+
+static ENV_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+fn write(path: &Path, bytes: &[u8]) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, bytes).unwrap();
+}
+
+fn gtpack() -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(&mut data));
+    let opts = zip::write::FileOptions::<()>::default();
+    zip.start_file("flows/main.ygtc", opts).unwrap();
+    zip.write_all(b"flow").unwrap();
+    zip.finish().unwrap();
+    data
+}
+
+/// Start a server for `root` on an ephemeral port and return its base URL.
+fn serve(root: &Path, token: Option<&str>) -> Result<String> {
+    let registry = LocalRegistry::open(root, token.map(str::to_string))?;
+    let server = DistributorServer::bind(registry, "127.0.0.1:0")?;
+    let url = format!("http://{}", server.local_addr()?);
+    std::thread::spawn(move || server.serve());
+    Ok(url)
+}
+
+fn point_config_at(home: &Path, url: &str) {
+    let path = home.join(".greentic/config.toml");
+    write(
+        &path,
+        format!("[distributor.default]\nurl = \"{url}\"\ntenant_id = \"local\"\n").as_bytes(),
+    );
+    unsafe { std::env::set_var("GREENTIC_CONFIG", &path) };
+}
+
+#[test]
+fn serves_semver_resolution_downloads_and_licenses() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let registry = tempdir()?;
+    let root = registry.path();
+    for version in ["1.0.0", "1.2.0", "2.0.0"] {
+        write(
+            &root.join(format!("components/greentic/echo/{version}/echo.wasm")),
+            format!("\0asm-echo-{version}").as_bytes(),
+        );
+    }
+    write(
+        &root.join("packs/greentic/demo/0.4.0/demo.gtpack"),
+        &gtpack(),
+    );
+    write(
+        &root.join("components/acme/pro/1.0.0/pro.wasm"),
+        b"\0asm-pro",
+    );
+    write(
+        &root.join("components/acme/pro/license.toml"),
+        b"license_type = \"commercial\"\nid = \"acme-pro\"\ncheckout_url = \"https://example.com/buy\"\nentitled_tenants = [\"acme\"]\n",
+    );
+    let url = serve(root, None)?;
+
+    let home = tempdir()?;
+    unsafe { std::env::set_var("HOME", home.path()) };
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", home.path().join("cache")) };
+    unsafe { std::env::remove_var("GREENTIC_DEV_OFFLINE") };
+    unsafe { std::env::remove_var("GREENTIC_DEV_RESOLVE_STUB") };
+    point_config_at(home.path(), &url);
+    let workspace = tempdir()?;
+    std::env::set_current_dir(workspace.path())?;
+
+    // Newest version matching the requirement, verified against the served digest.
+    let cache_dir = run_component_add(
+        "component://greentic/echo@^1",
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )?;
+    assert_eq!(
+        fs::read(cache_dir.join("artifact.wasm"))?,
+        b"\0asm-echo-1.2.0"
+    );
+    let manifest = WorkspaceManifest::load(&workspace.path().join(".greentic/manifest.json"))?;
+    assert_eq!(
+        manifest
+            .component("echo")
+            .unwrap()
+            .entry
+            .version
+            .to_string(),
+        "1.2.0"
+    );
+
+    run("pack://greentic/demo", None)?;
+    assert_eq!(
+        fs::read_to_string(workspace.path().join("demo/flows/main.ygtc"))?,
+        "flow"
+    );
+
+    // Commercial artifacts answer 402 unless the tenant is entitled.
+    let err = run_component_add(
+        "component://acme/pro@1",
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )
+    .unwrap_err();
+    let message = format!("{err:#}");
+    assert!(
+        message.contains("license required") && message.contains("https://example.com/buy"),
+        "unexpected error: {message}"
+    );
+    run_component_add(
+        "component://acme/pro@1",
+        None,
+        &[("x-greentic-tenant-id".into(), "acme".into())],
+        &["acme-pro".into()],
+        PackInitIntent::Dev,
+        false,
+    )?;
+
+    // Downloads honour Range requests so interrupted transfers can resume.
+    let http = reqwest::blocking::Client::new();
+    let partial = http
+        .get(format!("{url}/v1/artifact/components/greentic/echo/2.0.0"))
+        .header("range", "bytes=5-")
+        .send()?;
+    assert_eq!(partial.status().as_u16(), 206);
+    assert_eq!(partial.bytes()?.as_ref(), b"echo-2.0.0");
+    let missing = http
+        .get(format!("{url}/v1/artifact/components/greentic/echo/9.9.9"))
+        .send()?;
+    assert_eq!(missing.status().as_u16(), 404);
+    Ok(())
+}
+
+#[test]
+fn requires_configured_token() -> Result<()> {
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let registry = tempdir()?;
+    write(
+        &registry
+            .path()
+            .join("components/greentic/echo/1.0.0/echo.wasm"),
+        b"\0asm",
+    );
+    let url = serve(registry.path(), Some("s3cret"))?;
+    let http = reqwest::blocking::Client::new();
+    let body = serde_json::json!({
+        "coordinate": "component://greentic/echo",
+        "intent": "dev",
+        "platform": null,
+        "features": []
+    });
+    let denied = http.post(format!("{url}/v1/resolve")).json(&body).send()?;
+    assert_eq!(denied.status().as_u16(), 401);
+    let allowed = http
+        .post(format!("{url}/v1/resolve"))
+        .bearer_auth("s3cret")
+        .json(&body)
+        .send()?;
+    assert_eq!(allowed.status().as_u16(), 200);
+    let resolved: serde_json::Value = allowed.json()?;
+    assert_eq!(resolved["version"], "1.0.0");
+    assert_eq!(
+        resolved["artifact_download_path"],
+        "/v1/artifact/components/greentic/echo/1.0.0"
+    );
+    Ok(())
+}
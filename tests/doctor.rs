@@ -0,0 +1,136 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use assert_cmd::Command;
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_dev::distributor_server::{DistributorServer, LocalRegistry};
+use serde_json::Value;
+use tempfile::tempdir;
+
+fn doctor(root: &Path, base_url: &str) -> Command {
+    let config = root.join("config.toml");
+    fs::write(
+        &config,
+        format!(
+            "[distributor.profiles.default]\nbase_url = \"{base_url}\"\ntenant_id = \"local\"\n"
+        ),
+    )
+    .unwrap();
+    let mut cmd = cargo_bin_cmd!("greentic-dev");
+    cmd.current_dir(root)
+        .env("GREENTIC_DEV_CONFIG_FILE", &config)
+        .env("GREENTIC_DEV_CACHE_DIR", root.join("cache"))
+        .env_remove("GREENTIC_DEV_RESOLVE_STUB")
+        .env_remove("GREENTIC_DEV_OFFLINE")
+        .env_remove("GREENTIC_DISTRIBUTOR_PROFILE")
+        .args(["doctor", "--json"]);
+    cmd
+}
+
+fn check<'a>(report: &'a Value, name: &str) -> &'a Value {
+    report
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|check| check["name"] == name)
+        .unwrap_or_else(|| panic!("no `{name}` check in {report}"))
+}
+
+#[test]
+fn doctor_reports_config_distributor_and_cache_checks() -> Result<()> {
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+    let dir = tempdir()?;
+    let root = dir.path();
+    let registry = tempdir()?;
+    let server =
+        DistributorServer::bind(LocalRegistry::open(registry.path(), None)?, "127.0.0.1:0")?;
+    let url = format!("http://{}", server.local_addr()?);
+    std::thread::spawn(move || server.serve());
+
+    // Toolchain checks depend on the host, so only the report is asserted, not the exit code.
+    let output = doctor(root, &url).output()?;
+    let report: Value = serde_json::from_slice(&output.stdout)?;
+
+    assert_eq!(check(&report, "config")["status"], "pass");
+    let profile = check(&report, "distributor profile");
+    assert_eq!(profile["status"], "pass");
+    assert!(profile["detail"].as_str().unwrap().contains(&url));
+    assert_eq!(check(&report, "distributor")["status"], "pass");
+
+    let cache = check(&report, "cache");
+    assert_eq!(cache["status"], "warn");
+    assert!(cache["fix"].as_str().unwrap().contains("mkdir -p"));
+    for name in [
+        "rust toolchain",
+        "wasm32-wasip2",
+        "cargo-component",
+        "greentic-flow",
+    ] {
+        check(&report, name);
+    }
+
+    fs::create_dir_all(root.join("cache"))?;
+    let output = doctor(root, &url).output()?;
+    let report: Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(check(&report, "cache")["status"], "pass");
+    Ok(())
+}
+
+#[test]
+fn doctor_fails_when_the_distributor_is_unreachable() -> Result<()> {
+    let dir = tempdir()?;
+    let root = dir.path();
+    // Bind and drop a listener so the port is closed.
+    let Ok(listener) = std::net::TcpListener::bind("127.0.0.1:0") else {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    };
+    let port = listener.local_addr()?.port();
+    drop(listener);
+
+    let output = doctor(root, &format!("http://127.0.0.1:{port}")).output()?;
+    assert!(!output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout)?;
+    let distributor = check(&report, "distributor");
+    assert_eq!(distributor["status"], "fail");
+    assert!(
+        distributor["detail"]
+            .as_str()
+            .unwrap()
+            .contains("distributor serve")
+    );
+    Ok(())
+}
+
+#[test]
+fn doctor_warns_about_the_profile_without_a_config_file() -> Result<()> {
+    let dir = tempdir()?;
+    let root = dir.path();
+    let output = cargo_bin_cmd!("greentic-dev")
+        .current_dir(root)
+        .env("HOME", root)
+        .env("XDG_CONFIG_HOME", root.join("xdg"))
+        .env("GREENTIC_DEV_CACHE_DIR", root.join("cache"))
+        .env_remove("GREENTIC_DEV_CONFIG_FILE")
+        .env_remove("GREENTIC_DEV_RESOLVE_STUB")
+        .env_remove("GREENTIC_DEV_OFFLINE")
+        .env_remove("GREENTIC_DISTRIBUTOR_PROFILE")
+        .args(["doctor", "--json"])
+        .output()?;
+    let report: Value = serde_json::from_slice(&output.stdout)?;
+
+    let profile = check(&report, "distributor profile");
+    assert_eq!(profile["status"], "warn");
+    assert!(
+        profile["detail"]
+            .as_str()
+            .unwrap()
+            .contains("config set distributor.profiles.default.base_url")
+    );
+    assert_eq!(check(&report, "distributor")["status"], "warn");
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use greentic_dev::artifact_store::ArtifactStore;
+use greentic_dev::distributor::DistributorProfile;
+use greentic_dev::download::Downloader;
+use sha2::{Digest, Sha256};
+use tempfile::tempdir;
+
+type Requests = Arc<Mutex<Vec<String>>>;
+
+/// Serve one scripted raw HTTP response per connection, recording each request head.
+fn serve(responses: Vec<Vec<u8>>) -> Option<(String, Requests)> {
+    let listener = TcpListener::bind("127.0.0.1:0").ok()?;
+    let url = format!("http://{}", listener.local_addr().ok()?);
+    let requests: Requests = Arc::default();
+    let recorded = requests.clone();
+    thread::spawn(move || {
+        for response in responses {
+            let Ok((mut stream, _)) = listener.accept() else {
+                return;
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line.to_ascii_lowercase());
+            }
+            recorded.lock().unwrap().push(head);
+            let _ = stream.write_all(&response);
+        }
+    });
+    Some((url, requests))
+}
+
+fn response(status: &str, headers: &[String], body: &[u8]) -> Vec<u8> {
+    let mut out = format!("HTTP/1.1 {status}\r\nConnection: close\r\n");
+    for header in headers {
+        out.push_str(header);
+        out.push_str("\r\n");
+    }
+    out.push_str("\r\n");
+    let mut out = out.into_bytes();
+    out.extend_from_slice(body);
+    out
+}
+
+fn payload() -> Vec<u8> {
+    (0..200_000u32).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+fn has_partial_files(store: &ArtifactStore) -> Result<bool> {
+    Ok(store.partial_dir().read_dir()?.any(|entry| {
+        entry.is_ok_and(|entry| entry.path().extension().is_some_and(|ext| ext == "part"))
+    }))
+}
+
+#[test]
+fn retries_and_resumes_interrupted_downloads() -> Result<()> {
+    let body = payload();
+    let half = body.len() / 2;
+    let total = body.len();
+    let Some((url, requests)) = serve(vec![
+        response(
+            "503 Service Unavailable",
+            &["Content-Length: 4".into()],
+            b"busy",
+        ),
+        // Announces the full body but the connection drops halfway through.
+        response(
+            "200 OK",
+            &[format!("Content-Length: {total}")],
+            &body[..half],
+        ),
+        response(
+            "206 Partial Content",
+            &[
+                format!("Content-Range: bytes {half}-{}/{total}", total - 1),
+                format!("Content-Length: {}", total - half),
+            ],
+            &body[half..],
+        ),
+    ]) else {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    };
+
+    let cache = tempdir()?;
+    let store = ArtifactStore::at(cache.path());
+    let digest = format!("sha256:{:x}", Sha256::digest(&body));
+    let profile = DistributorProfile {
+        name: "default".into(),
+        url: url.clone(),
+        token: Some("secret".into()),
+        tenant_id: "local".into(),
+        environment_id: "dev".into(),
+        headers: Some(HashMap::from([("x-tenant".into(), "acme".into())])),
+    };
+    let stored = Downloader::for_profile(&profile)?.fetch_into_store(
+        &format!("{url}/v1/artifact/big"),
+        Some(&digest),
+        &store,
+        "artifact.wasm",
+    )?;
+
+    assert_eq!(stored.digest, digest);
+    assert_eq!(std::fs::read(&stored.path)?, body);
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 3);
+    assert!(
+        requests
+            .iter()
+            .all(|head| head.contains("authorization: bearer secret"))
+    );
+    assert!(requests.iter().all(|head| head.contains("x-tenant: acme")));
+    assert!(!requests[1].contains("range:"));
+    assert!(requests[2].contains(&format!("range: bytes={half}-")));
+    assert!(!has_partial_files(&store)?);
+    Ok(())
+}
+
+#[test]
+fn resumes_downloads_whose_connection_stalls() -> Result<()> {
+    let body = payload();
+    let half = body.len() / 2;
+    let total = body.len();
+    let Ok(listener) = TcpListener::bind("127.0.0.1:0") else {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    };
+    let url = format!("http://{}", listener.local_addr()?);
+    let requests: Requests = Arc::default();
+    let recorded = requests.clone();
+    let served = body.clone();
+    thread::spawn(move || {
+        // The first connection sends half the body and then goes quiet without closing.
+        let mut stalled = None;
+        for attempt in 0..2 {
+            let Ok((mut stream, _)) = listener.accept() else {
+                return;
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line.to_ascii_lowercase());
+            }
+            recorded.lock().unwrap().push(head);
+            if attempt == 0 {
+                let _ = stream.write_all(&response(
+                    "200 OK",
+                    &[format!("Content-Length: {total}")],
+                    &served[..half],
+                ));
+                stalled = Some(stream);
+            } else {
+                let _ = stream.write_all(&response(
+                    "206 Partial Content",
+                    &[
+                        format!("Content-Range: bytes {half}-{}/{total}", total - 1),
+                        format!("Content-Length: {}", total - half),
+                    ],
+                    &served[half..],
+                ));
+            }
+        }
+        drop(stalled);
+    });
+
+    let cache = tempdir()?;
+    let store = ArtifactStore::at(cache.path());
+    let digest = format!("sha256:{:x}", Sha256::digest(&body));
+    let stored = Downloader::anonymous()?
+        .with_read_timeout(Duration::from_millis(300))?
+        .fetch_into_store(
+            &format!("{url}/v1/artifact/stalled"),
+            Some(&digest),
+            &store,
+            "artifact.wasm",
+        )?;
+
+    assert_eq!(std::fs::read(&stored.path)?, body);
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].contains(&format!("range: bytes={half}-")));
+    Ok(())
+}
+
+#[test]
+fn verifies_blake3_and_rejects_mismatches_without_retrying() -> Result<()> {
+    let body = payload();
+    let length = format!("Content-Length: {}", body.len());
+    let Some((url, requests)) = serve(vec![
+        response("200 OK", std::slice::from_ref(&length), &body),
+        response("200 OK", std::slice::from_ref(&length), &body),
+        response("404 Not Found", &["Content-Length: 7".into()], b"missing"),
+    ]) else {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    };
+
+    let cache = tempdir()?;
+    let store = ArtifactStore::at(cache.path());
+    let downloader = Downloader::anonymous()?;
+    let blake3 = format!("blake3:{}", blake3::hash(&body).to_hex());
+    let stored = downloader.fetch_into_store(&url, Some(&blake3), &store, "bundle.gtpack")?;
+    assert_eq!(std::fs::read(&stored.path)?, body);
+
+    let wrong = format!("sha256:{:x}", Sha256::digest(b"something else"));
+    let err = downloader
+        .fetch_into_store(&url, Some(&wrong), &store, "artifact.wasm")
+        .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("digest mismatch"),
+        "unexpected error: {err:#}"
+    );
+    assert!(!has_partial_files(&store)?);
+
+    let err = downloader
+        .fetch_into_store(&url, None, &store, "artifact.wasm")
+        .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("404"),
+        "unexpected error: {err:#}"
+    );
+    assert_eq!(requests.lock().unwrap().len(), 3);
+    Ok(())
+}
+
+#[test]
+fn concurrent_fetches_of_one_digest_download_once() -> Result<()> {
+    let body = payload();
+    // Only one response is served; a second download would fail to connect.
+    let Some((url, requests)) = serve(vec![response(
+        "200 OK",
+        &[format!("Content-Length: {}", body.len())],
+        &body,
+    )]) else {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    };
+
+    let cache = tempdir()?;
+    let digest = format!("sha256:{:x}", Sha256::digest(&body));
+    let fetches: Vec<_> = (0..2)
+        .map(|_| {
+            let store = ArtifactStore::at(cache.path());
+            let (url, digest) = (url.clone(), digest.clone());
+            thread::spawn(move || {
+                Downloader::anonymous()?.fetch_into_store(
+                    &url,
+                    Some(&digest),
+                    &store,
+                    "artifact.wasm",
+                )
+            })
+        })
+        .collect();
+    for fetch in fetches {
+        let stored = fetch.join().unwrap()?;
+        assert_eq!(std::fs::read(&stored.path)?, body);
+    }
+    assert_eq!(requests.lock().unwrap().len(), 1);
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use greentic_dev::component_add::run_component_add;
+use greentic_dev::distributor_server::{DistributorServer, LocalRegistry};
+use greentic_dev::pack_init::{PackInitIntent, WorkspaceManifest};
+use once_cell::sync::Lazy;
+use tempfile::tempdir;
+
+static ENV_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+fn write(path: &Path, bytes: &[u8]) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, bytes).unwrap();
+}
+
+#[test]
+fn trial_components_require_recorded_acceptance() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let registry = tempdir()?;
+    write(
+        &registry
+            .path()
+            .join("components/acme/trial/1.0.0/trial.wasm"),
+        b"\0asm-trial",
+    );
+    write(
+        &registry.path().join("components/acme/trial/license.toml"),
+        b"license_type = \"trial\"\nid = \"acme-trial-30d\"\nrequires_acceptance = true\nterms = \"Evaluation use only.\"\ncheckout_url = \"https://example.com/trial\"\n",
+    );
+    let server =
+        DistributorServer::bind(LocalRegistry::open(registry.path(), None)?, "127.0.0.1:0")?;
+    let url = format!("http://{}", server.local_addr()?);
+    std::thread::spawn(move || server.serve());
+
+    let home = tempdir()?;
+    let config_path = home.path().join(".greentic/config.toml");
+    write(
+        &config_path,
+        format!("[distributor.default]\nurl = \"{url}\"\ntenant_id = \"local\"\n").as_bytes(),
+    );
+    unsafe { std::env::set_var("HOME", home.path()) };
+    unsafe { std::env::set_var("GREENTIC_CONFIG", &config_path) };
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", home.path().join("cache")) };
+    unsafe { std::env::set_var("CI", "1") };
+    unsafe { std::env::remove_var("GREENTIC_DEV_OFFLINE") };
+    unsafe { std::env::remove_var("GREENTIC_DEV_RESOLVE_STUB") };
+    let workspace = tempdir()?;
+    std::env::set_current_dir(workspace.path())?;
+    let manifest_path = workspace.path().join(".greentic/manifest.json");
+
+    let err = run_component_add(
+        "component://acme/trial@1",
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )
+    .unwrap_err();
+    let message = format!("{err:#}");
+    assert!(
+        message.contains("--accept-license acme-trial-30d")
+            && message.contains("https://example.com/trial"),
+        "unexpected error: {message}"
+    );
+    assert!(
+        WorkspaceManifest::load(&manifest_path)?
+            .components
+            .is_empty()
+    );
+
+    run_component_add(
+        "component://acme/trial@1",
+        None,
+        &[],
+        &["acme-trial-30d".into()],
+        PackInitIntent::Dev,
+        false,
+    )?;
+    let manifest = WorkspaceManifest::load(&manifest_path)?;
+    let license = manifest
+        .component("trial")
+        .and_then(|component| component.license.clone())
+        .expect("license recorded");
+    assert_eq!(license.license_type, "trial");
+    assert_eq!(license.id, "acme-trial-30d");
+    assert!(license.is_accepted());
+
+    // A recorded acceptance is reused without passing the flag again.
+    run_component_add(
+        "component://acme/trial@1",
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )?;
+    let manifest = WorkspaceManifest::load(&manifest_path)?;
+    assert_eq!(
+        manifest.component("trial").unwrap().license.as_ref(),
+        Some(&license)
+    );
+    unsafe { std::env::remove_var("CI") };
+    Ok(())
+}
@@ -0,0 +1,317 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use base64::Engine;
+use greentic_dev::component_add::run_component_add;
+use greentic_dev::lockfile::WorkspaceLock;
+use greentic_dev::oci::{OciArtifactKind, run_push};
+use greentic_dev::pack_init::{PackInitIntent, WorkspaceManifest};
+use httpmock::MockServer;
+use once_cell::sync::Lazy;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use tempfile::tempdir;
+
+static ENV_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+const WASM: &[u8] = b"\0asm\x0d\x00\x01\x00component";
+const CONFIG: &[u8] = b"{}";
+const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+const WASM_LAYER_MEDIA_TYPE: &str = "application/vnd.wasm.component.v1+wasm";
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(bytes))
+}
+
+fn image_manifest(layer_digest: &str) -> String {
+    json!({
+        "schemaVersion": 2,
+        "mediaType": MANIFEST_MEDIA_TYPE,
+        "config": {
+            "mediaType": "application/vnd.greentic.component.config.v1+json",
+            "digest": sha256(CONFIG),
+            "size": CONFIG.len()
+        },
+        "layers": [{
+            "mediaType": WASM_LAYER_MEDIA_TYPE,
+            "digest": layer_digest,
+            "size": WASM.len()
+        }]
+    })
+    .to_string()
+}
+
+/// Point greentic-dev at a config that talks plain HTTP to the stand-in registry.
+fn setup(home: &Path, registry: &str, credentials: &str) {
+    let config_dir = home.join(".greentic");
+    fs::create_dir_all(&config_dir).unwrap();
+    let path = config_dir.join("config.toml");
+    fs::write(
+        &path,
+        format!("[oci.registries.\"{registry}\"]\ninsecure = true\n{credentials}"),
+    )
+    .unwrap();
+    unsafe { std::env::set_var("GREENTIC_CONFIG", &path) };
+    unsafe { std::env::set_var("HOME", home) };
+    unsafe { std::env::set_var("GREENTIC_DEV_CACHE_DIR", home.join("cache")) };
+    unsafe { std::env::remove_var("GREENTIC_DEV_OFFLINE") };
+    unsafe { std::env::remove_var("GREENTIC_DEV_RESOLVE_STUB") };
+}
+
+#[test]
+fn component_add_pulls_from_oci_registry() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let server = MockServer::start();
+    let registry = server.address().to_string();
+    let home = tempdir()?;
+    setup(
+        home.path(),
+        &registry,
+        "username = \"ci\"\npassword = \"env:GREENTIC_TEST_OCI_PASSWORD\"\n",
+    );
+    unsafe { std::env::set_var("GREENTIC_TEST_OCI_PASSWORD", "s3cret") };
+    let workspace = tempdir()?;
+    std::env::set_current_dir(workspace.path())?;
+
+    let basic = format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode("ci:s3cret")
+    );
+    server.mock(|when, then| {
+        when.method("GET").path("/v2/");
+        then.status(401)
+            .header("WWW-Authenticate", "Basic realm=\"stand-in\"");
+    });
+    let layer_digest = sha256(WASM);
+    let manifest = server.mock(|when, then| {
+        when.method("GET")
+            .path("/v2/greentic/echo/manifests/1.0.0")
+            .header("authorization", &basic);
+        then.status(200)
+            .header("content-type", MANIFEST_MEDIA_TYPE)
+            .body(image_manifest(&layer_digest));
+    });
+    server.mock(|when, then| {
+        when.method("GET")
+            .path(format!("/v2/greentic/echo/blobs/{}", sha256(CONFIG)));
+        then.status(200).body(CONFIG);
+    });
+    let blob = server.mock(|when, then| {
+        when.method("GET")
+            .path(format!("/v2/greentic/echo/blobs/{layer_digest}"))
+            .header("authorization", &basic);
+        then.status(200).body(WASM);
+    });
+
+    let coordinate = format!("oci://{registry}/greentic/echo:1.0.0");
+    let cache_dir = run_component_add(&coordinate, None, &[], &[], PackInitIntent::Dev, false)?;
+    manifest.assert();
+    blob.assert();
+    assert_eq!(fs::read(cache_dir.join("artifact.wasm"))?, WASM);
+
+    let recorded = WorkspaceManifest::load(&workspace.path().join(".greentic/manifest.json"))?;
+    let component = recorded.component("echo").expect("component recorded");
+    assert_eq!(component.coordinate, coordinate);
+    assert_eq!(component.entry.version.to_string(), "1.0.0");
+    let lock = WorkspaceLock::load(&workspace.path().join("greentic.lock"))?;
+    let locked = lock.component("echo").expect("component locked");
+    assert_eq!(locked.digest, layer_digest);
+    assert_eq!(locked.profile, None);
+
+    // A registry serving bytes that do not match the manifest descriptor is rejected.
+    server.mock(|when, then| {
+        when.method("GET")
+            .path("/v2/greentic/tampered/manifests/1.0.0");
+        then.status(200)
+            .header("content-type", MANIFEST_MEDIA_TYPE)
+            .body(image_manifest(&layer_digest));
+    });
+    server.mock(|when, then| {
+        when.method("GET")
+            .path(format!("/v2/greentic/tampered/blobs/{}", sha256(CONFIG)));
+        then.status(200).body(CONFIG);
+    });
+    server.mock(|when, then| {
+        when.method("GET")
+            .path(format!("/v2/greentic/tampered/blobs/{layer_digest}"));
+        then.status(200).body("\0asm-tampered");
+    });
+    let err = run_component_add(
+        &format!("oci://{registry}/greentic/tampered:1.0.0"),
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("layer digest mismatch"),
+        "unexpected error: {err:#}"
+    );
+    Ok(())
+}
+
+#[test]
+fn push_uploads_blobs_and_manifest() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let server = MockServer::start();
+    let registry = server.address().to_string();
+    let home = tempdir()?;
+    setup(home.path(), &registry, "");
+
+    let session = "/v2/acme/support/blobs/uploads/session";
+    server.mock(|when, then| {
+        when.method("GET").path("/v2/");
+        then.status(200);
+    });
+    let begin = server.mock(|when, then| {
+        when.method("POST").path("/v2/acme/support/blobs/uploads/");
+        then.status(202).header("Location", session);
+    });
+    server.mock(|when, then| {
+        when.method("PATCH").path(session);
+        then.status(202).header("Location", session);
+    });
+    let pack = b"PK\x03\x04gtpack";
+    let layer = server.mock(|when, then| {
+        when.method("PUT")
+            .path(session)
+            .query_param("digest", sha256(pack));
+        then.status(201).header(
+            "Location",
+            format!("/v2/acme/support/blobs/{}", sha256(pack)),
+        );
+    });
+    let config = server.mock(|when, then| {
+        when.method("PUT")
+            .path(session)
+            .query_param("digest", sha256(CONFIG));
+        then.status(201).header(
+            "Location",
+            format!("/v2/acme/support/blobs/{}", sha256(CONFIG)),
+        );
+    });
+    let manifest = server.mock(|when, then| {
+        when.method("PUT")
+            .path("/v2/acme/support/manifests/0.4.0")
+            .body_includes("application/vnd.greentic.gtpack.v1+zip")
+            .body_includes(sha256(pack));
+        then.status(201)
+            .header("Location", "/v2/acme/support/manifests/0.4.0");
+    });
+
+    let dir = tempdir()?;
+    let artifact = dir.path().join("support.gtpack");
+    fs::write(&artifact, pack)?;
+    let reference = format!("oci://{registry}/acme/support:0.4.0");
+    run_push(&artifact, &reference, OciArtifactKind::Pack)?;
+    begin.assert_calls(2);
+    layer.assert();
+    config.assert();
+    manifest.assert();
+
+    // Publishing something that is not a component is refused before touching the registry.
+    let err = run_push(&artifact, &reference, OciArtifactKind::Component).unwrap_err();
+    assert!(
+        err.to_string().contains("is not a wasm component"),
+        "unexpected error: {err:#}"
+    );
+    begin.assert_calls(2);
+    Ok(())
+}
+
+#[test]
+fn component_add_versions_untagged_and_digest_pinned_references() -> Result<()> {
+    let _guard = ENV_LOCK.lock().unwrap();
+    if std::net::TcpListener::bind("127.0.0.1:0").is_err() {
+        eprintln!("Skipping test; cannot bind local port in this environment");
+        return Ok(());
+    }
+
+    let server = MockServer::start();
+    let registry = server.address().to_string();
+    let home = tempdir()?;
+    setup(home.path(), &registry, "");
+    let workspace = tempdir()?;
+    std::env::set_current_dir(workspace.path())?;
+
+    server.mock(|when, then| {
+        when.method("GET").path("/v2/");
+        then.status(200);
+    });
+    let layer_digest = sha256(WASM);
+    for repo in ["annotated", "pinned"] {
+        server.mock(|when, then| {
+            when.method("GET")
+                .path(format!("/v2/greentic/{repo}/blobs/{}", sha256(CONFIG)));
+            then.status(200).body(CONFIG);
+        });
+        server.mock(|when, then| {
+            when.method("GET")
+                .path(format!("/v2/greentic/{repo}/blobs/{layer_digest}"));
+            then.status(200).body(WASM);
+        });
+    }
+
+    // Untagged references pull `latest`; the version comes from the manifest annotation.
+    let mut annotated: serde_json::Value = serde_json::from_str(&image_manifest(&layer_digest))?;
+    annotated["annotations"] = json!({ "org.opencontainers.image.version": "v2.1.0" });
+    server.mock(|when, then| {
+        when.method("GET")
+            .path("/v2/greentic/annotated/manifests/latest");
+        then.status(200)
+            .header("content-type", MANIFEST_MEDIA_TYPE)
+            .body(annotated.to_string());
+    });
+    run_component_add(
+        &format!("oci://{registry}/greentic/annotated"),
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )?;
+
+    // Digest pins without an annotation lock to a version derived from the layer digest.
+    let manifest = image_manifest(&layer_digest);
+    let manifest_digest = sha256(manifest.as_bytes());
+    server.mock(|when, then| {
+        when.method("GET")
+            .path(format!("/v2/greentic/pinned/manifests/{manifest_digest}"));
+        then.status(200)
+            .header("content-type", MANIFEST_MEDIA_TYPE)
+            .body(&manifest);
+    });
+    run_component_add(
+        &format!("oci://{registry}/greentic/pinned@{manifest_digest}"),
+        None,
+        &[],
+        &[],
+        PackInitIntent::Dev,
+        false,
+    )?;
+
+    let recorded = WorkspaceManifest::load(&workspace.path().join(".greentic/manifest.json"))?;
+    let lock = WorkspaceLock::load(&workspace.path().join("greentic.lock"))?;
+    let digest_version = format!("0.0.0+sha256.{}", &layer_digest["sha256:".len()..][..12]);
+    for (name, version) in [("annotated", "2.1.0"), ("pinned", digest_version.as_str())] {
+        let component = recorded.component(name).expect("component recorded");
+        assert_eq!(component.entry.version.to_string(), version);
+        let locked = lock.component(name).expect("component locked");
+        assert_eq!(locked.version, version);
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::cargo::cargo_bin_cmd;
+use serde_json::Value;
+use tempfile::tempdir;
+
+fn events(stdout: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("every stdout line is a JSON event"))
+        .collect()
+}
+
+#[test]
+fn json_output_streams_events_and_exits_with_the_final_event_code() -> Result<()> {
+    let dir = tempdir()?;
+    let config_path = dir.path().join("config.toml");
+
+    let output = cargo_bin_cmd!("greentic-dev")
+        .current_dir(dir.path())
+        .args([
+            "--output",
+            "json",
+            "config",
+            "set",
+            "defaults.component.org",
+            "acme",
+        ])
+        .arg("--file")
+        .arg(&config_path)
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    let stream = events(&output.stdout);
+    assert_eq!(stream[0]["event"], "started");
+    assert_eq!(stream[0]["command"], "config set");
+    assert!(stream.iter().any(|event| event["event"] == "artifact"
+        && event["kind"] == "config"
+        && event["path"] == config_path.to_str().unwrap()));
+    let last = stream.last().unwrap();
+    assert_eq!(last["event"], "finished");
+    assert_eq!(last["status"], "ok");
+    assert_eq!(last["exit_code"], 0);
+
+    fs::write(&config_path, "colour = \"blue\"\n")?;
+    let output = cargo_bin_cmd!("greentic-dev")
+        .current_dir(dir.path())
+        .args(["--output", "json", "config", "validate", "--file"])
+        .arg(&config_path)
+        .output()?;
+    let stream = events(&output.stdout);
+    assert!(
+        stream
+            .iter()
+            .any(|event| event["event"] == "error" && event["code"] == "GD0501"),
+        "{stream:#?}"
+    );
+    let last = stream.last().unwrap();
+    assert_eq!(last["event"], "finished");
+    assert_eq!(last["status"], "error");
+    assert_eq!(
+        Some(last["exit_code"].as_i64().unwrap() as i32),
+        output.status.code()
+    );
+    Ok(())
+}
+
+#[cfg(not(windows))]
+#[test]
+fn json_output_keeps_passthrough_stdout_out_of_the_event_stream() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir()?;
+    let flow = dir.path().join("greentic-flow");
+    fs::write(&flow, "#!/bin/sh\necho \"flow says $*\"\n")?;
+    fs::set_permissions(&flow, fs::Permissions::from_mode(0o755))?;
+    let config_path = dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        "[hooks.flow]\npre = [\"echo hook says hi\"]\n",
+    )?;
+
+    let output = cargo_bin_cmd!("greentic-dev")
+        .current_dir(dir.path())
+        .env("GREENTIC_DEV_CONFIG_FILE", &config_path)
+        .env("GREENTIC_DEV_BIN_GREENTIC_FLOW", &flow)
+        .env_remove("GREENTIC_DEV_HOOK_PHASE")
+        .args(["--output", "json", "flow", "doctor", "demo.ygtc"])
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    let stream = events(&output.stdout);
+    assert_eq!(stream[0]["event"], "started");
+    assert_eq!(stream.last().unwrap()["event"], "finished");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("hook says hi"), "{stderr}");
+    assert!(stderr.contains("flow says doctor demo.ygtc"), "{stderr}");
+    Ok(())
+}
+
+#[test]
+fn json_output_reports_native_commands_as_events_only() -> Result<()> {
+    let dir = tempdir()?;
+    let run = |args: &[&str]| {
+        cargo_bin_cmd!("greentic-dev")
+            .current_dir(dir.path())
+            .env("HOME", dir.path())
+            .env("GREENTIC_DEV_CACHE_DIR", dir.path().join("cache"))
+            .env("GREENTIC_DEV_CONFIG_FILE", dir.path().join("config.toml"))
+            .args(["--output", "json"])
+            .args(args)
+            .output()
+    };
+
+    for (args, command) in [
+        (&["cache", "ls"][..], "cache ls"),
+        (&["explain"][..], "explain"),
+        (&["explain", "GD0001"][..], "explain"),
+    ] {
+        let output = run(args)?;
+        assert!(output.status.success(), "{output:?}");
+        let stream = events(&output.stdout);
+        assert_eq!(stream[0]["command"], command);
+        assert!(
+            stream.iter().any(|event| event["event"] == "result"),
+            "{stream:#?}"
+        );
+    }
+
+    let output = run(&["component", "remove", "missing"])?;
+    assert!(!output.status.success());
+    let stream = events(&output.stdout);
+    assert_eq!(stream[0]["command"], "component remove");
+    assert_eq!(stream.last().unwrap()["command"], "component remove");
+    Ok(())
+}
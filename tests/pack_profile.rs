@@ -0,0 +1,390 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use greentic_dev::pack_build::{self, PackSigning};
+use greentic_dev::pack_profile::{self, NodeRunStatus};
+use serde_json::json;
+use tempfile::tempdir;
+
+#[test]
+fn profile_reports_manifest_limits_per_node() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let flow_path = root.join("tests/fixtures/hello-pack/hello-flow.ygtc");
+    let component_dir = root.join("fixtures/components");
+
+    let report = pack_profile::profile_flow(&flow_path, Some(component_dir.as_path()))
+        .expect("profile flow");
+
+    let node_ids = report
+        .nodes
+        .iter()
+        .map(|node| node.node_id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(node_ids, ["start", "log"]);
+
+    for node in &report.nodes {
+        assert_eq!(node.component, "dev.greentic.echo");
+        assert_eq!(node.operation, "echo");
+        assert!(node.limits_declared, "fixture manifest declares limits");
+        assert_eq!(node.wall_time_ms.limit, Some(500));
+        assert_eq!(node.memory_mb.limit, Some(32));
+        assert_eq!(node.fuel.limit, Some(100));
+        assert_eq!(node.files.limit, Some(4));
+        if node.status == NodeRunStatus::Ok {
+            assert!(
+                node.wall_time_ms.used.is_some(),
+                "wall time should be measured"
+            );
+        }
+    }
+
+    assert!(
+        report
+            .notes
+            .iter()
+            .any(|note| note.contains("dev.greentic.echo") && note.contains("files")),
+        "the unmetered files limit should be called out: {:?}",
+        report.notes
+    );
+}
+
+#[test]
+fn profile_enforces_wall_time_and_memory_limits() {
+    let dir = tempdir().unwrap();
+    write_spinning_flow(dir.path());
+
+    let report = pack_profile::profile_flow(&dir.path().join("flow.ygtc"), Some(dir.path()))
+        .expect("profile flow");
+    let statuses = report
+        .nodes
+        .iter()
+        .map(|node| (node.node_id.as_str(), &node.status))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        statuses,
+        [
+            ("spin", &NodeRunStatus::Ok),
+            ("grow", &NodeRunStatus::MemoryExceeded),
+            ("hang", &NodeRunStatus::WallTimeExceeded),
+        ],
+        "{report:#?}"
+    );
+    assert!(report.nodes[0].wall_time_ms.used.is_some());
+    assert_eq!(report.nodes[2].wall_time_ms.used, Some(200));
+}
+
+#[cfg(not(windows))]
+#[test]
+fn pack_run_stops_before_the_runner_when_a_node_exceeds_its_limits() {
+    use assert_cmd::cargo::cargo_bin_cmd;
+    use std::os::unix::fs::PermissionsExt;
+
+    // `pack build` only reads flows under the working directory.
+    let dir = tempfile::tempdir_in(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    write_spinning_flow(dir.path());
+    let pack = dir.path().join("spin.gtpack");
+    pack_build::run(
+        &dir.path().join("flow.ygtc"),
+        &pack,
+        PackSigning::Dev,
+        None,
+        Some(dir.path()),
+    )
+    .expect("pack build");
+    let runner = dir.path().join("greentic-runner-cli");
+    fs::write(&runner, "#!/bin/sh\necho runner started\n").unwrap();
+    fs::set_permissions(&runner, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = cargo_bin_cmd!("greentic-dev")
+        .env("GREENTIC_DEV_BIN_GREENTIC_RUNNER_CLI", &runner)
+        .arg("pack")
+        .arg("run")
+        .arg("--pack")
+        .arg(&pack)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "{stdout}\n{stderr}");
+    assert!(!stdout.contains("runner started"), "{stdout}");
+    assert!(stderr.contains("error[GD0107]"), "{stderr}");
+    assert!(
+        stderr.contains("spin/grow (dev.greentic.grow): memory limit exceeded"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("spin/hang (dev.greentic.hang): wall time limit exceeded"),
+        "{stderr}"
+    );
+}
+
+/// A flow of three nodes: one within its limits, one that grows memory past a 1 MiB limit and one
+/// that spins past a 200 ms wall-time limit.
+fn write_spinning_flow(root: &Path) {
+    write_component(
+        root,
+        "dev.greentic.spin",
+        &spinning_component(10_000, 0),
+        5_000,
+    );
+    // 1 initial page plus 20 grown pages (~1.3 MiB).
+    write_component(root, "dev.greentic.grow", &spinning_component(0, 20), 5_000);
+    write_component(
+        root,
+        "dev.greentic.hang",
+        &spinning_component(i32::MAX, 0),
+        200,
+    );
+    fs::write(
+        root.join("flow.ygtc"),
+        "id: spin\ntype: messaging\nstart: spin\nnodes:\n  spin:\n    component.exec:\n      component: \"dev.greentic.spin\"\n      op: \"run\"\n      input: {}\n    routing:\n      - to: grow\n  grow:\n    component.exec:\n      component: \"dev.greentic.grow\"\n      op: \"run\"\n      input: {}\n    routing:\n      - to: hang\n  hang:\n    component.exec:\n      component: \"dev.greentic.hang\"\n      op: \"run\"\n      input: {}\n    routing:\n      - out: true\n",
+    )
+    .unwrap();
+}
+
+fn write_component(root: &Path, id: &str, wasm: &[u8], wall_time_ms: u64) {
+    let dir = root.join(id);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("component.wasm"), wasm).unwrap();
+    let manifest = json!({
+        "id": id,
+        "name": id,
+        "version": "0.1.0",
+        "world": "greentic:component/component@0.5.0",
+        "describe_export": "get-manifest",
+        "supports": ["messaging"],
+        "profiles": { "default": "dev", "supported": ["dev"] },
+        "capabilities": { "wasi": {}, "host": {} },
+        "limits": { "memory_mb": 1, "wall_time_ms": wall_time_ms },
+        "config_schema": {},
+        "operations": [{
+            "name": "run",
+            "input_schema": { "type": "object" },
+            "output_schema": { "type": "object" }
+        }],
+        "artifacts": { "component_wasm": "component.wasm" },
+        "hashes": {
+            "component_wasm": format!("blake3:{}", blake3::hash(wasm).to_hex())
+        }
+    });
+    fs::write(
+        dir.join("component.manifest.json"),
+        serde_json::to_vec_pretty(&manifest).unwrap(),
+    )
+    .unwrap();
+}
+
+/// `greentic:component@0.5.0`, enough of it to encode a component exporting `node`.
+const NODE_WIT: &str = r#"
+package greentic:component@0.5.0;
+
+interface control {
+  should-cancel: func() -> bool;
+  yield-now: func();
+}
+
+interface node {
+  type json = string;
+  record tenant-ctx {
+    tenant: string,
+    team: option<string>,
+    user: option<string>,
+    trace-id: option<string>,
+    i18n-id: option<string>,
+    correlation-id: option<string>,
+    deadline-unix-ms: option<u64>,
+    attempt: u32,
+    idempotency-key: option<string>,
+  }
+  record exec-ctx {
+    tenant: tenant-ctx,
+    i18n-id: option<string>,
+    flow-id: string,
+    node-id: option<string>,
+  }
+  record node-error {
+    code: string,
+    message: string,
+    retryable: bool,
+    backoff-ms: option<u64>,
+    details: option<json>,
+  }
+  variant invoke-result {
+    ok(json),
+    err(node-error),
+  }
+  variant stream-event {
+    data(json),
+    progress(u8),
+    done,
+    error(string),
+  }
+  enum lifecycle-status {
+    ok,
+  }
+  get-manifest: func() -> json;
+  on-start: func(ctx: exec-ctx) -> result<lifecycle-status, string>;
+  on-stop: func(ctx: exec-ctx, reason: string) -> result<lifecycle-status, string>;
+  invoke: func(ctx: exec-ctx, op: string, input: json) -> invoke-result;
+  invoke-stream: func(ctx: exec-ctx, op: string, input: json) -> list<stream-event>;
+}
+
+world component {
+  import control;
+  export node;
+}
+"#;
+
+/// A node component whose `invoke` spins `iterations` times and grows memory by `grow_pages`
+/// before returning `{}`.
+fn spinning_component(iterations: i32, grow_pages: i32) -> Vec<u8> {
+    use wasm_encoder::{
+        CodeSection, ConstExpr, DataSection, ExportKind, ExportSection, Function, FunctionSection,
+        GlobalSection, GlobalType, Instruction as I, MemorySection, MemoryType, Module,
+        TypeSection, ValType,
+    };
+
+    const EMPTY_JSON: i32 = 2048;
+    const INVOKE_OK: i32 = 1024;
+    const STRING_RESULT: i32 = 1100;
+    const ZEROED: i32 = 1200;
+
+    let mut types = TypeSection::new();
+    types.ty().function([], [ValType::I32]);
+    types.ty().function([ValType::I32], [ValType::I32]);
+    types.ty().function([ValType::I32; 4], [ValType::I32]);
+
+    let mut functions = FunctionSection::new();
+    let mut code = CodeSection::new();
+    let mut exports = ExportSection::new();
+    let returning = |value: i32| {
+        let mut body = Function::new([]);
+        body.instruction(&I::I32Const(value)).instruction(&I::End);
+        body
+    };
+
+    functions.function(0);
+    code.function(&returning(STRING_RESULT));
+    exports.export(
+        "greentic:component/node@0.5.0#get-manifest",
+        ExportKind::Func,
+        0,
+    );
+    // Zeroed memory reads as `ok(ok)` for the lifecycle hooks and an empty event list.
+    for (index, name) in [(1, "on-start"), (2, "on-stop"), (3, "invoke-stream")] {
+        functions.function(1);
+        code.function(&returning(ZEROED));
+        exports.export(
+            &format!("greentic:component/node@0.5.0#{name}"),
+            ExportKind::Func,
+            index,
+        );
+    }
+    let mut invoke = Function::new([(1, ValType::I32)]);
+    invoke
+        .instruction(&I::Block(wasm_encoder::BlockType::Empty))
+        .instruction(&I::Loop(wasm_encoder::BlockType::Empty))
+        .instruction(&I::LocalGet(1))
+        .instruction(&I::I32Const(iterations))
+        .instruction(&I::I32GeS)
+        .instruction(&I::BrIf(1))
+        .instruction(&I::LocalGet(1))
+        .instruction(&I::I32Const(1))
+        .instruction(&I::I32Add)
+        .instruction(&I::LocalSet(1))
+        .instruction(&I::Br(0))
+        .instruction(&I::End)
+        .instruction(&I::End)
+        .instruction(&I::I32Const(grow_pages))
+        .instruction(&I::MemoryGrow(0))
+        .instruction(&I::Drop)
+        .instruction(&I::I32Const(INVOKE_OK))
+        .instruction(&I::End);
+    functions.function(1);
+    code.function(&invoke);
+    exports.export("greentic:component/node@0.5.0#invoke", ExportKind::Func, 4);
+    // Bump allocator for arguments the host lowers into the guest.
+    let mut realloc = Function::new([]);
+    realloc
+        .instruction(&I::GlobalGet(0))
+        .instruction(&I::I32Const(7))
+        .instruction(&I::I32Add)
+        .instruction(&I::I32Const(!7))
+        .instruction(&I::I32And)
+        .instruction(&I::GlobalSet(0))
+        .instruction(&I::GlobalGet(0))
+        .instruction(&I::GlobalGet(0))
+        .instruction(&I::LocalGet(3))
+        .instruction(&I::I32Add)
+        .instruction(&I::GlobalSet(0))
+        .instruction(&I::End);
+    functions.function(2);
+    code.function(&realloc);
+    exports.export("cabi_realloc", ExportKind::Func, 5);
+    exports.export("memory", ExportKind::Memory, 0);
+
+    let mut memories = MemorySection::new();
+    memories.memory(MemoryType {
+        minimum: 1,
+        maximum: None,
+        memory64: false,
+        shared: false,
+        page_size_log2: None,
+    });
+    let mut globals = GlobalSection::new();
+    globals.global(
+        GlobalType {
+            val_type: ValType::I32,
+            mutable: true,
+            shared: false,
+        },
+        &ConstExpr::i32_const(4096),
+    );
+    let mut data = DataSection::new();
+    let le = |values: &[i32]| -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    };
+    data.active(0, &ConstExpr::i32_const(INVOKE_OK), le(&[0, EMPTY_JSON, 2]));
+    data.active(
+        0,
+        &ConstExpr::i32_const(STRING_RESULT),
+        le(&[EMPTY_JSON, 2]),
+    );
+    data.active(0, &ConstExpr::i32_const(EMPTY_JSON), b"{}".to_vec());
+
+    let mut module = Module::new();
+    module
+        .section(&types)
+        .section(&functions)
+        .section(&memories)
+        .section(&globals)
+        .section(&exports)
+        .section(&code)
+        .section(&data)
+        // greentic-component only accepts artifacts that mention their wasm32-wasip2 target.
+        .section(&wasm_encoder::CustomSection {
+            name: "target".into(),
+            data: b"wasm32-wasip2".as_slice().into(),
+        });
+    let mut module = module.finish();
+
+    let mut resolve = wit_parser::Resolve::default();
+    let package = resolve.push_str("node.wit", NODE_WIT).unwrap();
+    let world = resolve.select_world(&[package], Some("component")).unwrap();
+    wit_component::embed_component_metadata(
+        &mut module,
+        &resolve,
+        world,
+        wit_component::StringEncoding::UTF8,
+    )
+    .unwrap();
+    wit_component::ComponentEncoder::default()
+        .module(&module)
+        .unwrap()
+        .validate(true)
+        .encode()
+        .unwrap()
+}
@@ -0,0 +1,76 @@
+#![cfg(not(windows))]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::str::contains;
+use tempfile::tempdir;
+
+fn write_script(path: &Path, body: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    fs::write(path, format!("#!/bin/sh\n{body}\n")).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_path_buf()
+}
+
+#[test]
+fn hooks_run_around_passthrough_and_failing_pre_hook_aborts() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let log = root.join("log.txt");
+    let pack = write_script(
+        &root.join("greentic-pack"),
+        &format!("echo \"pack $*\" >> {}\nexit 3", log.display()),
+    );
+    fs::create_dir(root.join(".greentic")).unwrap();
+    fs::write(
+        root.join(".greentic/config.toml"),
+        format!(
+            r#"
+[hooks.pack]
+pre = ["echo \"any $GREENTIC_DEV_HOOK_PHASE $GREENTIC_DEV_HOOK_COMMAND\" >> {log}"]
+
+[hooks."pack build"]
+pre = ["echo \"pre $GREENTIC_DEV_HOOK_ARGS $GREENTIC_DEV_HOOK_ARGS_JSON\" >> {log}"]
+post = ["echo \"post $GREENTIC_DEV_HOOK_EXIT_STATUS\" >> {log}"]
+
+[hooks."pack lint"]
+pre = ["exit 7"]
+"#,
+            log = log.display()
+        ),
+    )
+    .unwrap();
+    fs::write(root.join("user.toml"), "").unwrap();
+    let greentic_dev = || {
+        let mut cmd = cargo_bin_cmd!("greentic-dev");
+        cmd.current_dir(root)
+            .env("GREENTIC_DEV_CONFIG_FILE", root.join("user.toml"))
+            .env("GREENTIC_DEV_BIN_GREENTIC_PACK", &pack)
+            .env_remove("GREENTIC_DEV_HOOK_PHASE");
+        cmd
+    };
+
+    greentic_dev()
+        .args(["pack", "build", "--in", "demo"])
+        .assert()
+        .code(3);
+    assert_eq!(
+        fs::read_to_string(&log).unwrap(),
+        "any pre pack\n\
+         pre build --in demo [\"build\",\"--in\",\"demo\"]\n\
+         pack build --in demo\n\
+         post 3\n"
+    );
+
+    fs::remove_file(&log).unwrap();
+    greentic_dev()
+        .args(["pack", "lint"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "pre hook `exit 7` for `pack lint` exited with code 7",
+        ));
+    assert_eq!(fs::read_to_string(&log).unwrap(), "any pre pack\n");
+}
@@ -0,0 +1,109 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use assert_cmd::Command;
+use assert_cmd::cargo::cargo_bin_cmd;
+use serde_json::Value;
+use tempfile::tempdir;
+
+fn write_stub(dir: &Path, name: &str, output: &str) -> PathBuf {
+    #[cfg(windows)]
+    let path = dir.join(format!("{name}.cmd"));
+    #[cfg(not(windows))]
+    let path = dir.join(name);
+
+    #[cfg(windows)]
+    let script = format!("@echo {output}\r\n");
+    #[cfg(not(windows))]
+    let script = format!("#!/bin/sh\necho \"{output}\"\n");
+
+    fs::write(&path, script).unwrap();
+
+    #[cfg(not(windows))]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    path
+}
+
+#[test]
+fn tools_doctor_checks_requirements_and_pin_records_versions() -> Result<()> {
+    let dir = tempdir()?;
+    let root = dir.path();
+    let flow = write_stub(root, "greentic-flow", "greentic-flow 0.4.2");
+    let pack = write_stub(root, "greentic-pack", "greentic-pack v1.0.0 (abc123)");
+    let component = write_stub(root, "greentic-component", "greentic-component 0.3.1");
+    let runner = write_stub(root, "greentic-runner-cli", "greentic-runner-cli 0.2.0");
+    let gui = write_stub(root, "greentic-gui", "greentic-gui dev build");
+
+    let config_path = root.join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+[tools.greentic-pack]
+version = "^2"
+
+[tools.greentic-gui]
+path = "{}"
+"#,
+            gui.display().to_string().replace('\\', "\\\\")
+        ),
+    )?;
+    let greentic_dev = |config: &Path| -> Command {
+        let mut cmd = cargo_bin_cmd!("greentic-dev");
+        cmd.current_dir(root)
+            .env("GREENTIC_DEV_CONFIG_FILE", config)
+            .env("GREENTIC_DEV_BIN_GREENTIC_FLOW", &flow)
+            .env("GREENTIC_DEV_BIN_GREENTIC_PACK", &pack)
+            .env("GREENTIC_DEV_BIN_GREENTIC_COMPONENT", &component)
+            .env("GREENTIC_DEV_BIN_GREENTIC_RUNNER_CLI", &runner)
+            .env_remove("GREENTIC_DEV_BIN_GREENTIC_GUI");
+        cmd
+    };
+
+    let output = greentic_dev(&config_path)
+        .args(["tools", "doctor", "--json"])
+        .output()?;
+    assert!(!output.status.success());
+    let report: Vec<Value> = serde_json::from_slice(&output.stdout)?;
+    let status = |name: &str| {
+        report
+            .iter()
+            .find(|tool| tool["name"] == name)
+            .map(|tool| (tool["version"].clone(), tool["status"].clone()))
+            .unwrap()
+    };
+    assert_eq!(status("greentic-flow"), ("0.4.2".into(), "ok".into()));
+    assert_eq!(status("greentic-pack"), ("1.0.0".into(), "mismatch".into()));
+    assert_eq!(status("greentic-gui"), (Value::Null, "ok".into()));
+
+    let pins = root.join("pins.toml");
+    let output = greentic_dev(&config_path)
+        .args(["tools", "pin", "--file"])
+        .arg(&pins)
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    let pinned = fs::read_to_string(&pins)?;
+    assert!(
+        pinned.contains("[tools.greentic-flow]\nversion = \"=0.4.2\""),
+        "{pinned}"
+    );
+    assert!(pinned.contains("version = \"=1.0.0\""), "{pinned}");
+    assert!(!pinned.contains("greentic-gui"), "{pinned}");
+    assert!(String::from_utf8(output.stderr)?.contains("skipping greentic-gui"));
+
+    greentic_dev(&pins)
+        .args(["tools", "doctor"])
+        .assert()
+        .success();
+    let named = greentic_dev(&config_path)
+        .args(["tools", "pin", "greentic-gui", "--file"])
+        .arg(&pins)
+        .output()?;
+    assert!(!named.status.success());
+    Ok(())
+}